@@ -9,11 +9,13 @@ public protocol PolisherProtocol: Sendable {
 // MARK: - Polisher Mode
 public enum PolisherMode: String, Sendable, CaseIterable {
     case cloud = "cloud"        // 雲端 API (統一策略)
+    case local = "local"        // 本地規則（Rust 端 pipeline，不需網路）
     case transcriptOnly = "none" // 只轉錄，不潤飾
 
     public var displayName: String {
         switch self {
         case .cloud: return "雲端 AI（需網路）"
+        case .local: return "本地規則（離線）"
         case .transcriptOnly: return "只轉錄"
         }
     }
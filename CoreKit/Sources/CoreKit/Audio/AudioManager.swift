@@ -18,6 +18,9 @@ public final class AudioManager: ObservableObject {
     private let bufferLock = NSLock()  // 保護 recordingBuffer 的鎖
     private var recordingCompletion: ((Data?) -> Void)?
 
+    /// 每個 16kHz mono PCM 區塊的回呼（在音訊執行緒呼叫），供 helper 串流給其他程序
+    public nonisolated(unsafe) var onSamples: (@Sendable ([Float]) -> Void)?
+
     /// Whisper 期望的取樣率
     private let targetSampleRate: Double = 16000
 
@@ -143,6 +146,7 @@ public final class AudioManager: ObservableObject {
         bufferLock.lock()
         recordingBuffer.append(contentsOf: samples)
         bufferLock.unlock()
+        onSamples?(samples)
 
         let level = rms
         Task { @MainActor in
@@ -6,6 +6,8 @@ public struct HistoryEntry: Codable, FetchableRecord, PersistableRecord, Sendabl
     public var id: String
     public var transcript: String          // 原始轉錄
     public var polishedText: String?       // AI 潤飾後
+    public var translatedText: String?     // 翻譯聽寫的譯文（polishedText 為原文）
+    public var targetLanguage: String?     // 翻譯目標語言，例如 "en-US"
     public var editedText: String?         // 使用者手動編輯
     public var appName: String?
     public var bundleId: String?
@@ -18,7 +20,7 @@ public struct HistoryEntry: Codable, FetchableRecord, PersistableRecord, Sendabl
     public var audioPath: String?
     public var durationSeconds: Double
     public var createdAt: Date
-    public var mode: String                // "voice_transcript" | "replace" | "append"
+    public var mode: String                // "voice_transcript" | "replace" | "append" | "translate"
 
     public static let databaseTableName = "history"
 
@@ -26,6 +28,8 @@ public struct HistoryEntry: Codable, FetchableRecord, PersistableRecord, Sendabl
         id: String = UUID().uuidString,
         transcript: String,
         polishedText: String? = nil,
+        translatedText: String? = nil,
+        targetLanguage: String? = nil,
         editedText: String? = nil,
         appName: String? = nil,
         bundleId: String? = nil,
@@ -43,6 +47,8 @@ public struct HistoryEntry: Codable, FetchableRecord, PersistableRecord, Sendabl
         self.id = id
         self.transcript = transcript
         self.polishedText = polishedText
+        self.translatedText = translatedText
+        self.targetLanguage = targetLanguage
         self.editedText = editedText
         self.appName = appName
         self.bundleId = bundleId
@@ -58,9 +64,9 @@ public struct HistoryEntry: Codable, FetchableRecord, PersistableRecord, Sendabl
         self.mode = mode
     }
 
-    /// 最終顯示文字 (優先順序: edited > polished > transcript)
+    /// 最終顯示文字 (優先順序: edited > translated > polished > transcript)
     public var displayText: String {
-        editedText ?? polishedText ?? transcript
+        editedText ?? translatedText ?? polishedText ?? transcript
     }
 }
 
@@ -89,6 +95,8 @@ public actor HistoryStore {
                 t.primaryKey("id", .text)
                 t.column("transcript", .text).notNull()
                 t.column("polishedText", .text)
+                t.column("translatedText", .text)
+                t.column("targetLanguage", .text)
                 t.column("editedText", .text)
                 t.column("appName", .text)
                 t.column("bundleId", .text)
@@ -110,6 +118,14 @@ public actor HistoryStore {
                     t.add(column: "webTitle", .text)
                 }
             }
+
+            // 翻譯聽寫 migration: add translatedText / targetLanguage columns
+            let columns = try db.columns(in: "history").map(\.name)
+            for column in ["translatedText", "targetLanguage"] where !columns.contains(column) {
+                try db.alter(table: "history") { t in
+                    t.add(column: column, .text)
+                }
+            }
         }
         dbQueue = queue
         isReady = true
@@ -4,17 +4,24 @@ import GRDB
 // MARK: - Settings Keys
 public enum SettingsKey: String, Sendable {
     case asrEngine          = "asrEngine"           // "whisper_turbo" | "qwen3_asr"
-    case polisherMode       = "polisherMode"         // "cloud" | "none"
-    case hotkey             = "hotkey"               // e.g. "fn+shift"
+    case polisherMode       = "polisherMode"         // "cloud" | "local" | "none"
+    case hotkey             = "hotkey"               // JSON: {"mode": "push_to_talk", "triggerKey": "right_option"}
+    case hotkeyBindings     = "hotkeyBindings"       // JSON object: 動作 → 觸發鍵手勢或快捷鍵（Rust 使用）
     case selectedMic        = "selectedMicId"
     case inputLanguage      = "inputLanguage"        // ASR 輸入語言: "auto" | "zh" | "en" etc.
     case outputLanguage     = "outputLanguage"       // AI 輸出語言: "zh-TW" | "zh-CN" | "en-US" etc.
     case apiKey             = "apiKey"               // Cloud API Key
     case apiBaseUrl         = "apiBaseUrl"           // Cloud API Base URL
+    case polisherModel      = "polisherModel"        // LLM 潤飾模型（空 = 依 endpoint 預設）
     case launchAtLogin      = "launchAtLogin"
     case showInMenuBar      = "showInMenuBar"
     case historyRetentionDays = "historyRetentionDays" // 歷史記錄保留天數
-    case personalDictionary = "personalDictionary"   // JSON array of custom words/terms
+    case personalDictionary = "personalDictionary"   // JSON array of terms or {term, sounds_like, case} entries
+    case snippets           = "snippets"             // JSON array of {trigger, text}（語音片段）
+    case helperTimeouts     = "helperTimeouts"       // JSON object: cmd → 超時秒數（Rust bridge 使用）
+    case formattingCommands = "formattingCommands"   // JSON object: 口述格式指令設定（Rust 潤飾使用）
+    case translation        = "translation"          // JSON object: 翻譯聽寫設定（依 App / 網域的目標語言）
+    case profiles           = "profiles"             // JSON array: 依 App / 網域覆寫設定的設定檔（Rust 使用）
 }
 
 // MARK: - Settings Store
@@ -1,10 +1,21 @@
 import Foundation
 
+// MARK: - Injection Method
+/// 注入方式（與 Rust protocol.rs 的 InjectionMethod 一致，可由設定檔依 App 指定）
+public enum InjectionMethod: String, Sendable {
+    case auto           // AX API，失敗時改用剪貼簿
+    case accessibility  // 只用 AX API，不動剪貼簿
+    case paste          // 一律剪貼簿 + Cmd+V
+}
+
 // MARK: - Text Injector Protocol
 public protocol TextInjectorProtocol: Sendable {
     /// 在游標位置插入文字
     func insertText(_ text: String) async throws
 
+    /// 以指定方式在游標位置插入文字
+    func insertText(_ text: String, method: InjectionMethod) async throws
+
     /// 替換目前選取的文字
     func replaceSelectedText(with text: String) async throws
 
@@ -38,8 +49,21 @@ public final class MacOSTextInjector: TextInjectorProtocol {
     public init() {}
 
     public func insertText(_ text: String) async throws {
-        let success = await tryAXInsertion(text: text)
-        if !success {
+        try await insertText(text, method: .auto)
+    }
+
+    public func insertText(_ text: String, method: InjectionMethod) async throws {
+        switch method {
+        case .auto:
+            let success = await tryAXInsertion(text: text)
+            if !success {
+                try await clipboardInsertion(text: text)
+            }
+        case .accessibility:
+            guard await tryAXInsertion(text: text) else {
+                throw InjectorError.insertionFailed("AX insertion failed")
+            }
+        case .paste:
             try await clipboardInsertion(text: text)
         }
     }
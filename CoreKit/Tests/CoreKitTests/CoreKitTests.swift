@@ -72,6 +72,9 @@ import Foundation
     #expect(SettingsKey.launchAtLogin.rawValue == "launchAtLogin")
     #expect(SettingsKey.historyRetentionDays.rawValue == "historyRetentionDays")
     #expect(SettingsKey.personalDictionary.rawValue == "personalDictionary")
+    #expect(SettingsKey.snippets.rawValue == "snippets")
+    #expect(SettingsKey.translation.rawValue == "translation")
+    #expect(SettingsKey.profiles.rawValue == "profiles")
 }
 
 // MARK: - History Entry Tests
@@ -80,6 +83,8 @@ import Foundation
     let entry = HistoryEntry(transcript: "Hello world")
     #expect(entry.transcript == "Hello world")
     #expect(entry.polishedText == nil)
+    #expect(entry.translatedText == nil)
+    #expect(entry.targetLanguage == nil)
     #expect(entry.editedText == nil)
     #expect(entry.appName == nil)
     #expect(entry.bundleId == nil)
@@ -125,6 +130,10 @@ import Foundation
     let e2 = HistoryEntry(transcript: "raw", polishedText: "polished")
     #expect(e2.displayText == "polished")
 
+    // translation shown over the polished source text
+    let e4 = HistoryEntry(transcript: "raw", polishedText: "polished", translatedText: "translated", targetLanguage: "en-US")
+    #expect(e4.displayText == "translated")
+
     // edited takes highest priority
     var e3 = HistoryEntry(transcript: "raw", polishedText: "polished")
     e3.editedText = "edited"
@@ -0,0 +1,442 @@
+// echotype-mock-helper — 測試用的 EchoTypeHelper 替身
+//
+// 說同一套協定（id / hello / 事件行 / length-prefixed framing），回應由劇本決定，
+// 讓 Tauri 命令層與 bridge 可以在沒有 macOS Swift helper 的 Linux CI 上測試。
+//
+// 使用方式：
+//   export ECHOTYPE_HELPER_PATH=target/debug/echotype-mock-helper
+//   export ECHOTYPE_MOCK_SCRIPT=/tmp/script.json   # 可省略，省略時全部使用預設回應
+//
+// 加上 `--socket <path>` 時改為監聽 Unix domain socket（與 `EchoTypeHelper --socket` 相同），
+// 每條連線各自說同一套協定，用來測試 bridge 連線到已在運行的 helper。
+//
+// 劇本格式：
+//   {
+//     "protocol_version": 1,                 // hello 回報的協定版本
+//     "helper_version": "mock",
+//     "unsupported": ["download_model"],     // 從 hello 的命令清單中移除
+//     "framing": "json_lines",               // 模擬不支援 framing 的舊 helper（預設接受 length_prefixed）
+//     "log": "/tmp/requests.jsonl",          // 收到的每一行請求都附加到此檔
+//     "commands": {
+//       "stop_recording": [                  // 依序消耗，最後一步重複使用
+//         { "spawn": 1, "crash": true },     // 只在第 1 次啟動時生效
+//         { "delay_ms": 500, "result": { "transcript": "...", "duration": 1.0, "asr_engine": "mock" } }
+//       ],
+//       "inject_text": { "error": "AX injection failed" }
+//     }
+//   }
+//
+// 步驟欄位：result / error / delay_ms / events（回應前推送的事件行）/
+//           malformed（回應前先輸出一行非 JSON）/ silent（不回應）/ crash（直接結束進程）/
+//           payload（回應的二進位 payload，UTF-8 字串；省略時原樣回傳請求的 payload）
+// 事件可帶 `payload` 字串，framed 模式下以二進位 payload 送出（JSON-line 模式下不送）。
+// 請求 log 中帶 payload 的請求會附上 `payload_len`。
+// 啟動次數記錄在 `<script>.spawns`，供 `spawn` 欄位比對（測試 bridge 重啟邏輯）。
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 預設支援的命令（hello 回報的清單）
+const COMMANDS: &[&str] = &[
+    "hello", "cancel", "ping",
+    "get_context", "start_recording", "stop_recording", "inject_text", "delete_backward",
+    "get_microphones", "get_settings", "set_setting", "get_api_key",
+    "get_dictionary", "set_dictionary",
+    "get_snippets", "set_snippets", "get_placeholder_values",
+    "get_history", "save_history", "prune_history",
+    "check_model_status", "download_model",
+    "check_microphone_permission", "request_microphone", "check_accessibility",
+    "stream_audio",
+];
+
+// MARK: - Script
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Script {
+    protocol_version: Option<u32>,
+    helper_version: Option<String>,
+    unsupported: Vec<String>,
+    framing: Option<String>,
+    log: Option<PathBuf>,
+    commands: HashMap<String, Steps>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Steps {
+    Many(Vec<Step>),
+    One(Step),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Step {
+    spawn: Option<u32>,
+    result: Option<Value>,
+    error: Option<String>,
+    delay_ms: u64,
+    events: Vec<Value>,
+    malformed: bool,
+    silent: bool,
+    crash: bool,
+    payload: Option<String>,
+}
+
+/// 一條連線的寫入端（stdout 或 socket），多個請求執行緒共用
+struct Conn {
+    writer: Mutex<(Box<dyn Write + Send>, bool)>,  // (寫入端, 是否已改用 length-prefixed)
+}
+
+type Output = Arc<Conn>;
+
+impl Conn {
+    fn new(writer: Box<dyn Write + Send>) -> Output {
+        Arc::new(Conn { writer: Mutex::new((writer, false)) })
+    }
+
+    /// 寫出一則訊息；JSON-line 模式下無法攜帶 payload，帶 payload 的訊息直接略過
+    fn write(&self, header: &str, payload: &[u8]) {
+        let mut guard = self.writer.lock().unwrap();
+        let (writer, framed) = &mut *guard;
+        write_message(writer, *framed, header, payload);
+    }
+
+    /// 寫出 hello 回應後立即切換為 length-prefixed（同一把鎖內，之後的訊息都已 framed）
+    fn write_then_frame(&self, header: &str) {
+        let mut guard = self.writer.lock().unwrap();
+        let (writer, framed) = &mut *guard;
+        write_message(writer, *framed, header, &[]);
+        *framed = true;
+    }
+}
+
+fn write_message(writer: &mut Box<dyn Write + Send>, framed: bool, header: &str, payload: &[u8]) {
+    if framed {
+        let _ = writer.write_all(&(header.len() as u32).to_be_bytes());
+        let _ = writer.write_all(&(payload.len() as u32).to_be_bytes());
+        let _ = writer.write_all(header.as_bytes());
+        let _ = writer.write_all(payload);
+    } else if payload.is_empty() {
+        let _ = writeln!(writer, "{}", header);
+    } else {
+        return;
+    }
+    let _ = writer.flush();
+}
+
+struct Mock {
+    script: Script,
+    spawn: u32,
+    cursors: Mutex<HashMap<String, usize>>,
+    log: Mutex<Option<std::fs::File>>,
+}
+
+impl Mock {
+    /// 取出此命令的下一個步驟（依啟動次數過濾，最後一步重複使用）
+    fn next_step(&self, cmd: &str) -> Step {
+        let steps: Vec<&Step> = match self.script.commands.get(cmd) {
+            Some(Steps::Many(steps)) => steps.iter().collect(),
+            Some(Steps::One(step)) => vec![step],
+            None => vec![],
+        };
+        let steps: Vec<&Step> = steps
+            .into_iter()
+            .filter(|s| s.spawn.is_none() || s.spawn == Some(self.spawn))
+            .collect();
+        if steps.is_empty() {
+            return Step::default();
+        }
+
+        let mut cursors = self.cursors.lock().unwrap();
+        let cursor = cursors.entry(cmd.to_string()).or_insert(0);
+        let step = steps[(*cursor).min(steps.len() - 1)].clone();
+        *cursor += 1;
+        step
+    }
+
+    /// hello 是否協商為 length-prefixed
+    fn negotiates_framing(&self, args: &Value) -> bool {
+        self.script.framing.as_deref() != Some("json_lines")
+            && args["framings"]
+                .as_array()
+                .is_some_and(|f| f.iter().any(|v| v == "length_prefixed"))
+    }
+
+    fn handle(&self, out: &Output, request: Request, step: Step) {
+        let Request { id, cmd, args, payload } = request;
+        let cmd = cmd.as_str();
+        std::thread::sleep(Duration::from_millis(step.delay_ms));
+        if step.crash {
+            eprintln!("[mock-helper] crashing on {}", cmd);
+            std::process::exit(101);
+        }
+        for event in &step.events {
+            let mut event = event.clone();
+            let data = event
+                .as_object_mut()
+                .and_then(|e| e.remove("payload"))
+                .and_then(|p| p.as_str().map(|p| p.as_bytes().to_vec()))
+                .unwrap_or_default();
+            out.write(&event.to_string(), &data);
+        }
+        if step.malformed {
+            out.write("this is not json {", &[]);
+        }
+        if step.silent {
+            return;
+        }
+
+        let mut resp = match (&step.error, step.result) {
+            (Some(error), _) => json!({ "ok": false, "error": error }),
+            (None, Some(result)) => json!({ "ok": true, "result": result }),
+            (None, None) => match self.default_result(cmd, &args) {
+                Ok(result) => json!({ "ok": true, "result": result }),
+                Err(error) => json!({ "ok": false, "error": error }),
+            },
+        };
+        if let Some(id) = id {
+            resp["id"] = id;
+        }
+        if cmd == "hello" && self.negotiates_framing(&args) {
+            out.write_then_frame(&resp.to_string());
+            return;
+        }
+        let resp_payload = step.payload.map(String::into_bytes).unwrap_or(payload);
+        out.write(&resp.to_string(), &resp_payload);
+    }
+
+    /// 未寫劇本時的預設回應，形狀與 Swift helper 相同
+    fn default_result(&self, cmd: &str, args: &Value) -> Result<Value, String> {
+        if self.script.unsupported.iter().any(|c| c == cmd) {
+            return Err(format!("Unknown command: {}", cmd));
+        }
+        let result = match cmd {
+            "hello" => {
+                let commands: Vec<&str> = COMMANDS
+                    .iter()
+                    .copied()
+                    .filter(|c| !self.script.unsupported.iter().any(|u| u == c))
+                    .collect();
+                let mut info = json!({
+                    "protocol_version": self.script.protocol_version.unwrap_or(1),
+                    "helper_version": self.script.helper_version.as_deref().unwrap_or("mock"),
+                    "commands": commands
+                });
+                if self.negotiates_framing(args) {
+                    info["framing"] = json!("length_prefixed");
+                }
+                info
+            }
+            "get_context" => json!({
+                "app_name": "MockApp",
+                "bundle_id": "com.echotype.mock",
+                "window_title": "Mock Window",
+                "web_url": null,
+                "web_domain": null,
+                "web_title": null,
+                "context_before": null,
+                "context_after": null,
+                "selected_text": null
+            }),
+            "start_recording" => json!("recording_started"),
+            "stop_recording" => json!({
+                "transcript": "hello from the mock helper",
+                "language": "en",
+                "duration": 1.5,
+                "asr_engine": "mock"
+            }),
+            "inject_text" | "delete_backward" => {
+                if args["text"].as_str().is_none() {
+                    return Err("Missing 'text' argument".into());
+                }
+                json!(true)
+            }
+            "get_microphones" => json!([
+                { "id": "default", "name": "Mock Microphone", "is_default": true }
+            ]),
+            "get_settings" => json!({
+                "asr_engine": "whisper_turbo",
+                "polisher_mode": "none",
+                "polisher_model": "",
+                "input_language": "auto",
+                "output_language": "zh-TW",
+                "selected_mic_id": "default",
+                "api_key": "",
+                "api_base_url": "",
+                "history_retention_days": "30",
+                "hotkey": "push_to_talk",
+                "hotkey_bindings": "",
+                "launch_at_login": "false",
+                "helper_timeouts": "",
+                "formatting_commands": "",
+                "translation": "",
+                "profiles": ""
+            }),
+            "get_api_key" => json!(""),
+            "get_dictionary" | "get_snippets" | "get_history" => json!([]),
+            "get_placeholder_values" => json!({ "date": "2026-01-01", "clipboard": null }),
+            "save_history" => json!("mock-history-id"),
+            "check_model_status" => {
+                let info = |name: &str| json!({
+                    "name": name, "path": "/tmp/mock", "downloaded": true, "size": null, "source": "mock"
+                });
+                json!({ "whisper": info("whisper"), "qwen3": info("qwen3") })
+            }
+            "check_microphone_permission" | "request_microphone" => json!("authorized"),
+            "set_setting" | "set_dictionary" | "set_snippets" | "prune_history" | "download_model"
+            | "check_accessibility" | "cancel" | "ping" | "stream_audio" => json!(true),
+            _ => return Err(format!("Unknown command: {}", cmd)),
+        };
+        Ok(result)
+    }
+}
+
+// MARK: - Main
+
+fn load_script(path: Option<&Path>) -> Script {
+    let Some(path) = path else {
+        return Script::default();
+    };
+    let raw = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("cannot read mock script {}: {}", path.display(), e));
+    serde_json::from_str(&raw)
+        .unwrap_or_else(|e| panic!("invalid mock script {}: {}", path.display(), e))
+}
+
+/// 累加並回傳啟動次數（沒有劇本檔時固定為 1）
+fn bump_spawn_counter(script_path: Option<&Path>) -> u32 {
+    let Some(path) = script_path else {
+        return 1;
+    };
+    let counter = path.with_extension("spawns");
+    let spawn = std::fs::read_to_string(&counter)
+        .ok()
+        .and_then(|s| s.trim().parse::<u32>().ok())
+        .unwrap_or(0)
+        + 1;
+    let _ = std::fs::write(&counter, spawn.to_string());
+    spawn
+}
+
+/// 一則請求
+struct Request {
+    id: Option<Value>,
+    cmd: String,
+    args: Value,
+    payload: Vec<u8>,
+}
+
+/// 讀取一個 length-prefixed frame；EOF 或格式錯誤時回傳 None
+fn read_frame(input: &mut impl Read) -> Option<(Vec<u8>, Vec<u8>)> {
+    let mut lengths = [0u8; 8];
+    input.read_exact(&mut lengths).ok()?;
+    let header_len = u32::from_be_bytes(lengths[..4].try_into().unwrap()) as usize;
+    let payload_len = u32::from_be_bytes(lengths[4..].try_into().unwrap()) as usize;
+    let mut header = vec![0u8; header_len];
+    input.read_exact(&mut header).ok()?;
+    let mut payload = vec![0u8; payload_len];
+    input.read_exact(&mut payload).ok()?;
+    Some((header, payload))
+}
+
+/// 讀取一條連線上的請求直到 EOF，每個請求在自己的執行緒回應。
+/// 協商成功的 hello 之後改讀 length-prefixed frame。
+fn serve(mock: &Arc<Mock>, mut input: impl BufRead, out: Output) {
+    let mut framed = false;
+    loop {
+        let (header, payload) = if framed {
+            match read_frame(&mut input) {
+                Some(frame) => frame,
+                None => break,
+            }
+        } else {
+            let mut line = Vec::new();
+            match input.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => (line, Vec::new()),
+            }
+        };
+
+        let request: Value = match serde_json::from_slice(&header) {
+            Ok(v) => v,
+            Err(_) => {
+                out.write(&json!({ "ok": false, "error": "Invalid JSON" }).to_string(), &[]);
+                continue;
+            }
+        };
+        if let Some(log) = mock.log.lock().unwrap().as_mut() {
+            let mut logged = request.clone();
+            if !payload.is_empty() {
+                logged["payload_len"] = json!(payload.len());
+            }
+            let _ = writeln!(log, "{}", logged);
+        }
+
+        let request = Request {
+            id: request.get("id").cloned(),
+            cmd: request["cmd"].as_str().unwrap_or_default().to_string(),
+            args: request.get("args").cloned().unwrap_or(Value::Null),
+            payload,
+        };
+        if request.cmd == "hello" && mock.negotiates_framing(&request.args) {
+            framed = true;
+        }
+
+        // 步驟在讀取時就決定，確保同一命令的回應順序與請求順序一致
+        let step = mock.next_step(&request.cmd);
+        let mock = Arc::clone(mock);
+        let out = Arc::clone(&out);
+        std::thread::spawn(move || mock.handle(&out, request, step));
+    }
+}
+
+/// `--socket <path>`：接受多條連線，直到進程被結束
+fn serve_socket(mock: Arc<Mock>, path: &Path) {
+    let _ = std::fs::remove_file(path);
+    let listener = std::os::unix::net::UnixListener::bind(path)
+        .unwrap_or_else(|e| panic!("cannot listen on {}: {}", path.display(), e));
+    eprintln!("[mock-helper] listening on {}", path.display());
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let Ok(writer) = stream.try_clone() else { continue };
+        let mock = Arc::clone(&mock);
+        std::thread::spawn(move || {
+            serve(&mock, BufReader::new(stream), Conn::new(Box::new(writer)));
+        });
+    }
+}
+
+fn main() {
+    let script_path = std::env::var_os("ECHOTYPE_MOCK_SCRIPT").map(PathBuf::from);
+    let spawn = bump_spawn_counter(script_path.as_deref());
+    let script = load_script(script_path.as_deref());
+    let log = script.log.as_ref().map(|path| {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap_or_else(|e| panic!("cannot open request log {}: {}", path.display(), e))
+    });
+    let mock = Arc::new(Mock {
+        script,
+        spawn,
+        cursors: Mutex::new(HashMap::new()),
+        log: Mutex::new(log),
+    });
+
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(i) = args.iter().position(|a| a == "--socket") {
+        let path = args.get(i + 1).expect("--socket requires a path");
+        serve_socket(mock, Path::new(path));
+        return;
+    }
+
+    serve(&mock, std::io::stdin().lock(), Conn::new(Box::new(std::io::stdout())));
+}
@@ -2,31 +2,247 @@
 //
 // 架構:
 //   Rust (Tauri) ─stdin→ EchoTypeHelper (Swift) ─stdout→ Rust
-//   每次呼叫發送一行 JSON，讀取一行 JSON 回應。
-
-#[cfg(debug_assertions)]
-use crate::log_warn;
-use serde::Deserialize;
+//   （或經由 Unix domain socket 連到獨立運行的 helper，見 transport.rs）
+//   每個請求帶一個遞增的 `id`，helper 回應時原樣帶回。
+//   單一 reader task 持續讀取 stdout，依 `id` 將回應分派給等待中的 future，
+//   因此多個命令可以同時在途（例如 ASR 轉錄期間仍可呼叫 get_context）。
+//
+// 除了回應之外，helper 也可主動推送事件行（沒有 `id`）：
+//   {"event": "audio_level", "data": {"level": 0.12}}
+//   reader task 將其轉為 Tauri 事件（snake_case → kebab-case，例如 `audio-level`）
+//   emit 給前端，與等待中的請求完全無關。
+//
+// 握手：每次 spawn 後先送 `hello`，helper 回報協定版本、build 版本與支援的 cmd 清單。
+//   協定版本不符（例如殘留的舊 debug build）直接拒絕並給出明確錯誤；
+//   helper 不支援的 cmd 在送出前就失敗，而不是等到超時。
+//
+// Framing：hello 同時協商訊息格式。helper 選定 length-prefixed 時，hello 回應之後
+//   雙向改用可攜帶二進位 payload 的 frame（見 framing.rs），例如錄音 PCM 不需 base64；
+//   舊 helper 不回報 framing，維持 JSON-line。帶 payload 的事件交給 Rust 端訂閱者
+//   （subscribe_binary_events），不轉發給前端。
+//
+// 超時與取消：每個命令有自己的超時（可由設定 `helper_timeouts` 覆寫）。
+//   超時或被前端取消時只放棄該請求並送出 `cancel` 給 helper，不會殺掉進程。
+//
+// 監督：helper 意外結束或心跳 `ping` 無回應時由 supervisor 記錄崩潰，
+//   重啟前套用指數退避，短時間內崩潰過多則停止重啟（見 supervisor.rs）。
+//
+// 錄製與重播：開啟 recorder 時所有收送的訊息寫入 session 檔（見 recorder.rs）；
+//   replay_session() 讓之後的連線改由錄製的 session 回應（見 replay.rs）。
+
+use crate::framing;
+use crate::protocol::{Cancel, Framing, Hello, HelperCommand, HelperInfo, HelperRequest, Ping};
+use crate::recorder::{self, Direction};
+use crate::replay::ReplayTransport;
+use crate::supervisor;
+use crate::transport::{BoxReader, BoxWriter, Connection, StdioTransport, Transport};
+use crate::{log_debug, log_warn};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::io::{BufRead, BufReader, ErrorKind, Write};
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
-
-#[cfg(unix)]
-use libc;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{broadcast, oneshot, Mutex as AsyncMutex};
 
 // MARK: - Lazy-init singleton helper process
 
-static HELPER: OnceLock<Mutex<Option<HelperProcess>>> = OnceLock::new();
+static HELPER: OnceLock<AsyncMutex<Option<Arc<HelperProcess>>>> = OnceLock::new();
+
+const HELPER_TIMEOUT_SECS: u64 = 10;  // 未特別列出的命令的預設超時時間
+const HELLO_TIMEOUT_SECS: u64 = 3;    // 握手超時時間（helper 啟動後應立即可回應）
+const HEARTBEAT_INTERVAL_SECS: u64 = 15;  // 閒置超過此時間才送 ping
+const HEARTBEAT_TIMEOUT_SECS: u64 = 5;    // ping 無回應即視為 helper 卡死
+
+/// 設定時，新連線改由此 session 檔重播（見 replay_session）
+static REPLAY_SESSION: Mutex<Option<std::path::PathBuf>> = Mutex::new(None);
+
+/// 使用者設定的超時覆寫：cmd → 秒數
+static TIMEOUT_OVERRIDES: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
 
-const HELPER_TIMEOUT_SECS: u64 = 10;  // Helper 回應超時時間
+/// 本版 app 所使用的 JSON-line 協定版本；helper 必須回報相同版本
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// 等待回應中的請求：id → 回應通道
+type PendingMap = Mutex<HashMap<u64, PendingRequest>>;
+
+struct PendingRequest {
+    cmd: String,
+    tx: oneshot::Sender<Reply>,
+}
+
+enum Reply {
+    Response(HelperResponse),
+    Cancelled,
+}
 
 struct HelperProcess {
-    stdin: ChildStdin,
-    stdout: BufReader<ChildStdout>,
-    child: Child,
-    last_response: Arc<Mutex<Instant>>,  // 最後一次成功回應時間
+    writer: AsyncMutex<BoxWriter>,         // 寫入端，序列化每一行 / 每個 frame
+    framed: AtomicBool,                    // 握手協商出 length-prefixed 後為 true
+    transport: Box<dyn Transport>,
+    pending: Arc<PendingMap>,
+    next_id: AtomicU64,
+    alive: Arc<AtomicBool>,                // reader task 讀到 EOF 或主動 kill 後設為 false
+    last_response: Arc<Mutex<Instant>>,    // 最後一次從 stdout 讀到任何一行的時間
+    info: HelperInfo,                      // 握手結果
+}
+
+impl HelperProcess {
+    /// 接上新連線並啟動 reader task（尚未握手）
+    fn start(connection: Connection) -> HelperProcess {
+        let pending: Arc<PendingMap> = Arc::new(Mutex::new(HashMap::new()));
+        let alive = Arc::new(AtomicBool::new(true));
+        let last_response = Arc::new(Mutex::new(Instant::now()));
+
+        tokio::spawn(read_responses(
+            connection.reader,
+            Arc::clone(&pending),
+            Arc::clone(&alive),
+            Arc::clone(&last_response),
+        ));
+
+        HelperProcess {
+            writer: AsyncMutex::new(connection.writer),
+            framed: AtomicBool::new(false),
+            transport: connection.transport,
+            pending,
+            next_id: AtomicU64::new(1),
+            alive,
+            last_response,
+            info: HelperInfo::default(),
+        }
+    }
+
+    /// 連線是否仍可接受請求
+    fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::Acquire) && self.transport.is_alive()
+    }
+
+    /// 結束連線（spawn 的子進程會被殺掉；socket 只斷線）
+    fn kill(&self) {
+        self.alive.store(false, Ordering::Release);
+        self.transport.close();
+    }
+
+    /// 寫入一個請求：JSON-line 模式為一行，framed 模式為一個 frame（可帶 payload）
+    async fn send(&self, request: &Value, payload: &[u8]) -> Result<(), String> {
+        if recorder::is_active() {
+            recorder::record(Direction::Send, request, payload.len());
+        }
+        let header = serde_json::to_vec(request).map_err(|e| e.to_string())?;
+        let bytes = if self.framed.load(Ordering::Acquire) {
+            framing::encode(&header, payload)?
+        } else if payload.is_empty() {
+            let mut line = header;
+            line.push(b'\n');
+            line
+        } else {
+            return Err("Binary payloads require length-prefixed framing".to_string());
+        };
+        let mut writer = self.writer.lock().await;
+        writer.write_all(&bytes).await.map_err(|e| format!("Write error: {}", e))?;
+        writer.flush().await.map_err(|e| format!("Flush error: {}", e))
+    }
+
+    /// 發送請求並等待對應 id 的回應；超時時放棄該請求並通知 helper 取消
+    async fn request(&self, cmd: &str, args: Value, timeout: Duration) -> Result<HelperResponse, RequestError> {
+        self.request_with_payload(cmd, args, &[], timeout).await
+    }
+
+    async fn request_with_payload(
+        &self,
+        cmd: &str,
+        args: Value,
+        payload: &[u8],
+        timeout: Duration,
+    ) -> Result<HelperResponse, RequestError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, PendingRequest { cmd: cmd.to_string(), tx });
+
+        let request = json!({ "id": id, "cmd": cmd, "args": args });
+        if let Err(e) = self.send(&request, payload).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(RequestError::Io(e));
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(Reply::Response(resp))) => Ok(resp),
+            Ok(Ok(Reply::Cancelled)) => Err(RequestError::Cancelled),
+            Ok(Err(_)) => Err(RequestError::Closed),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                self.send_cancel(id).await;
+                Err(RequestError::Timeout)
+            }
+        }
+    }
+
+    /// 請 helper 放棄某個請求（不等待回應；helper 不支援時略過）
+    async fn send_cancel(&self, request_id: u64) {
+        if !self.info.supports("cancel") {
+            return;
+        }
+        let Ok((cmd, args)) = HelperRequest::from(Cancel { request_id }).into_parts() else {
+            return;
+        };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = self.send(&json!({ "id": id, "cmd": cmd, "args": args }), &[]).await {
+            log_warn!("[bridge] Failed to send cancel for request {}: {}", request_id, e);
+        }
+    }
+
+    /// `hello` 握手：確認協定版本並取得支援的命令清單
+    async fn handshake(&self) -> Result<HelperInfo, String> {
+        let stale = || format!(
+            "EchoTypeHelper at {} does not support the hello handshake (stale build?). \
+             Rebuild it: cd EchoType/helper && swift build",
+            self.transport.describe()
+        );
+
+        let hello = Hello {
+            protocol_version: PROTOCOL_VERSION,
+            framings: vec![Framing::LengthPrefixed, Framing::JsonLines],
+        };
+        let (cmd, args) = HelperRequest::from(hello).into_parts()?;
+        let resp = match self.request(&cmd, args, Duration::from_secs(HELLO_TIMEOUT_SECS)).await {
+            Ok(resp) => resp,
+            Err(RequestError::Timeout) => return Err(stale()),
+            Err(RequestError::Closed | RequestError::Cancelled) => {
+                return Err("Helper process closed during handshake".into())
+            }
+            Err(RequestError::Io(e)) => return Err(e),
+        };
+        if !resp.ok {
+            log_warn!("[bridge] hello rejected: {}", resp.error.unwrap_or_default());
+            return Err(stale());
+        }
+
+        let info: HelperInfo = serde_json::from_value(resp.result.unwrap_or(Value::Null))
+            .map_err(|e| format!("Invalid hello response: {}", e))?;
+        if info.protocol_version != PROTOCOL_VERSION {
+            return Err(format!(
+                "EchoTypeHelper {} at {} speaks protocol v{}, but this app requires v{}. \
+                 Rebuild it: cd EchoType/helper && swift build",
+                info.helper_version,
+                self.transport.describe(),
+                info.protocol_version,
+                PROTOCOL_VERSION
+            ));
+        }
+        // reader task 讀到 hello 回應時已自行切換；之後的請求也改用 frame
+        self.framed.store(info.framing == Framing::LengthPrefixed, Ordering::Release);
+        Ok(info)
+    }
+}
+
+enum RequestError {
+    Io(String),
+    Closed,
+    Timeout,
+    Cancelled,
 }
 
 fn get_helper_path() -> std::path::PathBuf {
@@ -70,174 +286,473 @@ fn get_helper_path() -> std::path::PathBuf {
     }
 }
 
-fn acquire_helper() -> Result<(), String> {
-    let mutex = HELPER.get_or_init(|| Mutex::new(None));
-    let mut guard = mutex.lock().map_err(|e| e.to_string())?;
+/// 取得運行中的 helper；尚未啟動或已死亡時重新 spawn
+async fn acquire_helper() -> Result<Arc<HelperProcess>, String> {
+    let mutex = HELPER.get_or_init(|| AsyncMutex::new(None));
+    let mut guard = mutex.lock().await;
 
-    if guard.is_some() {
-        return Ok(());  // Already running
+    if let Some(helper) = guard.as_ref() {
+        if helper.is_alive() {
+            return Ok(Arc::clone(helper));  // Already running
+        }
+        // 健康檢查失敗：丟棄舊的 helper 並重新啟動
+        helper.kill();
+        *guard = None;
     }
 
-    let helper_path = get_helper_path();
-    if !helper_path.exists() {
-        return Err(format!(
-            "EchoTypeHelper not found at {}. Run: cd EchoType/helper && swift build",
-            helper_path.display()
-        ));
+    // 崩潰後的退避期間持有鎖等待，同時到達的呼叫者不會各自 spawn
+    let wait = supervisor::spawn_permit()?;
+    if !wait.is_zero() {
+        eprintln!("[bridge] Waiting {}ms before restarting helper", wait.as_millis());
+        tokio::time::sleep(wait).await;
     }
 
-    // M1 修復：將 stderr 重定向到日誌文件而非 /dev/null
-    let log_dir = std::env::temp_dir().join("echotype");
-    std::fs::create_dir_all(&log_dir).ok();
-    let stderr_log = log_dir.join("helper_stderr.log");
-    let stderr_file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&stderr_log)
-        .map_err(|e| format!("Failed to open stderr log: {}", e))?;
+    // 已在運行的 helper 握手失敗（過期或不相容的 socket）時改為 spawn，spawn 的也失敗才回報錯誤
+    let socket_helper = match connect_socket().await {
+        Some(connection) => start_helper(connection)
+            .await
+            .inspect_err(|e| log_warn!("[bridge] Socket helper handshake failed ({}), spawning helper instead", e))
+            .ok(),
+        None => None,
+    };
+    let helper = match socket_helper {
+        Some(helper) => helper,
+        None => {
+            let connection = spawn_helper().inspect_err(|e| supervisor::helper_failed(e))?;
+            start_helper(connection).await.inspect_err(|e| supervisor::helper_failed(e))?
+        }
+    };
+
+    let helper = Arc::new(helper);
+    *guard = Some(Arc::clone(&helper));
+    Ok(helper)
+}
 
-    let mut child = Command::new(&helper_path)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(stderr_file)
-        .spawn()
-        .map_err(|e| format!("Failed to spawn EchoTypeHelper: {}", e))?;
+/// 建立 HelperProcess 並握手；失敗時關閉連線
+async fn start_helper(connection: Connection) -> Result<HelperProcess, String> {
+    let mut helper = HelperProcess::start(connection);
+    match helper.handshake().await {
+        Ok(info) => {
+            eprintln!(
+                "[bridge] Connected to EchoTypeHelper {} via {} (protocol v{}, {:?}, {} commands)",
+                info.helper_version,
+                helper.transport.describe(),
+                info.protocol_version,
+                info.framing,
+                info.commands.len()
+            );
+            supervisor::helper_ready(&info.helper_version);
+            helper.info = info;
+            Ok(helper)
+        }
+        Err(e) => {
+            helper.kill();
+            Err(e)
+        }
+    }
+}
 
-    eprintln!("[bridge] Helper stderr logging to: {}", stderr_log.display());
+/// 連線已在運行的 helper（Unix socket）；沒有或連不上時回傳 None。重播模式下不使用 socket
+async fn connect_socket() -> Option<Connection> {
+    if REPLAY_SESSION.lock().unwrap().is_some() {
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        let socket = crate::transport::socket_path();
+        if socket.exists() {
+            match crate::transport::SocketTransport::connect(&socket).await {
+                Ok(connection) => return Some(connection),
+                Err(e) => log_debug!("[bridge] {}, spawning helper instead", e),
+            }
+        }
+    }
+    None
+}
 
-    let stdin = child.stdin.take().ok_or("No stdin")?;
-    let stdout = child.stdout.take().ok_or("No stdout")?;
+/// spawn helper 子進程；重播模式下改用 session 檔
+fn spawn_helper() -> Result<Connection, String> {
+    let replay = REPLAY_SESSION.lock().unwrap().clone();
+    if let Some(path) = replay {
+        return ReplayTransport::open(&path);
+    }
+    StdioTransport::spawn(&get_helper_path(), &supervisor::stderr_log_path())
+}
 
-    *guard = Some(HelperProcess {
-        stdin,
-        stdout: BufReader::new(stdout),
-        child,
-        last_response: Arc::new(Mutex::new(Instant::now())),
-    });
+/// 讓出 helper singleton（僅當 slot 仍指向同一個進程時），下次呼叫會重新 spawn
+async fn discard_helper(helper: &Arc<HelperProcess>) {
+    helper.kill();
+    if let Some(mutex) = HELPER.get() {
+        let mut guard = mutex.lock().await;
+        if guard.as_ref().is_some_and(|h| Arc::ptr_eq(h, helper)) {
+            *guard = None;
+        }
+    }
+}
 
-    Ok(())
+/// 結束目前的 helper 進程；下次呼叫時會重新 spawn（App 結束與測試重置時使用）
+pub async fn shutdown_helper() {
+    if let Some(mutex) = HELPER.get() {
+        if let Some(helper) = mutex.lock().await.take() {
+            helper.kill();
+        }
+    }
 }
 
-// MARK: - Core call function
+/// 改以錄製的 session 檔回應之後的所有命令（None = 恢復使用真正的 helper）。
+/// 目前的連線會先關閉，下一次呼叫時以新的來源重新握手。
+pub async fn replay_session(path: Option<std::path::PathBuf>) {
+    *REPLAY_SESSION.lock().unwrap() = path;
+    shutdown_helper().await;
+}
 
-#[derive(Deserialize)]
-struct HelperResponse {
-    ok: bool,
-    result: Option<Value>,
-    error: Option<String>,
+/// 使用者手動重試：清除崩潰紀錄並重新啟動 helper
+pub async fn restart_helper() -> Result<HelperInfo, String> {
+    supervisor::reset();
+    shutdown_helper().await;
+    helper_info().await
 }
 
-pub fn call_helper(cmd: &str, args: Value) -> Result<Value, String> {
-    acquire_helper()?;
+// MARK: - Heartbeat
 
-    let mutex = HELPER.get().ok_or("Helper not initialized")?;
-    let mut guard = mutex.lock().map_err(|e| e.to_string())?;
-    let helper = guard.as_mut().ok_or("Helper process not running")?;
+/// 週期性檢查 helper：閒置超過 HEARTBEAT_INTERVAL_SECS 就送 `ping`，
+/// 無回應視為卡死，記錄崩潰並丟棄（下次呼叫依退避重啟）。不會主動 spawn helper。
+pub async fn run_heartbeat() {
+    let mut ticker = tokio::time::interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+    loop {
+        ticker.tick().await;
+        heartbeat_once().await;
+    }
+}
 
-    // 健康檢查：如果 helper 進程已死亡，重啟
-    match helper.child.try_wait() {
-        Ok(Some(status)) => {
-            eprintln!("[bridge] Helper process exited with status: {}", status);
-            drop(guard);  // 釋放鎖
-            // 清空舊的 helper 並重新啟動
-            let mutex = HELPER.get().ok_or("Helper not initialized")?;
-            *mutex.lock().map_err(|e| e.to_string())? = None;
-            acquire_helper()?;
-            // 重新獲取鎖
-            guard = mutex.lock().map_err(|e| e.to_string())?;
+async fn heartbeat_once() {
+    let Some(mutex) = HELPER.get() else {
+        return;
+    };
+    let Some(helper) = mutex.lock().await.clone() else {
+        return;
+    };
+    let idle = helper.last_response.lock().unwrap().elapsed();
+    if idle < Duration::from_secs(HEARTBEAT_INTERVAL_SECS) || !helper.info.supports("ping") {
+        return;
+    }
+
+    let Ok((cmd, args)) = HelperRequest::from(Ping {}).into_parts() else {
+        return;
+    };
+    match helper.request(&cmd, args, Duration::from_secs(HEARTBEAT_TIMEOUT_SECS)).await {
+        Ok(_) | Err(RequestError::Cancelled) => {}
+        Err(RequestError::Closed) => {}  // reader task 已記錄崩潰
+        Err(RequestError::Timeout | RequestError::Io(_)) => {
+            // 先 kill（alive = false），reader 讀到 EOF 時就不會重複記錄
+            discard_helper(&helper).await;
+            supervisor::record_crash(&format!(
+                "no heartbeat response within {}s",
+                HEARTBEAT_TIMEOUT_SECS
+            ));
         }
-        Ok(None) => {}, // 進程仍在運行
-        Err(e) => {
-            eprintln!("[bridge] Failed to check helper status: {}", e);
+    }
+}
+
+// MARK: - Helper events
+
+/// 事件要送往的 App（於 setup 時設定；未設定時事件僅記錄 debug log）
+static EVENT_SINK: OnceLock<AppHandle> = OnceLock::new();
+
+/// helper 主動推送的事件（audio_level / device_changed / model_download_progress ...）。
+/// 不允許多餘欄位，避免把帶 `id` 的回應誤判為事件。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HelperEvent {
+    pub event: String,
+    #[serde(default)]
+    pub data: Value,
+}
+
+/// 設定 helper 事件的轉發目標，必須在第一次呼叫 helper 前完成
+pub fn set_event_sink(app: AppHandle) {
+    let _ = EVENT_SINK.set(app);
+}
+
+/// 帶二進位 payload 的 helper 事件（例如 `audio_chunk`），只發給 Rust 端訂閱者
+#[derive(Debug, Clone)]
+pub struct BinaryEvent {
+    pub event: String,
+    pub data: Value,
+    pub payload: Arc<Vec<u8>>,
+}
+
+static BINARY_EVENTS: OnceLock<broadcast::Sender<BinaryEvent>> = OnceLock::new();
+
+fn binary_events() -> &'static broadcast::Sender<BinaryEvent> {
+    BINARY_EVENTS.get_or_init(|| broadcast::channel(256).0)
+}
+
+/// 訂閱帶 payload 的 helper 事件（需 length-prefixed framing；落後太多的訂閱者會漏接）
+pub fn subscribe_binary_events() -> broadcast::Receiver<BinaryEvent> {
+    binary_events().subscribe()
+}
+
+/// helper 事件名 → Tauri 事件名（`audio_level` → `audio-level`）
+fn tauri_event_name(event: &str) -> Option<String> {
+    let valid = !event.is_empty()
+        && event.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    valid.then(|| event.replace('_', "-"))
+}
+
+fn forward_event(event: HelperEvent) {
+    let Some(name) = tauri_event_name(&event.event) else {
+        log_warn!("[bridge] Ignoring helper event with invalid name: {:?}", event.event);
+        return;
+    };
+    emit_app_event(&name, event.data);
+}
+
+/// 透過 event sink 發送 Tauri 事件（helper 事件與 supervisor 狀態共用）
+pub(crate) fn emit_app_event<S: Serialize + Clone>(name: &str, payload: S) {
+    match EVENT_SINK.get() {
+        Some(app) => {
+            if let Err(e) = app.emit(name, payload) {
+                log_warn!("[bridge] Failed to emit {}: {}", name, e);
+            }
         }
+        None => log_debug!("[bridge] No event sink, dropping event {}", name),
     }
+}
 
-    let helper = guard.as_mut().ok_or("Helper process not running after restart")?;
+// MARK: - Response reader
 
-    // Send request
-    let request = json!({ "cmd": cmd, "args": args });
-    let mut line = serde_json::to_string(&request).map_err(|e| e.to_string())?;
-    line.push('\n');
-    helper.stdin.write_all(line.as_bytes()).map_err(|e| format!("Write error: {}", e))?;
-    helper.stdin.flush().map_err(|e| format!("Flush error: {}", e))?;
+#[derive(Deserialize)]
+struct HelperResponse {
+    id: Option<u64>,
+    ok: bool,
+    result: Option<Value>,
+    error: Option<String>,
+    #[serde(skip)]
+    payload: Vec<u8>,
+}
 
-    // Read response with timeout using polling
-    let start = Instant::now();
-    let mut response_line = String::new();
+/// stdout 上的一行：事件優先判斷（必須有 `event` 且無其他欄位），否則視為回應
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HelperMessage {
+    Event(HelperEvent),
+    Response(HelperResponse),
+}
 
-    // 設置 stdout 為非阻塞模式
-    #[cfg(unix)]
-    let original_flags = {
-        use std::os::unix::io::AsRawFd;
-        let fd = helper.stdout.get_ref().as_raw_fd();
-        unsafe {
-            let flags = libc::fcntl(fd, libc::F_GETFL, 0);
-            libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
-            flags
+/// 讀取下一則訊息（header 與 payload）；EOF 時回傳 None
+async fn read_message(
+    reader: &mut BufReader<BoxReader>,
+    framing: Framing,
+) -> std::io::Result<Option<framing::Frame>> {
+    match framing {
+        Framing::JsonLines => {
+            let mut line = Vec::new();
+            if reader.read_until(b'\n', &mut line).await? == 0 {
+                return Ok(None);
+            }
+            Ok(Some(framing::Frame { header: line, payload: Vec::new() }))
         }
-    };
+        Framing::LengthPrefixed => framing::read_frame(reader).await,
+    }
+}
 
-    // 輪詢讀取，直到成功或超時
-    let read_result = loop {
-        match helper.stdout.read_line(&mut response_line) {
-            Ok(0) => {
-                // EOF - helper 進程已關閉
-                break Err("Helper process closed unexpectedly".to_string());
+/// 單一 reader task：逐則讀取 helper 輸出，回應依 id 喚醒對應的呼叫者，事件轉發給前端
+async fn read_responses(
+    reader: BoxReader,
+    pending: Arc<PendingMap>,
+    alive: Arc<AtomicBool>,
+    last_response: Arc<Mutex<Instant>>,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut framing = Framing::JsonLines;
+
+    loop {
+        let frame = match read_message(&mut reader, framing).await {
+            Ok(Some(frame)) => frame,
+            Ok(None) => break,  // EOF - helper 進程已關閉或連線中斷
+            Err(e) => {
+                eprintln!("[bridge] Read error: {}", e);
+                break;
             }
-            Ok(_) => {
-                // 成功讀取
-                *helper.last_response.lock().unwrap() = Instant::now();
-                break Ok(());
+        };
+        *last_response.lock().unwrap() = Instant::now();
+        let raw = || String::from_utf8_lossy(&frame.header).trim().to_string();
+        if recorder::is_active() {
+            let message = serde_json::from_slice(&frame.header).unwrap_or_else(|_| Value::String(raw()));
+            recorder::record(Direction::Recv, &message, frame.payload.len());
+        }
+
+        let mut resp = match serde_json::from_slice(&frame.header) {
+            Ok(HelperMessage::Response(resp)) => resp,
+            Ok(HelperMessage::Event(event)) if frame.payload.is_empty() => {
+                forward_event(event);
+                continue;
             }
-            Err(e) if e.kind() == ErrorKind::WouldBlock => {
-                // 非阻塞模式：數據尚未就緒
-                if start.elapsed() > Duration::from_secs(HELPER_TIMEOUT_SECS) {
-                    break Err(format!(
-                        "Helper timeout after {}s (cmd: {}). Process killed.",
-                        HELPER_TIMEOUT_SECS, cmd
-                    ));
-                }
-                // 短暫休眠後重試
-                std::thread::sleep(Duration::from_millis(10));
+            Ok(HelperMessage::Event(event)) => {
+                let _ = binary_events().send(BinaryEvent {
+                    event: event.event,
+                    data: event.data,
+                    payload: Arc::new(frame.payload),
+                });
+                continue;
             }
             Err(e) => {
-                break Err(format!("Read error: {}", e));
+                log_warn!("[bridge] JSON parse error: {} (raw: {})", e, raw());
+                continue;
             }
+        };
+        resp.payload = frame.payload;
+
+        let waiter = resp.id.and_then(|id| pending.lock().unwrap().remove(&id));
+        match waiter {
+            Some(req) => {
+                // hello 回應之後的訊息改用協商出的 framing（必須在讀下一則之前切換）
+                if req.cmd == "hello" && resp.ok {
+                    if let Some(negotiated) = resp.result.as_ref().and_then(|r| r.get("framing")) {
+                        framing = serde_json::from_value(negotiated.clone()).unwrap_or_default();
+                    }
+                }
+                let _ = req.tx.send(Reply::Response(resp));
+            }
+            None => log_debug!("[bridge] Dropping response with no waiting caller: {}", raw()),
         }
-    };
+    }
 
-    // 恢復 stdout 為阻塞模式
-    #[cfg(unix)]
-    {
-        use std::os::unix::io::AsRawFd;
-        let fd = helper.stdout.get_ref().as_raw_fd();
-        unsafe {
-            libc::fcntl(fd, libc::F_SETFL, original_flags);
-        }
+    // alive 仍為 true 表示不是我們 kill 的：helper 意外結束
+    if alive.swap(false, Ordering::AcqRel) {
+        supervisor::record_crash("helper exited unexpectedly");
     }
+    // 丟棄所有 sender，等待中的呼叫者會立即收到「helper 已關閉」錯誤
+    pending.lock().unwrap().clear();
+}
+
+// MARK: - Core call function
+
+/// 發送一個型別化命令並解析回應，例如 `call_helper(InjectText { text }).await`
+pub async fn call_helper<C: HelperCommand>(command: C) -> Result<C::Response, String> {
+    let (cmd, args) = command.into().into_parts()?;
+    let result = call_helper_raw(&cmd, args).await?;
+    serde_json::from_value(result).map_err(|e| format!("Parse error ({}): {}", cmd, e))
+}
 
-    // 處理讀取結果
-    if let Err(e) = read_result {
-        let _ = helper.child.kill();
-        drop(guard);
-        let mutex = HELPER.get().ok_or("Helper not initialized")?;
-        *mutex.lock().map_err(|e| e.to_string())? = None;
-        return Err(e);
+/// 發送一個未型別化的命令給 helper 並等待對應 id 的回應；可與其他命令並行
+pub async fn call_helper_raw(cmd: &str, args: Value) -> Result<Value, String> {
+    let (result, _) = call(cmd, args, &[]).await?;
+    Ok(result)
+}
+
+/// 發送帶二進位 payload 的命令，回傳結果與回應的 payload（需 length-prefixed framing）
+pub async fn call_helper_binary(cmd: &str, args: Value, payload: &[u8]) -> Result<(Value, Vec<u8>), String> {
+    call(cmd, args, payload).await
+}
+
+async fn call(cmd: &str, args: Value, payload: &[u8]) -> Result<(Value, Vec<u8>), String> {
+    let helper = acquire_helper().await?;
+
+    // helper 未宣告支援的命令直接失敗，不必等到超時
+    if !helper.info.supports(cmd) {
+        return Err(format!(
+            "EchoTypeHelper {} does not support command: {}",
+            helper.info.helper_version, cmd
+        ));
+    }
+    if !payload.is_empty() && helper.info.framing != Framing::LengthPrefixed {
+        return Err(format!(
+            "EchoTypeHelper {} does not support binary framing (cmd: {})",
+            helper.info.helper_version, cmd
+        ));
     }
 
-    let resp: HelperResponse = serde_json::from_str(&response_line)
-        .map_err(|e| format!("JSON parse error: {} (raw: {})", e, response_line.trim()))?;
+    let timeout = timeout_for(cmd);
+    let resp = match helper.request_with_payload(cmd, args, payload, timeout).await {
+        Ok(resp) => resp,
+        Err(RequestError::Io(e)) => {
+            discard_helper(&helper).await;
+            return Err(e);
+        }
+        Err(RequestError::Closed) => {
+            discard_helper(&helper).await;
+            return Err("Helper process closed unexpectedly".to_string());
+        }
+        Err(RequestError::Timeout) => {
+            return Err(format!("Helper timeout after {}s (cmd: {})", timeout.as_secs(), cmd));
+        }
+        Err(RequestError::Cancelled) => {
+            return Err(format!("Cancelled (cmd: {})", cmd));
+        }
+    };
 
     if resp.ok {
-        Ok(resp.result.unwrap_or(Value::Null))
+        Ok((resp.result.unwrap_or(Value::Null), resp.payload))
     } else {
         Err(resp.error.unwrap_or_else(|| "Unknown helper error".into()))
     }
 }
 
-/// 便捷函式：在非同步 Tauri 命令中用 spawn_blocking 呼叫同步的 call_helper
-pub async fn call_helper_async(cmd: &'static str, args: Value) -> Result<Value, String> {
-    tokio::task::spawn_blocking(move || call_helper(cmd, args))
-        .await
-        .map_err(|e| e.to_string())?
+/// 取消所有進行中的指定命令（例如使用者在轉錄時按 Esc），回傳取消的請求數。
+/// 等待中的呼叫者立即收到 `Cancelled` 錯誤，helper 收到 `cancel` 後自行中止工作。
+pub async fn cancel_command(cmd: &str) -> usize {
+    let Some(mutex) = HELPER.get() else {
+        return 0;
+    };
+    let Some(helper) = mutex.lock().await.clone() else {
+        return 0;
+    };
+
+    let cancelled: Vec<(u64, PendingRequest)> = {
+        let mut pending = helper.pending.lock().unwrap();
+        let ids: Vec<u64> = pending
+            .iter()
+            .filter(|(_, req)| req.cmd == cmd)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| pending.remove(&id).map(|req| (id, req)))
+            .collect()
+    };
+
+    for (id, req) in &cancelled {
+        log_debug!("[bridge] Cancelling request {} ({})", id, req.cmd);
+        helper.send_cancel(*id).await;
+    }
+    let count = cancelled.len();
+    for (_, req) in cancelled {
+        let _ = req.tx.send(Reply::Cancelled);
+    }
+    count
+}
+
+// MARK: - Timeouts
+
+/// 各命令的預設超時（秒）
+fn default_timeout_secs(cmd: &str) -> u64 {
+    match cmd {
+        "get_context" | "check_microphone_permission" | "check_accessibility" => 2,
+        "stop_recording" => 180,        // 長時間聽寫的 ASR
+        "request_microphone" => 120,    // 等待使用者回應系統對話框
+        "download_model" => 3600,
+        _ => HELPER_TIMEOUT_SECS,
+    }
+}
+
+/// 命令實際使用的超時：設定覆寫優先，其次為預設值
+pub fn timeout_for(cmd: &str) -> Duration {
+    let secs = TIMEOUT_OVERRIDES
+        .lock()
+        .unwrap()
+        .get(cmd)
+        .copied()
+        .unwrap_or_else(|| default_timeout_secs(cmd));
+    Duration::from_secs(secs)
+}
+
+/// 以設定值取代所有超時覆寫（0 秒的項目會被忽略）
+pub fn set_timeout_overrides(overrides: BTreeMap<String, u64>) {
+    *TIMEOUT_OVERRIDES.lock().unwrap() = overrides.into_iter().filter(|(_, secs)| *secs > 0).collect();
+}
+
+/// 目前連線中 helper 的握手資訊（必要時會先啟動 helper）
+pub async fn helper_info() -> Result<HelperInfo, String> {
+    Ok(acquire_helper().await?.info.clone())
 }
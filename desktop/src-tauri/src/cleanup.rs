@@ -0,0 +1,548 @@
+// cleanup.rs — 規則式清理（polish.rs 的第一個階段，不需要 LLM，結果可預測）
+//
+// 依序：
+//   1. 填充詞    um / uh、嗯 / 呃、「那個，」、えっと…（依語言）
+//   2. 口吃      "I I think"、"th- the"、「我我我覺得」、「我們，我們」
+//   3. 自我更正  "at 3 pm, no wait, 4 pm" → "at 4 pm"、「三點，不對，四點」→「四點」、
+//                "scratch that" 丟掉整句；標記必須自成一個子句（前後都有逗號或句子邊界），
+//                且更正的內容要能在前文找到對應的詞，否則原文不動
+//   4. 正規化    中英文之間加空白、依上下文轉換全形 / 半形標點、整理多餘空白
+//
+// 規則只處理明確的樣式；拿不準的（例如「謝謝」「研究研究」等疊字）一律保留。
+
+use crate::polish::is_cjk;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Chinese,
+    Japanese,
+}
+
+impl Language {
+    /// 由 ASR 回報的語言碼判斷；未知或 auto 時套用所有語言的規則
+    pub fn from_hint(hint: Option<&str>) -> &'static [Language] {
+        let hint = hint.unwrap_or_default().to_ascii_lowercase();
+        if hint.starts_with("en") {
+            &[Language::English]
+        } else if hint.starts_with("zh") || hint.starts_with("yue") {
+            &[Language::Chinese]
+        } else if hint.starts_with("ja") {
+            &[Language::Japanese]
+        } else {
+            &[Language::English, Language::Chinese, Language::Japanese]
+        }
+    }
+
+    fn rules(self) -> &'static Rules {
+        match self {
+            Language::English => &ENGLISH,
+            Language::Chinese => &CHINESE,
+            Language::Japanese => &JAPANESE,
+        }
+    }
+}
+
+/// 一個語言的規則表
+struct Rules {
+    /// 單獨出現即刪除
+    fillers: &'static [&'static str],
+    /// 後接逗號時才刪除（「那個人」不是填充詞）
+    comma_fillers: &'static [&'static str],
+    /// 在句首且後接逗號時才刪除（「問題就是，…」的「就是」是內容）
+    leading_fillers: &'static [&'static str],
+    /// 更正標記，前後須有逗號或句子邊界；標記後的內容取代前面對應的部分
+    corrections: &'static [&'static str],
+    /// 撤回標記：丟掉同一句中標記前的所有內容
+    retractions: &'static [&'static str],
+}
+
+const ENGLISH: Rules = Rules {
+    fillers: &["um", "umm", "uh", "uhh", "uhm", "erm", "hmm"],
+    comma_fillers: &["you know"],
+    leading_fillers: &[],
+    corrections: &["no wait", "no, wait", "wait, no", "sorry, I mean", "actually, no", "I mean"],
+    retractions: &["scratch that", "forget that", "never mind that"],
+};
+
+const CHINESE: Rules = Rules {
+    fillers: &["嗯", "呃", "欸"],
+    comma_fillers: &["那個", "這個"],
+    leading_fillers: &["就是", "然後"],
+    corrections: &["不對", "我是說", "講錯了", "更正"],
+    retractions: &["前面不算", "剛剛那句不算"],
+};
+
+const JAPANESE: Rules = Rules {
+    fillers: &["えっと", "えーと", "えー", "あのー"],
+    comma_fillers: &["あの", "まあ", "なんか"],
+    leading_fillers: &[],
+    corrections: &["じゃなくて", "いや"],
+    retractions: &["今のなし"],
+};
+
+/// 連續重複也可能是刻意的英文單字
+const LEGIT_REPEATS: &[&str] = &["that", "had", "is", "very", "no", "yes", "bye", "so"];
+/// 單字重複兩次幾乎都是口吃的中文字（疊字「謝謝」「看看」不在此列）
+const STUTTER_CHARS: &[&str] = &["我", "你", "他", "她", "它", "這", "那", "就"];
+/// 笑聲等重複是內容本身
+const REPEATABLE_CHARS: &[&str] = &["哈", "呵", "嘿", "嘻", "啦"];
+
+/// 清理一段轉錄文字
+pub fn clean(text: &str, language: Option<&str>) -> String {
+    let languages = Language::from_hint(language);
+    let mut tokens = tokenize(text);
+    for lang in languages {
+        tokens = remove_fillers(&tokens, lang.rules());
+    }
+    tokens = collapse_stutters(&tokens);
+    for lang in languages {
+        tokens = resolve_corrections(tokens, lang.rules());
+    }
+    let joined: String = tokens.iter().map(|t| t.text.as_str()).collect();
+    normalize(&joined, languages == [Language::Japanese])
+}
+
+// MARK: - Tokens
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// 拉丁字母 / 數字組成的單字（含 I'm、e-mail、3.5、結尾的 th-）
+    Word,
+    /// 單一個中日韓字
+    Cjk,
+    Punct,
+    Space,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Token {
+    pub(crate) text: String,
+    pub(crate) kind: Kind,
+}
+
+impl Token {
+    fn space() -> Token {
+        Token { text: " ".into(), kind: Kind::Space }
+    }
+
+    pub(crate) fn is_unit(&self) -> bool {
+        matches!(self.kind, Kind::Word | Kind::Cjk)
+    }
+
+    pub(crate) fn is_comma(&self) -> bool {
+        self.kind == Kind::Punct && matches!(self.text.as_str(), "," | "，" | "、")
+    }
+
+    pub(crate) fn is_sentence_end(&self) -> bool {
+        self.kind == Kind::Punct && matches!(self.text.as_str(), "." | "!" | "?" | "。" | "！" | "？")
+    }
+
+    /// 比對時忽略大小寫，各種逗號視為相同
+    fn same(&self, other: &Token) -> bool {
+        if self.is_comma() && other.is_comma() {
+            return true;
+        }
+        self.kind == other.kind && self.text.to_lowercase() == other.text.to_lowercase()
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() && !is_cjk(c)
+}
+
+pub(crate) fn tokenize(text: &str) -> Vec<Token> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let kind = if c.is_whitespace() {
+            Kind::Space
+        } else if is_cjk(c) {
+            Kind::Cjk
+        } else if is_word_char(c) {
+            Kind::Word
+        } else {
+            Kind::Punct
+        };
+
+        let mut end = i + 1;
+        match kind {
+            Kind::Space => {
+                while end < chars.len() && chars[end].is_whitespace() {
+                    end += 1;
+                }
+            }
+            Kind::Word => {
+                while end < chars.len() && (is_word_char(chars[end]) || joins_word(&chars, end)) {
+                    end += 1;
+                }
+            }
+            _ => {}
+        }
+        tokens.push(Token { text: chars[i..end].iter().collect(), kind });
+        i = end;
+    }
+    tokens
+}
+
+/// 單字中間的 ' 與 -、數字中的小數點，以及單字結尾的 -（"th-"）
+fn joins_word(chars: &[char], i: usize) -> bool {
+    let next = chars.get(i + 1).copied();
+    let next_is_word = next.is_some_and(is_word_char);
+    match chars[i] {
+        '\'' | '’' => next_is_word,
+        '-' => next_is_word || next.map_or(true, char::is_whitespace),
+        '.' => chars[i - 1].is_ascii_digit() && next.is_some_and(|c| c.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+/// 從 `i` 開始略過空白
+pub(crate) fn skip_space(tokens: &[Token], mut i: usize) -> usize {
+    while tokens.get(i).is_some_and(|t| t.kind == Kind::Space) {
+        i += 1;
+    }
+    i
+}
+
+/// 在 `start` 比對片語（片語內與 token 間的空白不計）；成功時回傳結束位置
+pub(crate) fn match_phrase(tokens: &[Token], start: usize, phrase: &str) -> Option<usize> {
+    let mut i = start;
+    for (n, expected) in tokenize(phrase).iter().filter(|t| t.kind != Kind::Space).enumerate() {
+        if n > 0 {
+            i = skip_space(tokens, i);
+        }
+        if !tokens.get(i)?.same(expected) {
+            return None;
+        }
+        i += 1;
+    }
+    Some(i)
+}
+
+// MARK: - Fillers
+
+/// 填充詞出現的位置限制
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FillerPosition {
+    Anywhere,
+    BeforeComma,
+    SentenceStart,
+}
+
+fn remove_fillers(tokens: &[Token], rules: &Rules) -> Vec<Token> {
+    let fillers = rules.fillers.iter().map(|f| (f, FillerPosition::Anywhere));
+    let comma_fillers = rules.comma_fillers.iter().map(|f| (f, FillerPosition::BeforeComma));
+    let leading_fillers = rules.leading_fillers.iter().map(|f| (f, FillerPosition::SentenceStart));
+    let candidates: Vec<_> = fillers.chain(comma_fillers).chain(leading_fillers).collect();
+
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    'tokens: while i < tokens.len() {
+        if tokens[i].is_unit() {
+            let sentence_start = out.iter().rev().find(|t| t.kind != Kind::Space).map_or(true, Token::is_sentence_end);
+            for (filler, position) in &candidates {
+                let Some(end) = match_phrase(tokens, i, filler) else {
+                    continue;
+                };
+                let after = skip_space(tokens, end);
+                let comma = tokens.get(after).is_some_and(Token::is_comma);
+                let allowed = match position {
+                    FillerPosition::Anywhere => true,
+                    FillerPosition::BeforeComma => comma,
+                    FillerPosition::SentenceStart => comma && sentence_start,
+                };
+                if !allowed {
+                    continue;
+                }
+                // 連同其後的逗號一起刪除
+                i = if comma { after + 1 } else { end };
+                continue 'tokens;
+            }
+        }
+        out.push(tokens[i].clone());
+        i += 1;
+    }
+    out
+}
+
+// MARK: - Stutters
+
+fn collapse_stutters(tokens: &[Token]) -> Vec<Token> {
+    let mut out = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let repeated_at = match tokens[i].kind {
+            Kind::Word => word_stutter(tokens, i),
+            Kind::Cjk => cjk_stutter(tokens, i),
+            _ => None,
+        };
+        // 丟掉前一次（連同中間的空白、逗號），保留最後一次
+        match repeated_at {
+            Some(next) => i = next,
+            None => {
+                out.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// "I I" / "I, I" / "th- the"：回傳下一次出現的位置
+fn word_stutter(tokens: &[Token], i: usize) -> Option<usize> {
+    let mut j = skip_space(tokens, i + 1);
+    if tokens.get(j).is_some_and(Token::is_comma) {
+        j = skip_space(tokens, j + 1);
+    }
+    let next = tokens.get(j).filter(|t| t.kind == Kind::Word)?;
+    let word = tokens[i].text.to_lowercase();
+    let next_word = next.text.to_lowercase();
+
+    let partial = word.len() > 1 && word.ends_with('-') && next_word.starts_with(&word[..word.len() - 1]);
+    let repeat = word == next_word && !LEGIT_REPEATS.contains(&word.as_str());
+    (partial || repeat).then_some(j)
+}
+
+/// 連續 `n` 個中日韓字（中間不可有空白）
+fn cjk_run(tokens: &[Token], start: usize, n: usize) -> Option<String> {
+    let run = tokens.get(start..start + n)?;
+    run.iter().all(|t| t.kind == Kind::Cjk).then(|| run.iter().map(|t| t.text.as_str()).collect())
+}
+
+/// 「我我我」「我們，我們」：回傳下一次出現的位置
+fn cjk_stutter(tokens: &[Token], i: usize) -> Option<usize> {
+    for n in (1..=4).rev() {
+        let Some(unit) = cjk_run(tokens, i, n) else {
+            continue;
+        };
+        // 中文字之間的空白多半是 ASR 斷詞，不算分隔
+        let mut j = skip_space(tokens, i + n);
+        let mut separated = false;
+        if tokens.get(j).is_some_and(Token::is_comma) {
+            j = skip_space(tokens, j + 1);
+            separated = true;
+        }
+        if cjk_run(tokens, j, n).as_deref() != Some(unit.as_str()) {
+            continue;
+        }
+        if REPEATABLE_CHARS.contains(&unit.as_str()) {
+            return None;
+        }
+        let thrice = cjk_run(tokens, j + n, n).as_deref() == Some(unit.as_str());
+        if separated || thrice || (n == 1 && STUTTER_CHARS.contains(&unit.as_str())) {
+            return Some(j);
+        }
+    }
+    None
+}
+
+// MARK: - Self-corrections
+
+/// 找到的標記：token 範圍與是否為撤回
+struct Marker {
+    start: usize,
+    end: usize,
+    retract: bool,
+}
+
+fn resolve_corrections(mut tokens: Vec<Token>, rules: &Rules) -> Vec<Token> {
+    // 每次處理一個標記；無法套用的標記保留原文、從其後繼續找。上限避免異常輸入造成長時間迴圈
+    let mut from = 0;
+    for _ in 0..16 {
+        let Some(marker) = find_marker(&tokens, rules, from) else {
+            break;
+        };
+        from = marker.end;
+        if let Some(corrected) = apply_correction(&tokens, marker) {
+            tokens = corrected;
+            from = 0;
+        }
+    }
+    tokens
+}
+
+/// 從 `from` 找下一個更正或撤回標記；更正標記須自成一個子句（"no wait time" 是內容）
+fn find_marker(tokens: &[Token], rules: &Rules, from: usize) -> Option<Marker> {
+    let boundary = |t: Option<&Token>| t.map_or(true, |t| t.is_comma() || t.is_sentence_end());
+    let prev_is_boundary = |i: usize| boundary(tokens[..i].iter().rev().find(|t| t.kind != Kind::Space));
+    let next_is_boundary = |i: usize| boundary(tokens.get(skip_space(tokens, i)));
+
+    for start in (from..tokens.len()).filter(|&i| tokens[i].is_unit()) {
+        for phrase in rules.retractions {
+            if let Some(end) = match_phrase(tokens, start, phrase) {
+                return Some(Marker { start, end, retract: true });
+            }
+        }
+        for phrase in rules.corrections {
+            if let Some(end) = match_phrase(tokens, start, phrase) {
+                if prev_is_boundary(start) && next_is_boundary(end) {
+                    return Some(Marker { start, end, retract: false });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 套用標記；找不到要取代的位置時回傳 None（原文不動）
+fn apply_correction(tokens: &[Token], marker: Marker) -> Option<Vec<Token>> {
+    // 被更正的範圍限於同一句
+    let clause_start = tokens[..marker.start]
+        .iter()
+        .rposition(Token::is_sentence_end)
+        .map_or(0, |p| p + 1);
+    let mut fix_start = marker.end;
+    while tokens.get(fix_start).is_some_and(|t| t.kind == Kind::Space || t.is_comma()) {
+        fix_start += 1;
+    }
+    let fix_end = tokens[fix_start..]
+        .iter()
+        .position(Token::is_sentence_end)
+        .map_or(tokens.len(), |p| fix_start + p);
+
+    let cut = if marker.retract {
+        clause_start
+    } else {
+        let before: Vec<usize> = (clause_start..marker.start).filter(|&k| tokens[k].is_unit()).collect();
+        let fix: Vec<usize> = (fix_start..fix_end).filter(|&k| tokens[k].is_unit()).collect();
+        correction_cut(tokens, &before, &fix)?
+    };
+
+    let mut out = tokens[..cut].to_vec();
+    while out.last().is_some_and(|t| t.kind == Kind::Space || t.is_comma()) && out.len() > clause_start {
+        out.pop();
+    }
+    if out.last().is_some_and(|t| t.kind != Kind::Cjk) && tokens.get(fix_start).is_some_and(|t| t.kind == Kind::Word) {
+        out.push(Token::space());
+    }
+    out.extend_from_slice(&tokens[fix_start..]);
+    Some(out)
+}
+
+/// 決定從哪個 token 開始被更正取代：
+///   1. 更正的第一個詞出現在前文 → 從該詞最後一次出現處取代（"send it to John, no wait, send it to Mary"）
+///   2. 更正的第二個詞出現在前文 → 從其前一個詞取代（「三點，不對，四點」、"at 3 pm, no wait, 4 pm"）
+///   3. 否則無法確定被更正的範圍，回傳 None
+fn correction_cut(tokens: &[Token], before: &[usize], fix: &[usize]) -> Option<usize> {
+    let first = &tokens[*fix.first()?];
+    if let Some(&k) = before.iter().rev().find(|&&k| tokens[k].same(first)) {
+        return Some(k);
+    }
+    if let Some(second) = fix.get(1).map(|&k| &tokens[k]) {
+        if let Some(pos) = before.iter().rposition(|&k| tokens[k].same(second)) {
+            if pos > 0 {
+                return Some(before[pos - 1]);
+            }
+        }
+    }
+    None
+}
+
+// MARK: - Normalization
+
+/// 依上下文轉換標點寬度，並整理中英文之間與標點前後的空白
+fn normalize(text: &str, japanese: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+
+    // 1. 標點寬度：看前後最近的文字（略過數字），鄰近中日韓字用全形，否則用半形
+    let mut width: Vec<char> = Vec::with_capacity(chars.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let prev = width.iter().rev().find(|c| !c.is_whitespace()).copied();
+        let next = chars[i + 1..].iter().find(|c| !c.is_whitespace()).copied();
+        let prev_letter = width.iter().rev().find(|c| c.is_alphabetic()).copied();
+        let next_letter = chars[i + 1..].iter().find(|c| c.is_alphabetic()).copied();
+        let digits = prev.is_some_and(|p| p.is_ascii_digit()) && next.is_some_and(|n| n.is_ascii_digit());
+        let ellipsis = c == '.' && (prev == Some('.') || chars.get(i + 1) == Some(&'.'));
+
+        let c = if digits || ellipsis {
+            c
+        } else if prev_letter.is_some_and(is_cjk) || next_letter.is_some_and(is_cjk) {
+            to_full_width(c, japanese)
+        } else if prev_letter.is_some() {
+            to_half_width(c)
+        } else {
+            c
+        };
+        // 刪除填充詞後可能留下的重複逗號、句首逗號
+        let is_comma = matches!(c, ',' | '，' | '、');
+        if is_comma && (prev.is_none() || prev.is_some_and(|p| matches!(p, ',' | '，' | '、'))) {
+            continue;
+        }
+        width.push(c);
+    }
+
+    // 2. 空白
+    let mut out = String::with_capacity(text.len() + 8);
+    let mut i = 0;
+    while i < width.len() {
+        let c = width[i];
+        let prev = out.chars().last();
+
+        if c.is_whitespace() {
+            while i < width.len() && width[i].is_whitespace() {
+                i += 1;
+            }
+            let next = width.get(i).copied();
+            let keep = match (prev, next) {
+                (Some(p), Some(n)) => {
+                    let glued = (is_cjk(p) && is_cjk(n))
+                        || is_full_width_punct(p)
+                        || is_full_width_punct(n)
+                        || matches!(n, ',' | '.' | '!' | '?' | ';' | ':' | ')')
+                        || p == '(';
+                    !glued
+                }
+                _ => false,
+            };
+            if keep {
+                out.push(' ');
+            }
+            continue;
+        }
+
+        if let Some(p) = prev {
+            let cjk_latin = (is_cjk(p) && c.is_ascii_alphanumeric()) || (p.is_ascii_alphanumeric() && is_cjk(c));
+            // 千分位（"1,000"）兩側都是數字，不補空白
+            let digits = c.is_ascii_digit() && out.chars().rev().nth(1).is_some_and(|d| d.is_ascii_digit());
+            let after_punct = matches!(p, ',' | '!' | '?' | ';') && is_word_char(c) && !digits;
+            if cjk_latin || after_punct {
+                out.push(' ');
+            }
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+fn to_full_width(c: char, japanese: bool) -> char {
+    match c {
+        ',' if japanese => '、',
+        ',' => '，',
+        '.' => '。',
+        '!' => '！',
+        '?' => '？',
+        ':' => '：',
+        ';' => '；',
+        c => c,
+    }
+}
+
+fn to_half_width(c: char) -> char {
+    match c {
+        '，' | '、' => ',',
+        '。' => '.',
+        '！' => '!',
+        '？' => '?',
+        '：' => ':',
+        '；' => ';',
+        c => c,
+    }
+}
+
+fn is_full_width_punct(c: char) -> bool {
+    matches!(c, '，' | '、' | '。' | '！' | '？' | '：' | '；' | '「' | '」' | '『' | '』' | '（' | '）')
+}
@@ -1,38 +1,28 @@
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
-use tauri::Emitter;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{Emitter, Runtime};
 use tauri_plugin_autostart::ManagerExt;
-use crate::bridge::call_helper_async;
+use crate::bridge::{self, call_helper};
+use crate::cleanup;
+use crate::dictionary::{DictionaryEntry, Replacement};
+use crate::formatting::FormattingSettings;
+use crate::hotkey::{Bindings, HotkeyAction, HotkeyConfig, Timing, Trigger};
+use crate::llm::{self, LlmConfig};
+use crate::log_warn;
 use crate::permissions;
+use crate::polish::{self, Polisher};
+use crate::profiles::{self, Profile};
+use crate::protocol::{self, HelperInfo, HelperSettings, InjectionMethod};
+use crate::recorder;
+use crate::snippets::{Placeholders, Snippet};
+use crate::translation::TranslationSettings;
 
 // MARK: - Data Types
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AppContext {
-    pub app_name: Option<String>,
-    pub bundle_id: Option<String>,
-    pub window_title: Option<String>,
-    pub web_url: Option<String>,
-    pub web_domain: Option<String>,
-    pub web_title: Option<String>,
-    pub context_before: Option<String>,
-    pub context_after: Option<String>,
-    pub selected_text: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct HistoryEntry {
-    pub id: String,
-    pub transcript: String,
-    pub polished_text: Option<String>,
-    pub app_name: Option<String>,
-    pub window_title: Option<String>,
-    pub web_url: Option<String>,
-    pub web_domain: Option<String>,
-    pub web_title: Option<String>,
-    pub asr_engine: String,
-    pub created_at: String,
-}
+// 與 helper 共用的線上型別定義在 protocol.rs
+pub use crate::protocol::{AppContext, AudioDevice, HistoryEntry};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RecordingResult {
@@ -40,96 +30,256 @@ pub struct RecordingResult {
     pub polished_text: String,
     pub duration_seconds: f64,
     pub asr_engine: String,
+    /// 詞典修正做了哪些替換
+    pub replacements: Vec<Replacement>,
+    /// 翻譯聽寫的目標語言；有值時 polished_text 是譯文
+    pub target_language: Option<String>,
+    /// 套用的設定檔名稱（profiles.rs）
+    pub profile: Option<String>,
+    /// 前端呼叫 inject_text 時使用的注入方式
+    pub injection: InjectionMethod,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AudioDevice {
-    pub id: String,
-    pub name: String,
-    pub is_default: bool,
+/// 最後一次 inject_text 的內容與目標欄位，供 undo_last_injection 使用
+#[derive(Debug, Clone)]
+struct Injection {
+    text: String,
+    /// 注入的是錄音結果時，撤銷可放回的原文（見 LAST_RESULT）
+    transcript: Option<String>,
+    method: InjectionMethod,
+    bundle_id: Option<String>,
+    window_title: Option<String>,
 }
 
-// MARK: - Helper: parse Value into typed struct
-
-fn parse<T: for<'de> Deserialize<'de>>(v: Value) -> Result<T, String> {
-    serde_json::from_value(v).map_err(|e| format!("Parse error: {}", e))
-}
+static LAST_INJECTION: Mutex<Option<Injection>> = Mutex::new(None);
+/// 最後一次錄音的 (polished_text, 原文)，inject_text 以此找回撤銷時要放回的文字；
+/// 聽寫的原文是原始轉錄，語音指令模式則是改寫前選取的文字
+static LAST_RESULT: Mutex<Option<(String, String)>> = Mutex::new(None);
 
 // MARK: - Tauri Commands (connected to EchoTypeHelper via bridge)
 
 /// 取得當前焦點 App 的上下文
 #[tauri::command]
 pub async fn get_context() -> Result<AppContext, String> {
-    let result = call_helper_async("get_context", json!({})).await?;
-    parse(result)
+    call_helper(protocol::GetContext {}).await
 }
 
 /// 開始錄音（在 helper 內啟動 AVAudioEngine）
 #[tauri::command]
 pub async fn start_recording() -> Result<String, String> {
-    let result = call_helper_async("start_recording", json!({})).await?;
-    Ok(result.as_str().unwrap_or("ok").to_string())
+    call_helper(protocol::StartRecording {}).await
 }
 
 /// 停止錄音、ASR 轉錄（whisper.cpp 或 MLX），回傳原始轉錄
 #[tauri::command]
 pub async fn stop_recording() -> Result<RecordingResult, String> {
+    finish_dictation(false).await
+}
+
+/// 翻譯聽寫（dictate-and-translate 綁定）：同 stop_recording，但不論是否啟用翻譯聽寫都翻譯成目標語言；
+/// 目前 App 沒有目標語言（也沒有 default_target）時回傳錯誤
+#[tauri::command]
+pub async fn stop_translate_recording() -> Result<RecordingResult, String> {
+    finish_dictation(true).await
+}
+
+async fn finish_dictation(translate: bool) -> Result<RecordingResult, String> {
     // 1. 停止錄音，取得 transcript
-    let asr_result = call_helper_async("stop_recording", json!({})).await?;
-
-    // H2 修復：統一錯誤處理，避免 unwrap_or 吞掉錯誤
-    let transcript = asr_result["transcript"]
-        .as_str()
-        .ok_or("Missing transcript field")?
-        .to_string();
-    let duration = asr_result["duration"]
-        .as_f64()
-        .ok_or("Missing duration field")?;
-    let engine = asr_result["asr_engine"]
-        .as_str()
-        .ok_or("Missing asr_engine field")?
-        .to_string();
+    let asr = call_helper(protocol::StopRecording {}).await?;
 
     // 2. 從設定取得潤飾模式
-    let settings = call_helper_async("get_settings", json!({})).await?;
-    let polisher_mode = settings["polisher_mode"].as_str().unwrap_or("none").to_string();
+    let mut settings = call_helper(protocol::GetSettings {}).await?;
 
-    // 3. 如果選擇本地或雲端潤飾，目前先 passthrough (模型整合在 helper 端 TODO)
-    let polished = if polisher_mode != "none" {
-        transcript.clone() // TODO: hook into LocalPolisher in helper
+    // 3. 取得上下文（非關鍵操作，失敗時以空上下文繼續），套用符合的設定檔
+    let ctx = call_helper(protocol::GetContext {}).await.unwrap_or_default();
+    let profiles = profiles::parse(&settings.profiles);
+    let profile = profiles::resolve(&profiles, &ctx);
+    if let Some(profile) = profile {
+        profile.apply(&mut settings);
+    }
+    let mut formatting = FormattingSettings::parse(&settings.formatting_commands);
+    if let Some(profile) = profile {
+        profile.apply_formatting(&mut formatting);
+    }
+
+    // 4. 依潤飾模式套用 pipeline；目前 App 設了目標語言時改為翻譯聽寫（不論潤飾模式都需要 LLM）
+    let mut translation = TranslationSettings::parse(&settings.translation);
+    translation.enabled |= translate;
+    // 翻譯聽寫綁定沒有目標語言可用時回報錯誤，不退回一般聽寫
+    if translate && translation.configured_target(&ctx).is_none() {
+        return Err("No translation target configured for this app".into());
+    }
+    let target_language = translation.target_for(&ctx, asr.language.as_deref());
+    let llm = if settings.polisher_mode == "cloud" || target_language.is_some() {
+        let api_key = call_helper(protocol::GetApiKey {}).await.unwrap_or_default();
+        Some(LlmConfig::from_settings(&settings, api_key))
+    } else {
+        None
+    };
+    let mut pipeline = polish::Pipeline::for_mode(&settings.polisher_mode, llm.clone());
+    if let (Some(target), Some(config)) = (&target_language, llm) {
+        pipeline = pipeline.with_translation(config, target);
+    }
+    let dictionary = if pipeline.has_stage("dictionary") {
+        call_helper(protocol::GetDictionary {}).await.unwrap_or_default()
     } else {
-        transcript.clone()
+        Vec::new()
+    };
+    let snippets = call_helper(protocol::GetSnippets {}).await.unwrap_or_default();
+    let placeholders = placeholder_values(&snippets, &ctx).await;
+    let polish_ctx = polish::PolishContext {
+        app: ctx.clone(),
+        language: asr.language.clone(),
+        output_language: settings.output_language.clone(),
+        dictionary,
+        tone: profile.and_then(Profile::tone),
+        formatting,
+        snippets,
+        placeholders,
+        ..Default::default()
     };
+    // 翻譯失敗時保留原文輸出，也不記錄目標語言
+    let (polished, source) = pipeline.run_capturing(&asr.transcript, &polish_ctx, "llm_translate").await;
+    let target_language = target_language.filter(|_| source.is_some());
 
-    // 4. 取得上下文並儲存歷史記錄（非關鍵操作，失敗僅記錄）
-    let ctx = call_helper_async("get_context", json!({})).await.ok();
-    if let Err(e) = call_helper_async("save_history", json!({
-        "transcript": transcript,
-        "polished_text": polished,
-        "app_name": ctx.as_ref().and_then(|c| c["app_name"].as_str()),
-        "window_title": ctx.as_ref().and_then(|c| c["window_title"].as_str()),
-        "web_url": ctx.as_ref().and_then(|c| c["web_url"].as_str()),
-        "web_domain": ctx.as_ref().and_then(|c| c["web_domain"].as_str()),
-        "web_title": ctx.as_ref().and_then(|c| c["web_title"].as_str()),
-        "asr_engine": engine,
-        "duration": duration
-    })).await {
+    // 5. 儲存歷史記錄（非關鍵操作，失敗僅記錄）；翻譯時同時保存原文與譯文
+    let (polished_text, translated_text) = match source {
+        Some(source) => (source, Some(polished.clone())),
+        None => (polished.clone(), None),
+    };
+    if let Err(e) = call_helper(protocol::SaveHistory {
+        transcript: asr.transcript.clone(),
+        polished_text: Some(polished_text),
+        translated_text,
+        target_language: target_language.clone(),
+        app_name: ctx.app_name,
+        window_title: ctx.window_title,
+        web_url: ctx.web_url,
+        web_domain: ctx.web_domain,
+        web_title: ctx.web_title,
+        asr_engine: asr.asr_engine.clone(),
+        duration: asr.duration,
+    }).await {
         eprintln!("[commands] Failed to save history: {}", e);
     }
 
-    Ok(RecordingResult {
-        transcript: transcript.clone(),
+    let result = RecordingResult {
+        transcript: asr.transcript,
         polished_text: polished,
-        duration_seconds: duration,
-        asr_engine: engine,
+        duration_seconds: asr.duration,
+        asr_engine: asr.asr_engine,
+        replacements: polish_ctx.replacements.take(),
+        target_language,
+        profile: profile.map(|p| p.name.clone()),
+        injection: profile.and_then(|p| p.injection).unwrap_or_default(),
+    };
+    *LAST_RESULT.lock().unwrap() = Some((result.polished_text.clone(), result.transcript.clone()));
+    Ok(result)
+}
+
+/// 片段佔位符的值；只在有片段用到時才向 helper 讀取日期與剪貼簿
+async fn placeholder_values(snippets: &[Snippet], ctx: &AppContext) -> Placeholders {
+    let clipboard = snippets.iter().any(|s| s.uses("{clipboard}"));
+    let values = if clipboard || snippets.iter().any(|s| s.uses("{date}")) {
+        call_helper(protocol::GetPlaceholderValues { clipboard }).await.unwrap_or_default()
+    } else {
+        protocol::PlaceholderValues::default()
+    };
+    Placeholders {
+        date: Some(values.date).filter(|date| !date.is_empty()),
+        clipboard: values.clipboard,
+        selected_text: ctx.selected_text.clone(),
+    }
+}
+
+/// 語音指令模式（fn + Ctrl）：停止錄音，把口述的指令透過 LLM 套用在選取的文字上。
+/// 回傳的 polished_text 由前端以 inject_text 取代選取範圍。
+#[tauri::command]
+pub async fn stop_command_recording() -> Result<RecordingResult, String> {
+    // 1. 停止錄音，轉錄出指令
+    let asr = call_helper(protocol::StopRecording {}).await?;
+    let instruction = cleanup::clean(&asr.transcript, asr.language.as_deref());
+
+    // 2. 選取的文字（錄音期間選取範圍不變）
+    let ctx = call_helper(protocol::GetContext {}).await?;
+    let selection = ctx.selected_text.clone().filter(|s| !s.trim().is_empty()).ok_or("No text is selected")?;
+
+    // 3. 與 cloud 潤飾共用 LLM 設定；本機伺服器可以不設 key。設定檔的語氣與輸出語言同樣適用
+    let mut settings = call_helper(protocol::GetSettings {}).await?;
+    let profiles = profiles::parse(&settings.profiles);
+    let profile = profiles::resolve(&profiles, &ctx);
+    if let Some(profile) = profile {
+        profile.apply(&mut settings);
+    }
+    let api_key = call_helper(protocol::GetApiKey {}).await.unwrap_or_default();
+    let config = LlmConfig { budget: llm::COMMAND_LATENCY_BUDGET, ..LlmConfig::from_settings(&settings, api_key) };
+    let polish_ctx = polish::PolishContext {
+        app: ctx.clone(),
+        language: asr.language.clone(),
+        output_language: settings.output_language.clone(),
+        tone: profile.and_then(Profile::tone),
+        ..Default::default()
+    };
+    let rewritten = polish::LlmCommand::new(config, instruction.clone()).polish(&selection, &polish_ctx).await?;
+
+    // 4. 儲存歷史記錄（非關鍵操作，失敗僅記錄）
+    if let Err(e) = call_helper(protocol::SaveHistory {
+        transcript: instruction.clone(),
+        polished_text: Some(rewritten.clone()),
+        app_name: ctx.app_name,
+        window_title: ctx.window_title,
+        web_url: ctx.web_url,
+        web_domain: ctx.web_domain,
+        web_title: ctx.web_title,
+        asr_engine: asr.asr_engine.clone(),
+        duration: asr.duration,
+        ..Default::default()
+    }).await {
+        eprintln!("[commands] Failed to save history: {}", e);
+    }
+
+    // 撤銷並還原時放回的是原本選取的文字，而不是口述的指令
+    *LAST_RESULT.lock().unwrap() = Some((rewritten.clone(), selection));
+
+    Ok(RecordingResult {
+        transcript: instruction,
+        polished_text: rewritten,
+        duration_seconds: asr.duration,
+        asr_engine: asr.asr_engine,
+        replacements: Vec::new(),
+        target_language: None,
+        profile: profile.map(|p| p.name.clone()),
+        injection: profile.and_then(|p| p.injection).unwrap_or_default(),
     })
 }
 
-/// 注入文字到焦點 App（AX API → 剪貼簿 fallback）
+/// 注入文字到焦點 App（預設 AX API → 剪貼簿 fallback；`method` 來自 RecordingResult.injection）
 #[tauri::command]
-pub async fn inject_text(app: tauri::AppHandle, text: String) -> Result<(), String> {
-    match call_helper_async("inject_text", json!({ "text": text.clone() })).await {
-        Ok(_) => Ok(()),
+pub async fn inject_text<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    text: String,
+    method: Option<InjectionMethod>,
+) -> Result<(), String> {
+    let method = method.unwrap_or_default();
+    match call_helper(protocol::InjectText { text: text.clone(), method }).await {
+        Ok(_) => {
+            // 記下注入的目標欄位（非關鍵操作，取不到上下文時撤銷不檢查目標）
+            let ctx = call_helper(protocol::GetContext {}).await.unwrap_or_default();
+            let transcript = LAST_RESULT
+                .lock()
+                .unwrap()
+                .as_ref()
+                .filter(|(polished, _)| *polished == text)
+                .map(|(_, transcript)| transcript.clone());
+            *LAST_INJECTION.lock().unwrap() = Some(Injection {
+                text,
+                transcript,
+                method,
+                bundle_id: ctx.bundle_id,
+                window_title: ctx.window_title,
+            });
+            Ok(())
+        }
         Err(e) => {
             // 注入失敗，emit 事件給前端，讓使用者手動複製
             let _ = app.emit("inject-failed", json!({ "text": text }));
@@ -138,17 +288,58 @@ pub async fn inject_text(app: tauri::AppHandle, text: String) -> Result<(), Stri
     }
 }
 
+/// 撤銷最後一次注入（Ctrl+Cmd+Z）：焦點仍在同一個欄位時刪除注入的文字；
+/// `restore_transcript` 時改放回原文（未潤飾的轉錄，或語音指令改寫前的選取文字；Ctrl+Cmd+Shift+Z）。
+/// 回傳放回的文字。
+#[tauri::command]
+pub async fn undo_last_injection(restore_transcript: Option<bool>) -> Result<Option<String>, String> {
+    let injection = LAST_INJECTION.lock().unwrap().clone().ok_or("Nothing to undo")?;
+
+    // 1. 焦點換了 App 或視窗就不動作，避免刪到別處的文字
+    let ctx = call_helper(protocol::GetContext {}).await?;
+    let changed = |recorded: &Option<String>, current: &Option<String>| {
+        matches!((recorded, current), (Some(a), Some(b)) if a != b)
+    };
+    if changed(&injection.bundle_id, &ctx.bundle_id) || changed(&injection.window_title, &ctx.window_title) {
+        return Err("The focused field changed since the last injection".into());
+    }
+    // 讀得到游標前的文字時，確認注入的內容還在游標前。
+    // helper 只回傳游標前約 200 字，較長的注入只比對重疊的結尾
+    let still_there = |before: &str| {
+        before.ends_with(&injection.text)
+            || (!before.is_empty() && before.len() < injection.text.len() && injection.text.ends_with(before))
+    };
+    if ctx.context_before.as_deref().is_some_and(|before| !still_there(before)) {
+        return Err("The injected text is no longer before the cursor".into());
+    }
+
+    // 2. 刪除注入的文字；之後不能再撤銷同一次注入
+    call_helper(protocol::DeleteBackward { text: injection.text.clone() }).await?;
+    *LAST_INJECTION.lock().unwrap() = None;
+
+    // 3. 放回原始轉錄（本身也可以再撤銷）
+    let transcript = injection.transcript.filter(|t| restore_transcript.unwrap_or(false) && *t != injection.text);
+    if let Some(transcript) = &transcript {
+        call_helper(protocol::InjectText { text: transcript.clone(), method: injection.method }).await?;
+        *LAST_INJECTION.lock().unwrap() = Some(Injection {
+            text: transcript.clone(),
+            transcript: None,
+            ..injection
+        });
+    }
+    Ok(transcript)
+}
+
 /// 取得歷史記錄（從 SQLite via GRDB）
 #[tauri::command]
 pub async fn get_history(limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
-    let result = call_helper_async("get_history", json!({ "limit": limit.unwrap_or(50) })).await?;
-    parse(result)
+    call_helper(protocol::GetHistory { limit: limit.unwrap_or(50) }).await
 }
 
 /// 取得全部設定
 #[tauri::command]
-pub async fn get_settings() -> Result<Value, String> {
-    call_helper_async("get_settings", json!({})).await
+pub async fn get_settings() -> Result<HelperSettings, String> {
+    call_helper(protocol::GetSettings {}).await
 }
 
 /// 儲存單一設定（同步反映到 ASR Manager）
@@ -165,15 +356,135 @@ pub async fn set_setting(app: tauri::AppHandle, key: String, value: String) -> R
         }
     }
 
-    call_helper_async("set_setting", json!({ "key": key, "value": value })).await?;
+    // 特殊處理：helperTimeouts 先驗證格式，儲存後立即生效
+    let timeouts = if key == "helperTimeouts" {
+        Some(parse_timeouts(&value)?)
+    } else {
+        None
+    };
+
+    // 特殊處理：hotkey 先驗證觸發鍵與時間參數（含與目前綁定的衝突），儲存後立即替換
+    let hotkey = if key == "hotkey" {
+        let config = HotkeyConfig::parse(&value)?;
+        let trigger = config.trigger()?;
+        let settings = call_helper(protocol::GetSettings {}).await?;
+        Bindings::parse(&settings.hotkey_bindings)
+            .unwrap_or_default()
+            .validate(trigger)?;
+        Some((trigger, config.timing()?))
+    } else {
+        None
+    };
+
+    // 特殊處理：profiles 與 set_profiles 相同，格式錯誤、pattern 無法編譯或潤飾模式未知時拒絕
+    if key == "profiles" {
+        profiles::validate(&profiles::parse_strict(&value)?)?;
+    }
+
+    // 特殊處理：hotkeyBindings 與 set_hotkey_bindings 相同，先驗證格式與衝突，儲存後立即替換
+    let bindings = if key == "hotkeyBindings" {
+        let bindings = Bindings::parse(&value)?;
+        let settings = call_helper(protocol::GetSettings {}).await?;
+        let trigger = HotkeyConfig::parse(&settings.hotkey)
+            .and_then(|config| config.trigger())
+            .unwrap_or_default();
+        bindings.validate(trigger)?;
+        Some(bindings)
+    } else {
+        None
+    };
+
+    call_helper(protocol::SetSetting { key, value }).await?;
+
+    if let Some(timeouts) = timeouts {
+        bridge::set_timeout_overrides(timeouts);
+    }
+    if let Some((trigger, timing)) = hotkey {
+        apply_hotkey(trigger, timing);
+    }
+    if let Some(bindings) = bindings {
+        apply_bindings(bindings);
+    }
     Ok(())
 }
 
+// MARK: - Runtime Settings（需要在 Rust 端生效的設定）
+
+/// 解析 `helper_timeouts` 設定（JSON 物件：cmd → 秒數）
+fn parse_timeouts(raw: &str) -> Result<BTreeMap<String, u64>, String> {
+    if raw.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    serde_json::from_str(raw).map_err(|e| format!("Invalid helperTimeouts: {}", e))
+}
+
+/// 將設定套用到 Rust 端各模組
+pub fn apply_runtime_settings(settings: &HelperSettings) {
+    match parse_timeouts(&settings.helper_timeouts) {
+        Ok(timeouts) => bridge::set_timeout_overrides(timeouts),
+        Err(e) => log_warn!("[commands] {}", e),
+    }
+    match HotkeyConfig::parse(&settings.hotkey) {
+        Ok(config) => {
+            let trigger = config.trigger().unwrap_or_else(|e| {
+                log_warn!("[commands] {}, falling back to fn", e);
+                Trigger::default()
+            });
+            let timing = config.timing().unwrap_or_else(|e| {
+                log_warn!("[commands] {}, using default timing", e);
+                Timing::default()
+            });
+            apply_hotkey(trigger, timing);
+        }
+        Err(e) => log_warn!("[commands] {}", e),
+    }
+    match Bindings::parse(&settings.hotkey_bindings) {
+        Ok(bindings) => apply_bindings(bindings),
+        Err(e) => {
+            log_warn!("[commands] {}, using default bindings", e);
+            apply_bindings(Bindings::default());
+        }
+    }
+}
+
+/// 替換 fn 鍵監聽的觸發鍵與時間參數（keyboard.rs 只在 macOS 編譯）
+fn apply_hotkey(trigger: Trigger, timing: Timing) {
+    #[cfg(target_os = "macos")]
+    {
+        crate::keyboard::set_trigger(trigger);
+        crate::keyboard::set_timing(timing);
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (trigger, timing);
+}
+
+/// 替換觸發鍵手勢與快捷鍵的動作綁定
+fn apply_bindings(bindings: Bindings) {
+    #[cfg(target_os = "macos")]
+    crate::keyboard::set_bindings(bindings);
+    #[cfg(not(target_os = "macos"))]
+    let _ = bindings;
+}
+
+/// 啟動時從 helper 載入設定並套用（失敗時沿用預設值）
+pub async fn load_runtime_settings() {
+    match call_helper(protocol::GetSettings {}).await {
+        Ok(settings) => apply_runtime_settings(&settings),
+        Err(e) => eprintln!("[commands] Failed to load settings: {}", e),
+    }
+}
+
+/// 取消進行中的 helper 命令（例如轉錄時使用者按 Esc），不會結束 helper 進程。
+/// 回傳被取消的請求數
+#[tauri::command]
+pub async fn cancel_helper_command(cmd: String) -> Result<usize, String> {
+    Ok(bridge::cancel_command(&cmd).await)
+}
+
 /// 取得麥克風列表
 #[tauri::command]
 pub async fn get_microphones() -> Result<Vec<AudioDevice>, String> {
-    let result = call_helper_async("get_microphones", json!({})).await?;
-    parse(result)
+    call_helper(protocol::GetMicrophones {}).await
 }
 
 // MARK: - v0.3.0 Permission Commands
@@ -190,9 +501,8 @@ pub struct PermissionStatusResponse {
 #[tauri::command]
 pub async fn check_permissions() -> Result<PermissionStatusResponse, String> {
     // H2 修復：麥克風權限查詢失敗應返回錯誤而非默認值
-    let mic_status = call_helper_async("check_microphone_permission", json!({}))
+    let mic_status = call_helper(protocol::CheckMicrophonePermission {})
         .await
-        .map(|v| v.as_str().unwrap_or("unknown").to_string())
         .unwrap_or_else(|e| {
             eprintln!("[commands] Failed to check microphone permission: {}", e);
             "error".to_string()
@@ -219,21 +529,112 @@ pub fn open_system_preferences(pane: String) -> Result<(), String> {
 /// 若用戶已決定（authorized/denied），此呼叫不會再次彈窗
 #[tauri::command]
 pub async fn request_microphone() -> Result<String, String> {
-    call_helper_async("request_microphone", json!({}))
-        .await
-        .map(|v| v.as_str().unwrap_or("denied").to_string())
+    call_helper(protocol::RequestMicrophone {}).await
+}
+
+/// 取得 helper 版本與支援的命令（診斷用）
+#[tauri::command]
+pub async fn get_helper_info() -> Result<HelperInfo, String> {
+    bridge::helper_info().await
+}
+
+/// helper 因連續崩潰停止重啟後，由使用者手動重新啟動
+#[tauri::command]
+pub async fn restart_helper() -> Result<HelperInfo, String> {
+    bridge::restart_helper().await
+}
+
+/// 開始錄製 bridge 流量（bug report 用），回傳 session 檔路徑
+#[tauri::command]
+pub fn start_bridge_recording(redact_text: bool) -> Result<String, String> {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let path = std::env::temp_dir()
+        .join("echotype")
+        .join("sessions")
+        .join(format!("session-{}.jsonl", millis));
+    recorder::start(&path, recorder::RecordOptions { redact_text })?;
+    Ok(path.display().to_string())
+}
+
+/// 結束錄製，回傳 session 檔路徑（未在錄製時為 None）
+#[tauri::command]
+pub fn stop_bridge_recording() -> Option<String> {
+    recorder::stop().map(|p| p.display().to_string())
 }
 
 /// 取得個人詞典
 #[tauri::command]
-pub async fn get_dictionary() -> Result<Vec<String>, String> {
-    let result = call_helper_async("get_dictionary", json!({})).await?;
-    parse(result)
+pub async fn get_dictionary() -> Result<Vec<DictionaryEntry>, String> {
+    call_helper(protocol::GetDictionary {}).await
 }
 
 /// 設定個人詞典
 #[tauri::command]
-pub async fn set_dictionary(words: Vec<String>) -> Result<(), String> {
-    call_helper_async("set_dictionary", json!({ "words": words })).await?;
+pub async fn set_dictionary(entries: Vec<DictionaryEntry>) -> Result<(), String> {
+    call_helper(protocol::SetDictionary { entries }).await?;
+    Ok(())
+}
+
+/// 取得語音片段
+#[tauri::command]
+pub async fn get_snippets() -> Result<Vec<Snippet>, String> {
+    call_helper(protocol::GetSnippets {}).await
+}
+
+/// 設定語音片段；觸發詞不可為空或重複
+#[tauri::command]
+pub async fn set_snippets(snippets: Vec<Snippet>) -> Result<(), String> {
+    let mut triggers = std::collections::HashSet::new();
+    for snippet in &snippets {
+        let trigger = snippet.trigger.trim().to_lowercase();
+        if trigger.is_empty() {
+            return Err("Snippet trigger must not be empty".into());
+        }
+        if !triggers.insert(trigger) {
+            return Err(format!("Duplicate snippet trigger: {}", snippet.trigger.trim()));
+        }
+    }
+    call_helper(protocol::SetSnippets { snippets }).await?;
+    Ok(())
+}
+
+/// 取得依 App / 網域覆寫設定的設定檔
+#[tauri::command]
+pub async fn get_profiles() -> Result<Vec<Profile>, String> {
+    let settings = call_helper(protocol::GetSettings {}).await?;
+    Ok(profiles::parse(&settings.profiles))
+}
+
+/// 儲存設定檔（順序即比對優先順序）；pattern 無法編譯時拒絕
+#[tauri::command]
+pub async fn set_profiles(profiles: Vec<Profile>) -> Result<(), String> {
+    profiles::validate(&profiles)?;
+    let value = serde_json::to_string(&profiles).map_err(|e| e.to_string())?;
+    call_helper(protocol::SetSetting { key: "profiles".into(), value }).await?;
+    Ok(())
+}
+
+/// 取得觸發鍵手勢與快捷鍵的動作綁定（動作名稱 → 綁定，未綁定為空字串）
+#[tauri::command]
+pub async fn get_hotkey_bindings() -> Result<BTreeMap<HotkeyAction, String>, String> {
+    let settings = call_helper(protocol::GetSettings {}).await?;
+    Ok(Bindings::parse(&settings.hotkey_bindings).unwrap_or_default().to_specs())
+}
+
+/// 儲存動作綁定並立即生效；格式錯誤或與觸發鍵、彼此衝突時拒絕
+#[tauri::command]
+pub async fn set_hotkey_bindings(bindings: BTreeMap<HotkeyAction, String>) -> Result<(), String> {
+    let parsed = Bindings::from_specs(&bindings)?;
+    let settings = call_helper(protocol::GetSettings {}).await?;
+    let trigger = HotkeyConfig::parse(&settings.hotkey)
+        .and_then(|config| config.trigger())
+        .unwrap_or_default();
+    parsed.validate(trigger)?;
+    let value = serde_json::to_string(&parsed.to_specs()).map_err(|e| e.to_string())?;
+    call_helper(protocol::SetSetting { key: "hotkeyBindings".into(), value }).await?;
+    apply_bindings(parsed);
     Ok(())
 }
@@ -0,0 +1,326 @@
+// dictionary.rs — 個人詞典：詞條（別名、大小寫規則）與誤辨識詞修正
+//
+// personalDictionary 是 JSON 陣列。舊版每個元素是字串，新版可以是物件：
+//   ["EchoType", {"term": "Kubernetes", "sounds_like": ["cooper netties"]}, {"term": "Go", "case": "sensitive"}]
+// 兩種寫法都接受，字串等同於只有 term 的詞條。
+//
+// correct() 在每個位置找出最佳的比對：完全相同（不分大小寫、忽略空白）→ sounds_like 別名
+// → 語音相近 → 編輯距離。拉丁文字的語音鍵是簡化的子音骨架（b/p、d/t、c/k/g…視為同音）；
+// 中日文只做完全比對（平假名與片假名視為相同）與 sounds_like 別名：沒有拼音表可判斷讀音，
+// 逐字比對會把「張小姐」改成「張小明」、「語音輸出」改成「語音輸入」。同音誤字請用 sounds_like 指定。
+
+use crate::cleanup::{self, Kind, Token};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// 拉丁詞模糊比對的最短長度；較短的詞（"Notion"）容易改到一般單字，只做完全與語音比對
+const FUZZY_MIN_LEN: usize = 7;
+/// 語音鍵最少要有幾個子音才採用（"NTN" 這種太短的骨架太常撞到其他字）
+const PHONETIC_MIN_LEN: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseRule {
+    /// 不分大小寫比對，一律改成詞典中的寫法（"github" → "GitHub"）
+    #[default]
+    Canonical,
+    /// 詞本身只在大小寫完全相同時才算，也不做模糊比對（避免 "Go" 改掉一般的 "go"）；
+    /// sounds_like 別名仍會替換
+    Sensitive,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DictionaryEntry {
+    /// 詞典中的寫法
+    pub term: String,
+    /// ASR 常見的誤辨識寫法（"cooper netties"、「台雞電」）
+    pub sounds_like: Vec<String>,
+    pub case: CaseRule,
+}
+
+impl DictionaryEntry {
+    pub fn new(term: impl Into<String>) -> DictionaryEntry {
+        DictionaryEntry { term: term.into(), sounds_like: Vec::new(), case: CaseRule::Canonical }
+    }
+}
+
+impl From<&str> for DictionaryEntry {
+    fn from(term: &str) -> DictionaryEntry {
+        DictionaryEntry::new(term)
+    }
+}
+
+impl<'de> Deserialize<'de> for DictionaryEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Term(String),
+            Entry {
+                term: String,
+                #[serde(default)]
+                sounds_like: Vec<String>,
+                #[serde(default)]
+                case: CaseRule,
+            },
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Term(term) => DictionaryEntry::new(term),
+            Raw::Entry { term, sounds_like, case } => DictionaryEntry { term, sounds_like, case },
+        })
+    }
+}
+
+// MARK: - Replacements
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// 只有大小寫或空白不同（"git hub" → "GitHub"）
+    Exact,
+    /// 符合 sounds_like 別名
+    Alias,
+    /// 拉丁詞的語音鍵相同
+    Phonetic,
+    /// 拉丁詞的編輯距離在容許範圍內
+    Fuzzy,
+}
+
+/// 一次替換，回傳給前端顯示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replacement {
+    /// 轉錄中的原文
+    pub original: String,
+    /// 替換成的詞典寫法
+    pub replacement: String,
+    pub kind: MatchKind,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Correction {
+    pub text: String,
+    pub replacements: Vec<Replacement>,
+}
+
+/// 潤飾期間收集替換紀錄（Dictionary 階段寫入，stop_recording 讀出）
+#[derive(Debug, Clone, Default)]
+pub struct ReplacementLog(Arc<Mutex<Vec<Replacement>>>);
+
+impl ReplacementLog {
+    pub fn extend(&self, replacements: Vec<Replacement>) {
+        if let Ok(mut log) = self.0.lock() {
+            log.extend(replacements);
+        }
+    }
+
+    pub fn take(&self) -> Vec<Replacement> {
+        self.0.lock().map(|mut log| std::mem::take(&mut *log)).unwrap_or_default()
+    }
+}
+
+// MARK: - Correction
+
+/// 比對用的詞條
+struct Prepared<'a> {
+    entry: &'a DictionaryEntry,
+    key: String,
+    units: usize,
+    cjk: bool,
+    phonetic: String,
+    aliases: Vec<String>,
+}
+
+impl<'a> Prepared<'a> {
+    fn new(entry: &'a DictionaryEntry) -> Option<Prepared<'a>> {
+        let tokens = cleanup::tokenize(entry.term.trim());
+        let units: Vec<&Token> = tokens.iter().filter(|t| t.is_unit()).collect();
+        if units.is_empty() {
+            return None;
+        }
+        let key = key_of(units.iter().copied());
+        Some(Prepared {
+            entry,
+            cjk: units.iter().all(|t| t.kind == Kind::Cjk),
+            phonetic: phonetic_key(&key),
+            units: units.len(),
+            aliases: entry
+                .sounds_like
+                .iter()
+                .map(|alias| key_of(cleanup::tokenize(alias).iter()))
+                .filter(|alias| !alias.is_empty())
+                .collect(),
+            key,
+        })
+    }
+
+    /// 別名最多幾個 token（決定要往後看多遠）
+    fn max_units(&self) -> usize {
+        let aliases = self.entry.sounds_like.iter().map(|a| cleanup::tokenize(a).iter().filter(|t| t.is_unit()).count());
+        // ASR 可能把一個詞拆成兩個（"GitHub" → "git hub"）
+        aliases.chain([self.units + 1]).max().unwrap_or(1)
+    }
+
+    /// 與一段轉錄比對；回傳 (種類, 距離)
+    fn match_span(&self, span: &Span) -> Option<(MatchKind, usize)> {
+        if self.aliases.contains(&span.key) {
+            return Some((MatchKind::Alias, 0));
+        }
+        if self.entry.case == CaseRule::Sensitive {
+            return None;
+        }
+        if span.key == self.key {
+            return Some((MatchKind::Exact, 0));
+        }
+
+        // 中日文不做模糊比對
+        if self.cjk || !span.latin {
+            return None;
+        }
+        let len = self.key.chars().count();
+        let span_len = span.key.chars().count();
+        let distance = edit_distance(&span.key, &self.key);
+        if self.phonetic.len() >= PHONETIC_MIN_LEN
+            && phonetic_key(&span.key) == self.phonetic
+            && distance * 2 <= len.max(span_len)
+        {
+            return Some((MatchKind::Phonetic, distance));
+        }
+        (len >= FUZZY_MIN_LEN && distance <= len / FUZZY_MIN_LEN).then_some((MatchKind::Fuzzy, distance))
+    }
+}
+
+/// 從某個位置開始、連續幾個 token 組成的片段
+struct Span {
+    key: String,
+    units: usize,
+    end: usize,
+    latin: bool,
+}
+
+/// 用詞典修正轉錄中的誤辨識詞，並回傳做了哪些替換
+pub fn correct(text: &str, dictionary: &[DictionaryEntry]) -> Correction {
+    let prepared: Vec<Prepared> = dictionary.iter().filter_map(Prepared::new).collect();
+    if prepared.is_empty() {
+        return Correction { text: text.to_string(), replacements: Vec::new() };
+    }
+    let max_units = prepared.iter().map(Prepared::max_units).max().unwrap_or(1);
+
+    let tokens = cleanup::tokenize(text);
+    let mut out = String::with_capacity(text.len());
+    let mut replacements = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if !tokens[i].is_unit() {
+            out.push_str(&tokens[i].text);
+            i += 1;
+            continue;
+        }
+
+        // 種類優先，其次距離，最後偏好較長的片段（"git hub" 先配 GitHub 而不是 Git）
+        let best = spans(&tokens, i, max_units)
+            .iter()
+            .flat_map(|span| {
+                prepared.iter().filter_map(move |p| {
+                    p.match_span(span).map(|(kind, distance)| ((kind, distance, std::cmp::Reverse(span.units)), span.end, p))
+                })
+            })
+            .min_by_key(|(score, _, _)| *score);
+
+        let Some(((kind, _, _), end, p)) = best else {
+            out.push_str(&tokens[i].text);
+            i += 1;
+            continue;
+        };
+        let original: String = tokens[i..end].iter().map(|t| t.text.as_str()).collect();
+        let term = p.entry.term.trim();
+        if original != term {
+            replacements.push(Replacement { original, replacement: term.to_string(), kind });
+        }
+        out.push_str(term);
+        i = end;
+    }
+    Correction { text: out, replacements }
+}
+
+/// 從 `start` 開始的 1..=max_units 個 token 片段（token 間只能隔空白）
+fn spans(tokens: &[Token], start: usize, max_units: usize) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut key = String::new();
+    let mut latin = true;
+    let mut i = start;
+    for units in 1..=max_units {
+        if units > 1 {
+            i = cleanup::skip_space(tokens, i);
+        }
+        let Some(token) = tokens.get(i).filter(|t| t.is_unit()) else {
+            break;
+        };
+        key.push_str(&fold(&token.text));
+        latin &= token.kind == Kind::Word;
+        i += 1;
+        spans.push(Span { key: key.clone(), units, end: i, latin });
+    }
+    spans
+}
+
+fn key_of<'t>(tokens: impl Iterator<Item = &'t Token>) -> String {
+    tokens.filter(|t| t.is_unit()).map(|t| fold(&t.text)).collect()
+}
+
+/// 比對用的正規化：小寫、片假名轉平假名
+fn fold(text: &str) -> String {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x30A1..=0x30F6 => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            _ => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// 簡化的子音骨架：首字母保留母音，其餘母音與 h / w 省略，發音相近的子音合併
+fn phonetic_key(key: &str) -> String {
+    let chars: Vec<char> = key.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    let mut out = String::new();
+    let mut previous = None;
+    for (i, &c) in chars.iter().enumerate() {
+        let next = chars.get(i + 1).copied();
+        let code = match c {
+            'a' | 'e' | 'i' | 'o' | 'u' | 'y' if i == 0 => 'A',
+            'a' | 'e' | 'i' | 'o' | 'u' | 'y' => {
+                previous = None;
+                continue;
+            }
+            'h' | 'w' => continue,
+            'p' if next == Some('h') => 'F',
+            'b' | 'p' => 'P',
+            'c' if matches!(next, Some('e' | 'i' | 'y')) => 'S',
+            'c' | 'g' | 'k' | 'q' => 'K',
+            'd' | 't' => 'T',
+            's' | 'z' | 'x' => 'S',
+            'f' | 'v' => 'F',
+            c => c.to_ascii_uppercase(),
+        };
+        if previous != Some(code) {
+            out.push(code);
+        }
+        previous = Some(code);
+    }
+    out
+}
+
+/// Levenshtein 距離（以字元計）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
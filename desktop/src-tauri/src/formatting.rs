@@ -0,0 +1,260 @@
+// formatting.rs — 口述格式指令（"new line"、"period"、「換行」、「句號」…）
+//
+// 在潤飾 pipeline 中（local / cloud）、標點修復之前，把口述的指令換成換行、條列或標點。
+// 預設關閉，需在設定中開啟。設定存在 `formattingCommands`（JSON）：
+//   {"enabled": true, "languages": ["en", "zh", "ja"], "disabled_apps": ["com.apple.Terminal"]}
+// disabled_apps 比對 AppContext.bundle_id。終端機預設停用，避免口述的換行直接執行命令。
+//
+// 指令只在子句邊界生效（話語開頭 / 結尾、ASR 加上的逗號或句號，或相鄰的另一個指令）：
+// 標點與右引號之後、左引號之前必須是邊界，換行與條列任一側是邊界即可。
+// 因此 "Hello period, how are you" 會轉換，句子中的 "the period ended"、"put a comma here" 照原文保留。
+
+use crate::cleanup::{self, Kind, Token};
+use crate::log_warn;
+use serde::{Deserialize, Serialize};
+
+/// 預設停用的 App（換行會直接送出）
+const DEFAULT_DISABLED_APPS: &[&str] = &["com.apple.Terminal", "com.googlecode.iterm2", "dev.warp.Warp-Stable"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormattingSettings {
+    pub enabled: bool,
+    /// 啟用的指令語言（`en`、`zh`、`ja`）
+    pub languages: Vec<String>,
+    /// 停用的 App bundle id
+    pub disabled_apps: Vec<String>,
+}
+
+impl Default for FormattingSettings {
+    fn default() -> Self {
+        FormattingSettings {
+            enabled: false,
+            languages: vec!["en".into(), "zh".into(), "ja".into()],
+            disabled_apps: DEFAULT_DISABLED_APPS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl FormattingSettings {
+    /// 解析設定值；空字串或格式錯誤時使用預設值
+    pub fn parse(raw: &str) -> FormattingSettings {
+        if raw.trim().is_empty() {
+            return FormattingSettings::default();
+        }
+        serde_json::from_str(raw).unwrap_or_else(|e| {
+            log_warn!("[formatting] Invalid formattingCommands setting, using defaults: {}", e);
+            FormattingSettings::default()
+        })
+    }
+
+    /// 是否對指定的 App 套用
+    pub fn applies_to(&self, bundle_id: Option<&str>) -> bool {
+        self.enabled
+            && !bundle_id.is_some_and(|id| self.disabled_apps.iter().any(|app| app.eq_ignore_ascii_case(id)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    /// 標點：黏在前一個字後面
+    Punct(&'static str),
+    /// 左引號 / 左括號：黏在下一個字前面
+    Open(&'static str),
+    /// 右引號 / 右括號
+    Close(&'static str),
+    /// 換行（"\n" 或空一行的 "\n\n"）
+    Break(&'static str),
+    /// 條列項目：換行並加上 "- "
+    Bullet,
+}
+
+use Action::*;
+
+const ENGLISH: &[(&str, Action)] = &[
+    ("new paragraph", Break("\n\n")),
+    ("new line", Break("\n")),
+    ("bullet point", Bullet),
+    ("period", Punct(".")),
+    ("full stop", Punct(".")),
+    ("comma", Punct(",")),
+    ("question mark", Punct("?")),
+    ("exclamation mark", Punct("!")),
+    ("exclamation point", Punct("!")),
+    ("colon", Punct(":")),
+    ("semicolon", Punct(";")),
+    ("open quote", Open("\"")),
+    ("close quote", Close("\"")),
+    ("end quote", Close("\"")),
+    ("open paren", Open("(")),
+    ("close paren", Close(")")),
+];
+
+const CHINESE: &[(&str, Action)] = &[
+    ("新段落", Break("\n\n")),
+    ("換段", Break("\n\n")),
+    ("換行", Break("\n")),
+    ("項目符號", Bullet),
+    ("句號", Punct("。")),
+    ("逗號", Punct("，")),
+    ("頓號", Punct("、")),
+    ("問號", Punct("？")),
+    ("驚嘆號", Punct("！")),
+    ("感嘆號", Punct("！")),
+    ("冒號", Punct("：")),
+    ("分號", Punct("；")),
+    ("上引號", Open("「")),
+    ("下引號", Close("」")),
+    ("左括號", Open("（")),
+    ("右括號", Close("）")),
+];
+
+const JAPANESE: &[(&str, Action)] = &[
+    ("新しい段落", Break("\n\n")),
+    ("改行", Break("\n")),
+    ("句点", Punct("。")),
+    ("読点", Punct("、")),
+    ("疑問符", Punct("？")),
+    ("かっことじ", Close("」")),
+    ("かっこ", Open("「")),
+];
+
+/// 啟用語言的指令，較長的片語優先比對（「かっことじ」先於「かっこ」）
+fn commands_for(languages: &[String]) -> Vec<(&'static str, Action)> {
+    let mut commands: Vec<(&'static str, Action)> = Vec::new();
+    for language in languages {
+        let table = match language.to_ascii_lowercase().get(..2) {
+            Some("en") => ENGLISH,
+            Some("zh") => CHINESE,
+            Some("ja") => JAPANESE,
+            _ => continue,
+        };
+        commands.extend_from_slice(table);
+    }
+    commands.sort_by_key(|(phrase, _)| std::cmp::Reverse(phrase.chars().count()));
+    commands
+}
+
+/// 把口述指令換成格式；在 `bundle_id` 停用或設定關閉時原樣回傳
+pub fn apply(text: &str, settings: &FormattingSettings, bundle_id: Option<&str>) -> String {
+    if !settings.applies_to(bundle_id) {
+        return text.to_string();
+    }
+    let commands = commands_for(&settings.languages);
+    if commands.is_empty() {
+        return text.to_string();
+    }
+
+    let tokens = cleanup::tokenize(text);
+    let matches = find_commands(&tokens, &commands);
+    let mut out = String::with_capacity(text.len());
+    // 半形標點之後的下一個英文字前要補空白
+    let mut space_next = false;
+    // 上一個輸出是指令（其後的標點是口述的，不是 ASR 加上的）
+    let mut after_command = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        if let Some(&(_, end, action)) = matches.iter().find(|(start, ..)| *start == i) {
+            // 指令前 ASR 自動加上的逗號不保留（前一個指令口述的標點保留）
+            if !after_command {
+                let trimmed = out.trim_end_matches([' ', '\t', ',', '，', '、']).len();
+                out.truncate(trimmed);
+            }
+            render(&mut out, action);
+            space_next = matches!(action, Punct(s) | Close(s) if s.is_ascii());
+            after_command = true;
+
+            // ASR 常在口述指令後自動加上逗號或句號
+            i = cleanup::skip_space(&tokens, end);
+            while tokens.get(i).is_some_and(|t| t.is_comma() || t.is_sentence_end()) {
+                i = cleanup::skip_space(&tokens, i + 1);
+            }
+            continue;
+        }
+
+        match token.kind {
+            Kind::Space => {
+                if !out.is_empty() && !out.ends_with(char::is_whitespace) {
+                    out.push_str(&token.text);
+                }
+            }
+            kind => {
+                if space_next && kind == Kind::Word {
+                    out.push(' ');
+                }
+                out.push_str(&token.text);
+                after_command = false;
+            }
+        }
+        space_next = false;
+        i += 1;
+    }
+    out.trim_end_matches([' ', '\t']).to_string()
+}
+
+/// 找出位於子句邊界的指令：(開始, 結束, 動作)。
+/// 先比對所有候選（較長的片語優先），再依動作檢查前後是否為邊界或相鄰的候選。
+fn find_commands(tokens: &[Token], commands: &[(&'static str, Action)]) -> Vec<(usize, usize, Action)> {
+    let mut candidates: Vec<(usize, usize, Action)> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let found = tokens[i]
+            .is_unit()
+            .then(|| commands.iter().find_map(|(phrase, action)| Some((cleanup::match_phrase(tokens, i, phrase)?, *action))))
+            .flatten();
+        match found {
+            Some((end, action)) => {
+                candidates.push((i, end, action));
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+
+    let is_boundary = |k: Option<usize>| k.map_or(true, |k| tokens[k].is_comma() || tokens[k].is_sentence_end());
+    candidates
+        .iter()
+        .filter(|&&(start, end, action)| {
+            let before = tokens[..start].iter().rposition(|t| t.kind != Kind::Space);
+            let after = Some(cleanup::skip_space(tokens, end)).filter(|&k| k < tokens.len());
+            let open_before = is_boundary(before) || candidates.iter().any(|&(_, e, _)| Some(e - 1) == before);
+            let open_after = is_boundary(after) || candidates.iter().any(|&(s, _, _)| Some(s) == after);
+            match action {
+                Punct(_) | Close(_) => open_after,
+                Open(_) => open_before,
+                Break(_) | Bullet => open_before || open_after,
+            }
+        })
+        .copied()
+        .collect()
+}
+
+fn render(out: &mut String, action: Action) {
+    match action {
+        Punct(symbol) | Close(symbol) => {
+            let trimmed = out.trim_end_matches([' ', '\t']).len();
+            out.truncate(trimmed);
+            out.push_str(symbol);
+        }
+        Open(symbol) => {
+            if symbol.is_ascii() && out.ends_with(|c: char| !c.is_whitespace() && c != '(' && c != '"') {
+                out.push(' ');
+            }
+            out.push_str(symbol);
+        }
+        Break(breaks) => {
+            let trimmed = out.trim_end_matches([' ', '\t']).len();
+            out.truncate(trimmed);
+            out.push_str(breaks);
+        }
+        Bullet => {
+            let trimmed = out.trim_end_matches([' ', '\t']).len();
+            out.truncate(trimmed);
+            if !out.is_empty() && !out.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str("- ");
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// framing.rs — helper 連線上的 length-prefixed framing
+//
+// 握手前（以及不支援的舊 helper）使用 JSON-line；hello 協商成功後，
+// hello 回應之後的所有訊息雙向改用：
+//
+//   ┌────────────┬─────────────┬──────────────┬──────────────┐
+//   │ header_len │ payload_len │ header       │ payload      │
+//   │ u32 BE     │ u32 BE      │ JSON (UTF-8) │ 原始位元組    │
+//   └────────────┴─────────────┴──────────────┴──────────────┘
+//
+// header 與 JSON-line 模式下的一行完全相同（請求 / 回應 / 事件），
+// payload 為可選的二進位資料（例如錄音 PCM），不需 base64。
+
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+pub const MAX_HEADER_LEN: usize = 1 << 20;   // 1 MiB
+pub const MAX_PAYLOAD_LEN: usize = 64 << 20; // 64 MiB
+
+/// 一個 frame：JSON header 與可選的二進位 payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub header: Vec<u8>,
+    pub payload: Vec<u8>,
+}
+
+/// 編碼一個 frame
+pub fn encode(header: &[u8], payload: &[u8]) -> Result<Vec<u8>, String> {
+    if header.len() > MAX_HEADER_LEN || payload.len() > MAX_PAYLOAD_LEN {
+        return Err(format!(
+            "Frame too large (header {} bytes, payload {} bytes)",
+            header.len(),
+            payload.len()
+        ));
+    }
+    let mut buf = Vec::with_capacity(8 + header.len() + payload.len());
+    buf.extend_from_slice(&(header.len() as u32).to_be_bytes());
+    buf.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    buf.extend_from_slice(header);
+    buf.extend_from_slice(payload);
+    Ok(buf)
+}
+
+/// 讀取下一個 frame；在 frame 邊界遇到 EOF 時回傳 `Ok(None)`。
+/// 長度超出上限代表串流已失去同步，回傳錯誤（連線無法再使用）。
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Option<Frame>> {
+    let mut lengths = [0u8; 8];
+    match reader.read_exact(&mut lengths[..1]).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    reader.read_exact(&mut lengths[1..]).await?;
+
+    let header_len = u32::from_be_bytes([lengths[0], lengths[1], lengths[2], lengths[3]]) as usize;
+    let payload_len = u32::from_be_bytes([lengths[4], lengths[5], lengths[6], lengths[7]]) as usize;
+    if header_len > MAX_HEADER_LEN || payload_len > MAX_PAYLOAD_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame too large (header {} bytes, payload {} bytes)", header_len, payload_len),
+        ));
+    }
+
+    let mut header = vec![0u8; header_len];
+    reader.read_exact(&mut header).await?;
+    let mut payload = vec![0u8; payload_len];
+    reader.read_exact(&mut payload).await?;
+    Ok(Some(Frame { header, payload }))
+}
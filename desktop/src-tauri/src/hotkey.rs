@@ -0,0 +1,873 @@
+// hotkey.rs — 觸發鍵狀態機（與平台無關）
+//
+// keyboard.rs 把 CGEventTap 事件轉成 KeyEvent 餵進來，再把回傳的 Action 轉成前端事件；
+// 時間一律從 Clock 讀取，測試以 ManualClock 推進虛擬時間，任何 OS 都能執行。
+//
+//   Idle ──按下──▶ Pending ──300ms（poll）──▶ Holding ──放開──▶ HoldEnd（≥ 500ms）/ Cancel(TooShort)
+//                    └──放開（< 300ms）──▶ Tap
+// Pending / Holding 期間按下其他鍵或不屬於任何 layer 的 modifier 即取消；
+// layer 是可以和觸發鍵一起按的 modifier 組合（預設只有 Ctrl，實際由 Bindings 決定），
+// Action 的 `modifiers` 即按下時的 layer，keyboard.rs 依此查出要執行的動作。
+// 距上一個觸發鍵事件不到 50ms 的事件只記錄按鍵狀態、不轉換狀態；
+// 因此 Pending 期間被濾掉的放開，會在 poll 時以按鍵已放開判定為 Tap。
+//
+// 雙擊鎖定（免持錄音）：Tap 之後 300ms 內再次按下 → Lock（Locked）；
+// Locked 時再按一次觸發鍵或按 Esc → Unlock。Locked 期間打字不會取消錄音。
+//
+// 觸發鍵由 `hotkey` 設定的 triggerKey 決定（Trigger）：fn、單側 modifier、F 鍵或 chord；
+// 會讓一般打字或系統快捷鍵誤觸的組合在解析時就拒絕。
+// 觸發鍵手勢與快捷鍵對應到哪個動作由 `hotkeyBindings` 設定決定（Bindings），儲存時檢查衝突。
+// 上面的 300 / 500 / 50ms 是預設值，可在同一個設定中調整（Timing），立即生效。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 長按判定閾值
+pub const HOLD_THRESHOLD: Duration = Duration::from_millis(300);
+/// 最短有效錄音時長
+pub const MIN_RECORDING: Duration = Duration::from_millis(500);
+/// 忽略這段時間內的重複觸發鍵事件（flagsChanged 常有抖動）
+pub const DEBOUNCE: Duration = Duration::from_millis(50);
+/// 點按後在這段時間內再按下即為雙擊鎖定
+pub const DOUBLE_TAP: Duration = Duration::from_millis(300);
+
+/// 使用者可調整的範圍（毫秒）
+pub const HOLD_THRESHOLD_RANGE: RangeInclusive<u64> = 100..=1000;
+pub const MIN_RECORDING_RANGE: RangeInclusive<u64> = 0..=5000;
+pub const DEBOUNCE_RANGE: RangeInclusive<u64> = 0..=200;
+/// 0 = 停用雙擊鎖定
+pub const DOUBLE_TAP_RANGE: RangeInclusive<u64> = 0..=1000;
+
+/// 按鍵時間參數；預設為上面的常數，可由 `hotkey` 設定覆寫
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub hold_threshold: Duration,
+    pub min_recording: Duration,
+    pub debounce: Duration,
+    pub double_tap: Duration,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing {
+            hold_threshold: HOLD_THRESHOLD,
+            min_recording: MIN_RECORDING,
+            debounce: DEBOUNCE,
+            double_tap: DOUBLE_TAP,
+        }
+    }
+}
+
+// MARK: - Clock
+
+/// 單調時鐘；回傳自任意起點經過的時間
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+/// 實際時間
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock(Instant);
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// 手動推進的虛擬時鐘；clone 共用同一個時間
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        self.0.fetch_add(by.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_millis(self.0.load(Ordering::SeqCst))
+    }
+}
+
+// MARK: - Events & Actions
+
+/// 觸發鍵以外、同時按住的 modifier
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub cmd: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers { shift: false, ctrl: false, alt: false, cmd: false };
+    pub const CTRL: Modifiers = Modifiers { ctrl: true, ..Modifiers::NONE };
+
+    pub fn is_empty(self) -> bool {
+        self == Modifiers::NONE
+    }
+
+    /// 去掉 `other` 中按著的 modifier（chord 觸發鍵本身需要的 modifier 不算額外按下）
+    pub fn without(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            shift: self.shift && !other.shift,
+            ctrl: self.ctrl && !other.ctrl,
+            alt: self.alt && !other.alt,
+            cmd: self.cmd && !other.cmd,
+        }
+    }
+
+    pub fn contains(self, other: Modifiers) -> bool {
+        other.without(self).is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEvent {
+    /// 觸發鍵按下或放開，附帶當下的 modifier
+    Trigger { down: bool, modifiers: Modifiers },
+    /// 其他按鍵按下
+    KeyDown,
+    /// Esc 按下（Locked 時結束錄音，其他狀態同 KeyDown）
+    Escape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelReason {
+    /// 錄音中按了其他鍵或 modifier（使用者其實在按快捷鍵組合）
+    ComboKey,
+    /// 長按超過 300ms 但不到最短錄音時長
+    TooShort,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// 長按確認，開始錄音
+    HoldStart { modifiers: Modifiers },
+    /// 長按放開，停止錄音
+    HoldEnd { modifiers: Modifiers },
+    /// 短按（toggle 模式由前端切換錄音）
+    Tap { modifiers: Modifiers },
+    /// 雙擊，鎖定錄音（免持）
+    Lock { modifiers: Modifiers },
+    /// 結束鎖定的錄音
+    Unlock { modifiers: Modifiers },
+    /// 放棄進行中的錄音
+    Cancel(CancelReason),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// 閒置，等待觸發鍵按下
+    Idle,
+    /// 觸發鍵已按下，等待判定長按或點按
+    Pending,
+    /// 確認長按，正在錄音
+    Holding,
+    /// 雙擊鎖定，錄音中（不需按著）
+    Locked,
+}
+
+// MARK: - Trigger Spec
+
+/// 可單獨當觸發鍵的左右側 modifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifierKey {
+    LeftShift,
+    RightShift,
+    LeftCtrl,
+    RightCtrl,
+    LeftOption,
+    RightOption,
+    LeftCmd,
+    RightCmd,
+}
+
+impl ModifierKey {
+    const ALL: [(ModifierKey, &'static str); 8] = [
+        (ModifierKey::LeftShift, "left_shift"),
+        (ModifierKey::RightShift, "right_shift"),
+        (ModifierKey::LeftCtrl, "left_ctrl"),
+        (ModifierKey::RightCtrl, "right_ctrl"),
+        (ModifierKey::LeftOption, "left_option"),
+        (ModifierKey::RightOption, "right_option"),
+        (ModifierKey::LeftCmd, "left_cmd"),
+        (ModifierKey::RightCmd, "right_cmd"),
+    ];
+
+    pub fn name(self) -> &'static str {
+        ModifierKey::ALL.iter().find(|(key, _)| *key == self).map(|(_, name)| *name).unwrap_or_default()
+    }
+}
+
+/// chord 裡的一般按鍵
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// F1–F20
+    Function(u8),
+    /// a–z、0–9（小寫）
+    Char(char),
+    Space,
+    Escape,
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Key::Function(n) => write!(f, "F{}", n),
+            Key::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            Key::Space => write!(f, "Space"),
+            Key::Escape => write!(f, "Esc"),
+        }
+    }
+}
+
+/// 觸發鍵設定，字串格式例如 `fn`、`right_option`、`F13`、`cmd+alt+k`（不分大小寫）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Trigger {
+    /// fn（Globe）鍵
+    #[default]
+    Fn,
+    /// 單獨按下某一側的 modifier
+    Modifier(ModifierKey),
+    /// 一般按鍵，可加 modifier 組成 chord
+    Key { key: Key, modifiers: Modifiers },
+}
+
+impl std::str::FromStr for Trigger {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Trigger, String> {
+        let parts = split_spec(spec, "Trigger key")?;
+        let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+
+        // fn 與左右側 modifier 只能單獨使用
+        if let [part] = parts.as_slice() {
+            if let Some(trigger) = standalone(part) {
+                return Ok(trigger);
+            }
+        }
+        let (modifiers, key) = parse_chord(spec, &parts)?;
+        let Some(key) = key else {
+            return Err(format!(
+                "Trigger \"{}\" has only modifiers; use a single side such as right_option instead",
+                spec.trim()
+            ));
+        };
+        if !matches!(key, Key::Function(_)) && !modifiers.cmd && !modifiers.alt {
+            return Err(format!("\"{}\" needs Cmd or Option, otherwise typing it would trigger dictation", key));
+        }
+        check_reserved(key, modifiers)?;
+        Ok(Trigger::Key { key, modifiers })
+    }
+}
+
+/// 小寫、以 `+` 分段；每段的 `-` 與空白視為 `_`（"Right Alt" → "right_alt"）
+fn split_spec(spec: &str, what: &str) -> Result<Vec<String>, String> {
+    let spec = spec.trim().to_ascii_lowercase();
+    if spec.is_empty() {
+        return Err(format!("{} is empty", what));
+    }
+    let parts: Vec<String> = spec.split('+').map(|part| part.trim().replace(['-', ' '], "_")).collect();
+    if parts.iter().any(String::is_empty) {
+        return Err(format!("Invalid {} \"{}\"", what.to_ascii_lowercase(), spec));
+    }
+    Ok(parts)
+}
+
+fn standalone(part: &str) -> Option<Trigger> {
+    match part {
+        "fn" | "globe" => Some(Trigger::Fn),
+        _ => ModifierKey::ALL
+            .iter()
+            .find(|(_, name)| *name == part.replace("command", "cmd").replace("control", "ctrl").replace("alt", "option"))
+            .map(|(key, _)| Trigger::Modifier(*key)),
+    }
+}
+
+/// modifier + 至多一個按鍵（`cmd+alt+k`）
+fn parse_chord(spec: &str, parts: &[&str]) -> Result<(Modifiers, Option<Key>), String> {
+    let spec = spec.trim();
+    let mut modifiers = Modifiers::NONE;
+    let mut key = None;
+    for part in parts {
+        let flag = match *part {
+            "shift" => &mut modifiers.shift,
+            "ctrl" | "control" => &mut modifiers.ctrl,
+            "alt" | "option" | "opt" => &mut modifiers.alt,
+            "cmd" | "command" => &mut modifiers.cmd,
+            _ if standalone(part).is_some() => return Err(format!("\"{}\" can only be used on its own", part)),
+            _ => {
+                if key.replace(parse_key(part)?).is_some() {
+                    return Err(format!("\"{}\" has more than one key", spec));
+                }
+                continue;
+            }
+        };
+        if std::mem::replace(flag, true) {
+            return Err(format!("\"{}\" repeats \"{}\"", spec, part));
+        }
+    }
+    Ok((modifiers, key))
+}
+
+fn check_reserved(key: Key, modifiers: Modifiers) -> Result<(), String> {
+    if modifiers == (Modifiers { cmd: true, ..Modifiers::NONE }) && key == Key::Space {
+        return Err("Cmd+Space is reserved by macOS (Spotlight)".into());
+    }
+    Ok(())
+}
+
+/// 按著的 modifier 名稱，macOS 慣例順序 ⌃⌥⇧⌘
+fn modifier_names(modifiers: Modifiers) -> impl Iterator<Item = &'static str> {
+    [
+        (modifiers.ctrl, "ctrl"),
+        (modifiers.alt, "alt"),
+        (modifiers.shift, "shift"),
+        (modifiers.cmd, "cmd"),
+    ]
+    .into_iter()
+    .filter_map(|(held, name)| held.then_some(name))
+}
+
+fn parse_key(part: &str) -> Result<Key, String> {
+    match part {
+        "space" => return Ok(Key::Space),
+        "esc" | "escape" => return Ok(Key::Escape),
+        _ => {}
+    }
+    if let Some(n) = part.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return if (1..=20).contains(&n) {
+            Ok(Key::Function(n))
+        } else {
+            Err(format!("Unsupported function key \"{}\" (F1–F20)", part))
+        };
+    }
+    match part.chars().collect::<Vec<_>>().as_slice() {
+        [c] if c.is_ascii_alphanumeric() => Ok(Key::Char(*c)),
+        _ => Err(format!("Unknown key \"{}\"", part)),
+    }
+}
+
+impl std::fmt::Display for Trigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Trigger::Fn => write!(f, "fn"),
+            Trigger::Modifier(key) => write!(f, "{}", key.name()),
+            Trigger::Key { key, modifiers } => {
+                for name in modifier_names(*modifiers) {
+                    write!(f, "{}+", name)?;
+                }
+                write!(f, "{}", key)
+            }
+        }
+    }
+}
+
+// MARK: - Bindings
+
+/// 可綁定快捷鍵的動作（設定中以 kebab-case 名稱表示）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HotkeyAction {
+    /// 聽寫
+    Dictate,
+    /// 聽寫並翻譯成目標語言（不論是否啟用翻譯聽寫）
+    DictateAndTranslate,
+    /// 語音指令：改寫選取的文字
+    CommandOnSelection,
+    /// 貼上最後一次轉錄
+    PasteLast,
+    /// 撤銷最後一次注入（多按 Shift 時放回原始轉錄）
+    UndoLast,
+    /// 放棄進行中的錄音或轉錄
+    Cancel,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 6] = [
+        HotkeyAction::Dictate,
+        HotkeyAction::DictateAndTranslate,
+        HotkeyAction::CommandOnSelection,
+        HotkeyAction::PasteLast,
+        HotkeyAction::UndoLast,
+        HotkeyAction::Cancel,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HotkeyAction::Dictate => "dictate",
+            HotkeyAction::DictateAndTranslate => "dictate-and-translate",
+            HotkeyAction::CommandOnSelection => "command-on-selection",
+            HotkeyAction::PasteLast => "paste-last",
+            HotkeyAction::UndoLast => "undo-last",
+            HotkeyAction::Cancel => "cancel",
+        }
+    }
+
+    /// 預設綁定；空字串 = 未綁定
+    pub fn default_binding(self) -> &'static str {
+        match self {
+            HotkeyAction::Dictate => "trigger",
+            HotkeyAction::DictateAndTranslate => "trigger+shift",
+            HotkeyAction::CommandOnSelection => "trigger+ctrl",
+            HotkeyAction::PasteLast => "ctrl+cmd+v",
+            HotkeyAction::UndoLast => "ctrl+cmd+z",
+            HotkeyAction::Cancel => "",
+        }
+    }
+
+    /// 需要按住 / 點按的錄音動作只能綁在觸發鍵手勢上，其他動作只能綁快捷鍵
+    fn is_recording(self) -> bool {
+        matches!(
+            self,
+            HotkeyAction::Dictate | HotkeyAction::DictateAndTranslate | HotkeyAction::CommandOnSelection
+        )
+    }
+}
+
+/// 綁定，字串格式為 `trigger`、`trigger+shift`（觸發鍵手勢）或 `ctrl+cmd+v`（快捷鍵）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    /// 觸發鍵手勢（按住、點按、雙擊鎖定），與觸發鍵一起按的 modifier 區分動作
+    Trigger(Modifiers),
+    /// 一般快捷鍵，keyDown 時觸發
+    Shortcut { key: Key, modifiers: Modifiers },
+}
+
+impl std::str::FromStr for Binding {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Binding, String> {
+        let parts = split_spec(spec, "Binding")?;
+        let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+        if let Some(at) = parts.iter().position(|part| *part == "trigger") {
+            let mut rest = parts.clone();
+            rest.remove(at);
+            return match parse_chord(spec, &rest)? {
+                (modifiers, None) => Ok(Binding::Trigger(modifiers)),
+                (_, Some(key)) => Err(format!("\"{}\" combines the trigger with key {}", spec.trim(), key)),
+            };
+        }
+        let (modifiers, key) = parse_chord(spec, &parts)?;
+        let Some(key) = key else {
+            return Err(format!("Shortcut \"{}\" has no key", spec.trim()));
+        };
+        if !matches!(key, Key::Function(_)) && !modifiers.ctrl && !modifiers.cmd && !modifiers.alt {
+            return Err(format!("Shortcut \"{}\" needs Ctrl, Cmd or Option", spec.trim()));
+        }
+        check_reserved(key, modifiers)?;
+        Ok(Binding::Shortcut { key, modifiers })
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Trigger(modifiers) => {
+                // modifier 寫在 trigger 後面：`trigger+ctrl+shift`
+                write!(f, "trigger")?;
+                for name in modifier_names(*modifiers) {
+                    write!(f, "+{}", name)?;
+                }
+                Ok(())
+            }
+            Binding::Shortcut { key, modifiers } => {
+                for name in modifier_names(*modifiers) {
+                    write!(f, "{}+", name)?;
+                }
+                write!(f, "{}", key)
+            }
+        }
+    }
+}
+
+/// `hotkeyBindings` 設定（JSON 物件：動作名稱 → 綁定），例如
+///   {"dictate": "trigger", "command-on-selection": "trigger+ctrl", "cancel": "ctrl+cmd+x"}
+/// 沒列出的動作用預設綁定，空字串代表不綁定。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bindings(BTreeMap<HotkeyAction, Binding>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let specs = HotkeyAction::ALL.iter().map(|action| (*action, action.default_binding().to_string()));
+        Bindings::from_specs(&specs.collect()).expect("default bindings are valid")
+    }
+}
+
+impl Bindings {
+    pub fn parse(raw: &str) -> Result<Bindings, String> {
+        if raw.trim().is_empty() {
+            return Ok(Bindings::default());
+        }
+        let specs: BTreeMap<HotkeyAction, String> =
+            serde_json::from_str(raw).map_err(|e| format!("Invalid hotkeyBindings: {}", e))?;
+        Bindings::from_specs(&specs)
+    }
+
+    /// 從動作 → 綁定字串建立；沒列出的動作用預設值
+    pub fn from_specs(specs: &BTreeMap<HotkeyAction, String>) -> Result<Bindings, String> {
+        let mut bindings = BTreeMap::new();
+        for action in HotkeyAction::ALL {
+            let spec = specs.get(&action).map(String::as_str).unwrap_or(action.default_binding());
+            if spec.trim().is_empty() {
+                continue;
+            }
+            let binding: Binding = spec.parse().map_err(|e| format!("{}: {}", action.name(), e))?;
+            match (action.is_recording(), binding) {
+                (true, Binding::Shortcut { .. }) => {
+                    return Err(format!("{} can only be bound to the trigger key (trigger or trigger+modifier)", action.name()))
+                }
+                (false, Binding::Trigger(_)) => {
+                    return Err(format!("{} needs a keyboard shortcut such as ctrl+cmd+x", action.name()))
+                }
+                _ => {}
+            }
+            bindings.insert(action, binding);
+        }
+        Ok(Bindings(bindings))
+    }
+
+    /// 每個動作的綁定字串（未綁定為空字串），給前端編輯
+    pub fn to_specs(&self) -> BTreeMap<HotkeyAction, String> {
+        HotkeyAction::ALL
+            .iter()
+            .map(|action| (*action, self.0.get(action).map(Binding::to_string).unwrap_or_default()))
+            .collect()
+    }
+
+    pub fn get(&self, action: HotkeyAction) -> Option<Binding> {
+        self.0.get(&action).copied()
+    }
+
+    /// 檢查與觸發鍵、彼此之間的衝突
+    pub fn validate(&self, trigger: Trigger) -> Result<(), String> {
+        let mut seen: Vec<(Binding, HotkeyAction)> = Vec::new();
+        for (action, binding) in &self.0 {
+            // undo-last 多按 Shift 是另一個動作（放回原始轉錄），也要佔用
+            let mut occupied = vec![*binding];
+            if let (HotkeyAction::UndoLast, Binding::Shortcut { key, modifiers }) = (action, binding) {
+                occupied.push(Binding::Shortcut { key: *key, modifiers: Modifiers { shift: true, ..*modifiers } });
+            }
+            for binding in occupied {
+                if let Some((_, other)) = seen.iter().find(|(b, _)| *b == binding) {
+                    return Err(format!("{} and {} are both bound to {}", other.name(), action.name(), binding));
+                }
+                seen.push((binding, *action));
+            }
+
+            match (trigger, binding) {
+                // chord 觸發鍵本身的 modifier 無法再區分 layer
+                (Trigger::Key { modifiers: required, .. }, Binding::Trigger(layer)) if layer.without(required) != *layer =>
+                {
+                    return Err(format!("{} ({}) overlaps the trigger key {}", action.name(), binding, trigger));
+                }
+                // 快捷鍵會先被 chord 觸發鍵攔截
+                (Trigger::Key { key, modifiers: required }, Binding::Shortcut { key: k, modifiers })
+                    if *k == key && modifiers.contains(required) =>
+                {
+                    return Err(format!("{} ({}) collides with the trigger key {}", action.name(), binding, trigger));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 可以和觸發鍵一起按的 modifier 組合（給 HotkeyMachine::set_layers）
+    pub fn layers(&self) -> Vec<Modifiers> {
+        self.0
+            .values()
+            .filter_map(|binding| match binding {
+                Binding::Trigger(modifiers) if !modifiers.is_empty() => Some(*modifiers),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 觸發鍵手勢（按下時的 layer）對應的動作
+    pub fn trigger_action(&self, layer: Modifiers) -> Option<HotkeyAction> {
+        self.0.iter().find(|(_, binding)| **binding == Binding::Trigger(layer)).map(|(action, _)| *action)
+    }
+
+    /// 快捷鍵對應的動作；第二個值為多按了 Shift 的 undo-last（放回原始轉錄）
+    pub fn shortcut_action(&self, key: Key, held: Modifiers) -> Option<(HotkeyAction, bool)> {
+        let find = |modifiers: Modifiers| {
+            self.0.iter().find(|(_, binding)| **binding == Binding::Shortcut { key, modifiers }).map(|(action, _)| *action)
+        };
+        if let Some(action) = find(held) {
+            return Some((action, false));
+        }
+        let unshifted = held.without(Modifiers { shift: true, ..Modifiers::NONE });
+        match find(unshifted) {
+            Some(HotkeyAction::UndoLast) if held.shift => Some((HotkeyAction::UndoLast, true)),
+            _ => None,
+        }
+    }
+}
+
+/// `hotkey` 設定（JSON，與前端 hotkey-config.ts 的 HotkeyConfig 一致）：
+///   {"mode": "push_to_talk", "triggerKey": "right_option",
+///    "holdThresholdMs": 300, "minRecordingMs": 500, "debounceMs": 50, "doubleTapMs": 300}
+/// 舊版只存錄音模式字串（"push_to_talk" / "toggle"），此時觸發鍵為 fn、時間為預設值。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HotkeyConfig {
+    /// 錄音模式由前端處理；Rust 端兩種事件都會發送
+    pub mode: String,
+    pub trigger_key: String,
+    pub hold_threshold_ms: u64,
+    pub min_recording_ms: u64,
+    pub debounce_ms: u64,
+    pub double_tap_ms: u64,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        HotkeyConfig {
+            mode: "push_to_talk".into(),
+            trigger_key: "fn".into(),
+            hold_threshold_ms: HOLD_THRESHOLD.as_millis() as u64,
+            min_recording_ms: MIN_RECORDING.as_millis() as u64,
+            debounce_ms: DEBOUNCE.as_millis() as u64,
+            double_tap_ms: DOUBLE_TAP.as_millis() as u64,
+        }
+    }
+}
+
+impl HotkeyConfig {
+    pub fn parse(raw: &str) -> Result<HotkeyConfig, String> {
+        let raw = raw.trim();
+        let config = if raw.is_empty() {
+            HotkeyConfig::default()
+        } else if raw.starts_with('{') {
+            serde_json::from_str(raw).map_err(|e| format!("Invalid hotkey setting: {}", e))?
+        } else {
+            HotkeyConfig { mode: raw.to_string(), ..Default::default() }
+        };
+        if !matches!(config.mode.as_str(), "push_to_talk" | "toggle") {
+            return Err(format!("Unknown hotkey mode \"{}\"", config.mode));
+        }
+        Ok(config)
+    }
+
+    pub fn trigger(&self) -> Result<Trigger, String> {
+        self.trigger_key.parse()
+    }
+
+    /// 時間參數；超出範圍時回傳錯誤（不自動截斷，避免使用者以為設定已生效）
+    pub fn timing(&self) -> Result<Timing, String> {
+        let check = |name: &str, ms: u64, range: RangeInclusive<u64>| {
+            if range.contains(&ms) {
+                Ok(Duration::from_millis(ms))
+            } else {
+                Err(format!("{} must be between {} and {} ms, got {}", name, range.start(), range.end(), ms))
+            }
+        };
+        Ok(Timing {
+            hold_threshold: check("holdThresholdMs", self.hold_threshold_ms, HOLD_THRESHOLD_RANGE)?,
+            min_recording: check("minRecordingMs", self.min_recording_ms, MIN_RECORDING_RANGE)?,
+            debounce: check("debounceMs", self.debounce_ms, DEBOUNCE_RANGE)?,
+            double_tap: check("doubleTapMs", self.double_tap_ms, DOUBLE_TAP_RANGE)?,
+        })
+    }
+}
+
+// MARK: - State Machine
+
+pub struct HotkeyMachine<C: Clock = SystemClock> {
+    clock: C,
+    timing: Timing,
+    state: State,
+    pressed_at: Duration,
+    last_trigger: Option<Duration>,
+    /// 觸發鍵實際是否按著（含被 debounce 濾掉的事件）
+    key_down: bool,
+    /// 按下時的 layer（與觸發鍵一起按的 modifier）
+    layer: Modifiers,
+    /// 允許的 layer；其他 modifier 組合視為別的快捷鍵
+    layers: Vec<Modifiers>,
+    /// 上一次 Tap 的時間（判定雙擊）
+    last_tap: Option<Duration>,
+}
+
+impl Default for HotkeyMachine {
+    fn default() -> Self {
+        HotkeyMachine::new(SystemClock::default())
+    }
+}
+
+impl<C: Clock> HotkeyMachine<C> {
+    pub fn new(clock: C) -> HotkeyMachine<C> {
+        HotkeyMachine {
+            clock,
+            timing: Timing::default(),
+            state: State::Idle,
+            pressed_at: Duration::ZERO,
+            last_trigger: None,
+            key_down: false,
+            layer: Modifiers::NONE,
+            layers: vec![Modifiers::CTRL],
+            last_tap: None,
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// 觸發鍵目前是否按著
+    pub fn is_down(&self) -> bool {
+        self.key_down
+    }
+
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+
+    /// 更換允許的 layer（Bindings::layers）；進行中的按壓沿用原本的 layer
+    pub fn set_layers(&mut self, layers: Vec<Modifiers>) {
+        self.layers = layers;
+    }
+
+    /// 放棄進行中的按壓或鎖定，不產生動作（例如已由快捷鍵取消錄音）
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+        self.last_tap = None;
+    }
+
+    /// 更換時間參數，立即生效（進行中的按壓以新閾值判定）
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.timing = timing;
+    }
+
+    /// Pending 時應在何時呼叫 poll（時鐘讀數）；其他狀態為 None
+    pub fn deadline(&self) -> Option<Duration> {
+        (self.state == State::Pending).then(|| self.pressed_at + self.timing.hold_threshold)
+    }
+
+    /// 距離 deadline 還有多久（已過期為 0）
+    pub fn until_deadline(&self) -> Option<Duration> {
+        self.deadline().map(|deadline| deadline.saturating_sub(self.clock.now()))
+    }
+
+    /// 計時器到期時呼叫：Pending 已滿長按閾值則轉為 Holding（按鍵已放開則為 Tap）。
+    /// 過期的計時器（按壓已取消或已是新的一次按壓）不會有動作。
+    pub fn poll(&mut self) -> Option<Action> {
+        let deadline = self.deadline()?;
+        if self.clock.now() < deadline {
+            return None;
+        }
+        if self.key_down {
+            self.state = State::Holding;
+            Some(Action::HoldStart { modifiers: self.layer })
+        } else {
+            Some(self.tap(self.clock.now()))
+        }
+    }
+
+    pub fn handle(&mut self, event: KeyEvent) -> Option<Action> {
+        match event {
+            // 鎖定錄音時使用者可能在其他地方打字，不取消
+            KeyEvent::KeyDown if self.state == State::Locked => None,
+            KeyEvent::Escape if self.state == State::Locked => Some(self.unlock()),
+            KeyEvent::KeyDown | KeyEvent::Escape => self.cancel(),
+            KeyEvent::Trigger { down, modifiers } => self.trigger(down, modifiers),
+        }
+    }
+
+    fn trigger(&mut self, down: bool, modifiers: Modifiers) -> Option<Action> {
+        let was_down = std::mem::replace(&mut self.key_down, down);
+        // layer 以外的 modifier 代表使用者在按別的快捷鍵組合
+        let is_layer = self.layers.contains(&modifiers);
+        if down && !modifiers.is_empty() {
+            match self.state {
+                // layer + 觸發鍵同時按下：照一般流程進入 Pending
+                State::Idle if is_layer => {}
+                // 觸發鍵先按、modifier 後按：仍在判定期間，切換 layer
+                State::Pending if is_layer => {
+                    self.layer = modifiers;
+                    return None;
+                }
+                State::Holding if modifiers == self.layer => return None,
+                // 鎖定中：帶 modifier 按下觸發鍵同樣結束錄音
+                State::Locked => {}
+                _ => return self.cancel(),
+            }
+        }
+
+        // 觸發鍵狀態沒變（例如按著觸發鍵時放開了 Ctrl）
+        if down == was_down {
+            return None;
+        }
+        let now = self.clock.now();
+        if self.last_trigger.is_some_and(|last| now.saturating_sub(last) < self.timing.debounce) {
+            return None;
+        }
+        self.last_trigger = Some(now);
+
+        match (self.state, down) {
+            // 點按後很快又按下：雙擊鎖定，沿用第一下的模式
+            (State::Idle, true) if self.is_double_tap(now) => {
+                self.state = State::Locked;
+                self.last_tap = None;
+                Some(Action::Lock { modifiers: self.layer })
+            }
+            (State::Idle, true) => {
+                self.state = State::Pending;
+                self.pressed_at = now;
+                self.layer = modifiers;
+                None
+            }
+            (State::Pending, false) => Some(self.tap(now)),
+            (State::Locked, true) => Some(self.unlock()),
+            (State::Holding, false) => {
+                self.state = State::Idle;
+                if now.saturating_sub(self.pressed_at) >= self.timing.min_recording {
+                    Some(Action::HoldEnd { modifiers: self.layer })
+                } else {
+                    Some(Action::Cancel(CancelReason::TooShort))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn tap(&mut self, now: Duration) -> Action {
+        self.state = State::Idle;
+        self.last_tap = Some(now);
+        Action::Tap { modifiers: self.layer }
+    }
+
+    fn is_double_tap(&self, now: Duration) -> bool {
+        let window = self.timing.double_tap;
+        !window.is_zero() && self.last_tap.is_some_and(|tap| now.saturating_sub(tap) <= window)
+    }
+
+    /// 結束鎖定；放開觸發鍵的事件在 Idle 時會被忽略
+    fn unlock(&mut self) -> Action {
+        self.state = State::Idle;
+        self.last_tap = None;
+        Action::Unlock { modifiers: self.layer }
+    }
+
+    /// 回到 Idle；只有已開始錄音（Holding）時需要通知前端
+    fn cancel(&mut self) -> Option<Action> {
+        let was_holding = self.state == State::Holding;
+        self.state = State::Idle;
+        was_holding.then_some(Action::Cancel(CancelReason::ComboKey))
+    }
+}
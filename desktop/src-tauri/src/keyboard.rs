@@ -4,17 +4,25 @@
 // 支援兩種錄音模式：
 //   - push_to_talk: 長按 fn > 300ms → 開始錄音 → 放開 → 停止
 //   - toggle:       短按 fn < 300ms → toggle 錄音開/關（hotkey-tap 事件，由前端處理）
+// 雙擊觸發鍵鎖定錄音（hotkey-locked），再按一次或 Esc 結束（hotkey-unlocked）。
+// 觸發鍵可在設定中更換（見 hotkey.rs 的 Trigger）：fn、單側 modifier（flagsChanged），
+// 或 F 鍵 / chord（keyDown + keyUp）。
+// 動作由 hotkeyBindings 設定決定（見 hotkey.rs 的 Bindings）：
+//   - 觸發鍵手勢：與觸發鍵一起按的 modifier 選擇動作，事件 payload 為動作名稱
+//     （預設 dictate、trigger+shift → dictate-and-translate、trigger+ctrl → command-on-selection）
+//   - 快捷鍵：paste-last（預設 Ctrl+Cmd+V）、undo-last（Ctrl+Cmd+Z，加 Shift 放回原始轉錄）、cancel
 //
-// 防誤觸策略：
-//   1. 50ms debounce（忽略 flagsChanged 事件抖動）
-//   2. 300ms 長按閾值（HOLD_THRESHOLD_MS）
-//   3. 500ms 最短有效錄音時長（MIN_RECORDING_MS）
+// 防誤觸策略（1–3 在 hotkey.rs 的狀態機中，可在任何 OS 上測試）：
+//   1. 50ms debounce（忽略 flagsChanged 事件抖動，可在設定中調整）
+//   2. 300ms 長按閾值（HOLD_THRESHOLD，可在設定中調整）
+//   3. 500ms 最短有效錄音時長（MIN_RECORDING，可在設定中調整）
 //   4. CGEventTap 被系統停用時自動 re-enable
 
+use crate::hotkey::{
+    Action, Bindings, CancelReason, HotkeyAction, HotkeyMachine, Key, KeyEvent, ModifierKey, Modifiers, Timing, Trigger,
+};
 use std::ffi::c_void;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
 // MARK: - FFI Types
@@ -32,6 +40,7 @@ const K_CG_HEAD_INSERT_EVENT_TAP: u32 = 0;
 const K_CG_EVENT_TAP_OPTION_LISTEN_ONLY: u32 = 1;
 const K_CG_EVENT_FLAGS_CHANGED: u32 = 12;
 const K_CG_EVENT_KEY_DOWN: u32 = 10;
+const K_CG_EVENT_KEY_UP: u32 = 11;
 const K_CG_EVENT_TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFFFFFE;
 
 const FN_FLAG_MASK: u64 = 0x800000; // kCGEventFlagMaskSecondaryFn
@@ -39,11 +48,16 @@ const SHIFT_FLAG: u64   = 0x020000; // kCGEventFlagMaskShift
 const CTRL_FLAG: u64    = 0x040000; // kCGEventFlagMaskControl
 const ALT_FLAG: u64     = 0x080000; // kCGEventFlagMaskAlternate
 const CMD_FLAG: u64     = 0x100000; // kCGEventFlagMaskCommand
-const COMBO_MASK: u64   = SHIFT_FLAG | CTRL_FLAG | ALT_FLAG | CMD_FLAG;
 
-const HOLD_THRESHOLD_MS: u64 = 300;  // 長按判定閾值
-const MIN_RECORDING_MS: u64 = 500;   // 最短有效錄音時長
-const DEBOUNCE_MS: u64 = 50;         // flagsChanged 去抖動
+// 區分左右側的 device flags（NX_DEVICE*KEYMASK）
+const LCTL_FLAG: u64   = 0x000001;
+const LSHIFT_FLAG: u64 = 0x000002;
+const RSHIFT_FLAG: u64 = 0x000004;
+const LCMD_FLAG: u64   = 0x000008;
+const RCMD_FLAG: u64   = 0x000010;
+const LALT_FLAG: u64   = 0x000020;
+const RALT_FLAG: u64   = 0x000040;
+const RCTL_FLAG: u64   = 0x002000;
 
 // MARK: - FFI 宣告
 
@@ -79,24 +93,13 @@ extern "C" {
     static kCFRunLoopCommonModes: *const c_void;
 }
 
-// MARK: - 狀態機
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum FnKeyState {
-    Idle,      // 閒置，等待 fn 按下
-    Pending,   // fn 已按下，等待 300ms 判定長按或點按
-    Holding,   // 確認長按（> 300ms），正在錄音
-}
-
 // MARK: - Context（CGEventTap callback 全域狀態）
 
 struct FnKeyContext {
-    state: Mutex<FnKeyState>,
-    press_instant: Mutex<Instant>,
-    last_event_ms: AtomicU64,
+    /// 狀態機本身與平台無關（hotkey.rs），這裡只負責餵事件與發送前端事件
+    machine: Mutex<HotkeyMachine>,
     app_handle: AppHandle,
     tap_ref: Mutex<Option<CFMachPortRef>>,
-    timer_cancelled: AtomicBool,  // 取消標記：防止計時器在 fn 放開後仍觸發
 }
 
 // SAFETY: Manual Send/Sync implementation for FnKeyContext
@@ -107,8 +110,7 @@ struct FnKeyContext {
 // 2. The tap_ref is only accessed under Mutex protection, ensuring exclusive access
 // 3. The CGEventTap callback runs on a dedicated CFRunLoop thread that we spawn and control
 // 4. AppHandle is already Send+Sync (Tauri guarantees this)
-// 5. Mutex<FnKeyState> and Mutex<Instant> are Send+Sync by definition
-// 6. AtomicU64 is Send+Sync by definition
+// 5. Mutex<HotkeyMachine> is Send+Sync by definition
 //
 // The CFMachPortRef itself is never directly accessed across threads without synchronization.
 // All mutations happen either:
@@ -121,6 +123,28 @@ unsafe impl Sync for FnKeyContext {}
 // 全域 context 指標（CGEventTap C callback 需要，只在 start_fn_key_listener 中設定一次）
 static GLOBAL_CTX: std::sync::OnceLock<Arc<FnKeyContext>> = std::sync::OnceLock::new();
 
+// 目前的觸發鍵、時間參數與綁定（設定變更時即時替換，不需重建 CGEventTap）；None = 預設值
+static TRIGGER: Mutex<Trigger> = Mutex::new(Trigger::Fn);
+static TIMING: Mutex<Option<Timing>> = Mutex::new(None);
+static BINDINGS: Mutex<Option<Bindings>> = Mutex::new(None);
+
+fn with_bindings<T>(f: impl FnOnce(&Bindings) -> T) -> T {
+    let guard = BINDINGS.lock().unwrap();
+    match guard.as_ref() {
+        Some(bindings) => f(bindings),
+        None => f(&Bindings::default()),
+    }
+}
+
+fn new_machine() -> HotkeyMachine {
+    let mut machine = HotkeyMachine::default();
+    if let Some(timing) = *TIMING.lock().unwrap() {
+        machine.set_timing(timing);
+    }
+    machine.set_layers(with_bindings(Bindings::layers));
+    machine
+}
+
 // MARK: - CGEventTap Callback
 
 extern "C" fn event_tap_callback(
@@ -142,34 +166,38 @@ extern "C" fn event_tap_callback(
         return event;
     }
 
-    // 處理 keyDown 事件
-    if event_type == K_CG_EVENT_KEY_DOWN {
+    let trigger = *TRIGGER.lock().unwrap();
+
+    // 處理 keyDown / keyUp 事件
+    if event_type == K_CG_EVENT_KEY_DOWN || event_type == K_CG_EVENT_KEY_UP {
         if let Some(ctx) = GLOBAL_CTX.get() {
             let flags = unsafe { CGEventGetFlags(event) };
             let keycode = unsafe { CGEventGetIntegerValueField(event, 9) }; // kCGKeyboardEventKeycode = 9
 
-            // 偵測 Ctrl+Cmd+V (keycode 0x09 = V key)
-            const V_KEYCODE: i64 = 0x09;
-            let has_cmd = (flags & CMD_FLAG) != 0;
-            let has_ctrl = (flags & CTRL_FLAG) != 0;
-
-            if keycode == V_KEYCODE && has_cmd && has_ctrl {
-                let _ = ctx.app_handle.emit("paste-last-transcript", "ctrl-cmd-v");
+            // F 鍵 / chord 觸發鍵：按下時需按著 chord 的 modifier，多按的 modifier 交給狀態機判定
+            if let Trigger::Key { key, modifiers: required } = trigger {
+                let held = modifiers(flags);
+                if keycode == key_code(key) && (event_type == K_CG_EVENT_KEY_UP || held.contains(required)) {
+                    let down = event_type == K_CG_EVENT_KEY_DOWN;
+                    dispatch(ctx, KeyEvent::Trigger { down, modifiers: held.without(required) });
+                    return event;
+                }
+            }
+            if event_type == K_CG_EVENT_KEY_UP {
                 return event;
             }
 
-            // 如果在 Pending/Holding 狀態收到任何按鍵，取消錄音
-            let mut state = ctx.state.lock().unwrap();
-            if *state == FnKeyState::Pending || *state == FnKeyState::Holding {
-                // H1 修復：Pending 狀態也需要設置取消標記
-                if *state == FnKeyState::Pending {
-                    ctx.timer_cancelled.store(true, Ordering::Release);
-                }
-                if *state == FnKeyState::Holding {
-                    let _ = ctx.app_handle.emit("hotkey-cancelled", "combo-key");
-                }
-                *state = FnKeyState::Idle;
+            // 綁定的快捷鍵（paste-last / undo-last / cancel）
+            let shortcut = key_from_code(keycode)
+                .and_then(|key| with_bindings(|bindings| bindings.shortcut_action(key, modifiers(flags))));
+            if let Some((action, shifted)) = shortcut {
+                run_shortcut(ctx, action, shifted);
+                return event;
             }
+
+            // 如果在 Pending/Holding 狀態收到任何按鍵，取消錄音；Locked 時只有 Esc 會結束錄音
+            const ESC_KEYCODE: i64 = 0x35;
+            dispatch(ctx, if keycode == ESC_KEYCODE { KeyEvent::Escape } else { KeyEvent::KeyDown });
         }
         return event;
     }
@@ -179,116 +207,214 @@ extern "C" fn event_tap_callback(
     }
 
     let flags = unsafe { CGEventGetFlags(event) };
-    let fn_down = (flags & FN_FLAG_MASK) != 0;
 
     if let Some(ctx) = GLOBAL_CTX.get() {
-        process_fn_event(ctx, fn_down, flags);
+        let key_event = match trigger {
+            Trigger::Fn => Some(KeyEvent::Trigger { down: (flags & FN_FLAG_MASK) != 0, modifiers: modifiers(flags) }),
+            // 單側 modifier：另一側同名 modifier 仍算額外按下
+            Trigger::Modifier(key) => {
+                let mask = device_flag(key);
+                Some(KeyEvent::Trigger { down: (flags & mask) != 0, modifiers: device_modifiers(flags & !mask) })
+            }
+            // chord 按著時多按 modifier 也要交給狀態機判定（可能是其他組合鍵）
+            Trigger::Key { modifiers: required, .. } => {
+                let down = ctx.machine.lock().unwrap().is_down();
+                down.then(|| KeyEvent::Trigger { down, modifiers: modifiers(flags).without(required) })
+            }
+        };
+        if let Some(key_event) = key_event {
+            dispatch(ctx, key_event);
+        }
     }
 
     event
 }
 
-fn process_fn_event(ctx: &Arc<FnKeyContext>, fn_down: bool, flags: u64) {
-    // 如果 fn 與其他 modifier 同時按下（Shift/Ctrl/Alt/Cmd），忽略此事件
-    if fn_down && (flags & COMBO_MASK) != 0 {
-        let mut state = ctx.state.lock().unwrap();
-        // H1 修復：Pending 狀態也需要設置取消標記
-        if *state == FnKeyState::Pending {
-            ctx.timer_cancelled.store(true, Ordering::Release);
-        }
-        if *state == FnKeyState::Holding {
-            let _ = ctx.app_handle.emit("hotkey-cancelled", "combo-key");
-        }
-        *state = FnKeyState::Idle;
-        return;
+fn modifiers(flags: u64) -> Modifiers {
+    Modifiers {
+        shift: (flags & SHIFT_FLAG) != 0,
+        ctrl: (flags & CTRL_FLAG) != 0,
+        alt: (flags & ALT_FLAG) != 0,
+        cmd: (flags & CMD_FLAG) != 0,
     }
+}
 
-    // Debounce：忽略 50ms 內的重複事件（flagsChanged 事件常有抖動）
-    let now_ms = {
-        let epoch = ctx.press_instant.lock().unwrap();
-        epoch.elapsed().as_millis() as u64
-    };
-    let last = ctx.last_event_ms.load(Ordering::Relaxed);
-    if now_ms.saturating_sub(last) < DEBOUNCE_MS {
-        return;
+fn device_modifiers(flags: u64) -> Modifiers {
+    Modifiers {
+        shift: (flags & (LSHIFT_FLAG | RSHIFT_FLAG)) != 0,
+        ctrl: (flags & (LCTL_FLAG | RCTL_FLAG)) != 0,
+        alt: (flags & (LALT_FLAG | RALT_FLAG)) != 0,
+        cmd: (flags & (LCMD_FLAG | RCMD_FLAG)) != 0,
     }
-    ctx.last_event_ms.store(now_ms, Ordering::Relaxed);
-
-    let mut state = ctx.state.lock().unwrap();
-
-    match (*state, fn_down) {
-        // IDLE + fn 按下 → PENDING 並啟動 300ms 計時器
-        (FnKeyState::Idle, true) => {
-            *ctx.press_instant.lock().unwrap() = Instant::now();
-            *state = FnKeyState::Pending;
+}
 
-            // 重置取消標記
-            ctx.timer_cancelled.store(false, Ordering::Release);
+fn device_flag(key: ModifierKey) -> u64 {
+    match key {
+        ModifierKey::LeftShift => LSHIFT_FLAG,
+        ModifierKey::RightShift => RSHIFT_FLAG,
+        ModifierKey::LeftCtrl => LCTL_FLAG,
+        ModifierKey::RightCtrl => RCTL_FLAG,
+        ModifierKey::LeftOption => LALT_FLAG,
+        ModifierKey::RightOption => RALT_FLAG,
+        ModifierKey::LeftCmd => LCMD_FLAG,
+        ModifierKey::RightCmd => RCMD_FLAG,
+    }
+}
 
-            let ctx_clone = Arc::clone(ctx);
-            std::thread::spawn(move || {
-                std::thread::sleep(Duration::from_millis(HOLD_THRESHOLD_MS));
+/// macOS virtual keycode（ANSI 配置）
+fn key_code(key: Key) -> i64 {
+    const FUNCTION: [i64; 20] = [
+        0x7A, 0x78, 0x63, 0x76, 0x60, 0x61, 0x62, 0x64, 0x65, 0x6D, // F1–F10
+        0x67, 0x6F, 0x69, 0x6B, 0x71, 0x6A, 0x40, 0x4F, 0x50, 0x5A, // F11–F20
+    ];
+    const LETTERS: [i64; 26] = [
+        0x00, 0x0B, 0x08, 0x02, 0x0E, 0x03, 0x05, 0x04, 0x22, 0x26, 0x28, 0x25, 0x2E, // a–m
+        0x2D, 0x1F, 0x23, 0x0C, 0x0F, 0x01, 0x11, 0x20, 0x09, 0x0D, 0x07, 0x10, 0x06, // n–z
+    ];
+    const DIGITS: [i64; 10] = [0x1D, 0x12, 0x13, 0x14, 0x15, 0x17, 0x16, 0x1A, 0x1C, 0x19]; // 0–9
+    match key {
+        Key::Function(n) => FUNCTION[usize::from(n.clamp(1, 20)) - 1],
+        Key::Char(c @ 'a'..='z') => LETTERS[(c as u8 - b'a') as usize],
+        Key::Char(c @ '0'..='9') => DIGITS[(c as u8 - b'0') as usize],
+        Key::Char(_) => -1,
+        Key::Space => 0x31,
+        Key::Escape => 0x35,
+    }
+}
 
-                // 檢查取消標記：如果已取消，直接返回
-                if ctx_clone.timer_cancelled.load(Ordering::Acquire) {
-                    return;
-                }
+/// keycode 對應的 Key（key_code 的反查）
+fn key_from_code(keycode: i64) -> Option<Key> {
+    (1..=20)
+        .map(Key::Function)
+        .chain(('a'..='z').chain('0'..='9').map(Key::Char))
+        .chain([Key::Space, Key::Escape])
+        .find(|key| key_code(*key) == keycode)
+}
 
-                let mut s = ctx_clone.state.lock().unwrap();
-                if *s == FnKeyState::Pending {
-                    *s = FnKeyState::Holding;
-                    // 通知前端：長按確認，開始錄音
-                    let _ = ctx_clone.app_handle.emit("hotkey-pressed", "fn-hold");
-                }
-            });
+fn run_shortcut(ctx: &FnKeyContext, action: HotkeyAction, shifted: bool) {
+    let _ = match action {
+        HotkeyAction::PasteLast => ctx.app_handle.emit("paste-last-transcript", action.name()),
+        // 加 Shift 時放回原始轉錄
+        HotkeyAction::UndoLast => {
+            ctx.app_handle.emit("undo-last-injection", if shifted { "restore-transcript" } else { "remove" })
         }
-
-        // PENDING + fn 放開（< 300ms）→ 點按模式，通知前端 toggle
-        (FnKeyState::Pending, false) => {
-            // 設置取消標記，防止計時器線程繼續執行
-            ctx.timer_cancelled.store(true, Ordering::Release);
-            *state = FnKeyState::Idle;
-            let _ = ctx.app_handle.emit("hotkey-tap", "fn-tap");
+        // 放棄進行中的錄音或轉錄；觸發鍵可能仍按著或處於 Locked，一併重置
+        HotkeyAction::Cancel => {
+            ctx.machine.lock().unwrap().reset();
+            ctx.app_handle.emit("hotkey-cancelled", "shortcut")
         }
+        // 錄音動作只會綁在觸發鍵手勢上（Bindings::from_specs 檢查）
+        _ => Ok(()),
+    };
+}
+
+/// 把事件交給狀態機；進入 Pending 時啟動長按計時器，到期後 poll 判定長按
+fn dispatch(ctx: &Arc<FnKeyContext>, event: KeyEvent) {
+    let (action, timer) = {
+        let mut machine = ctx.machine.lock().unwrap();
+        let before = machine.deadline();
+        let action = machine.handle(event);
+        let timer = machine.until_deadline().filter(|_| machine.deadline() != before);
+        (action, timer)
+    };
+    if let Some(action) = action {
+        emit(ctx, action);
+    }
 
-        // HOLDING + fn 放開 → 長按模式，判斷錄音時長是否達到最短門檻
-        (FnKeyState::Holding, false) => {
-            let elapsed = ctx.press_instant.lock().unwrap().elapsed();
-            *state = FnKeyState::Idle;
-
-            if elapsed.as_millis() >= MIN_RECORDING_MS as u128 {
-                // 有效錄音，通知前端停止
-                let _ = ctx.app_handle.emit("hotkey-released", "fn-hold");
-            } else {
-                // 錄音太短（通常是 300ms-500ms 之間），丟棄
-                let _ = ctx.app_handle.emit("hotkey-cancelled", "too-short");
+    if let Some(mut wait) = timer {
+        let ctx = Arc::clone(ctx);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(wait);
+            // 過期的計時器（已放開或已取消）poll 不會有動作
+            let (action, remaining) = {
+                let mut machine = ctx.machine.lock().unwrap();
+                (machine.poll(), machine.until_deadline())
+            };
+            if let Some(action) = action {
+                emit(&ctx, action);
             }
-        }
+            // 按壓期間閾值被調長時，等到新的 deadline 再判定
+            match remaining {
+                Some(rest) if !rest.is_zero() => wait = rest,
+                _ => break,
+            }
+        });
+    }
+}
 
-        _ => {}
+/// 狀態機的動作轉成前端事件；payload 為按下時 layer 綁定的動作名稱
+fn emit(ctx: &FnKeyContext, action: Action) {
+    let bound = |modifiers: Modifiers| with_bindings(|bindings| bindings.trigger_action(modifiers));
+    let (event, payload) = match action {
+        Action::HoldStart { modifiers } => ("hotkey-pressed", bound(modifiers)),
+        Action::HoldEnd { modifiers } => ("hotkey-released", bound(modifiers)),
+        Action::Tap { modifiers } => ("hotkey-tap", bound(modifiers)),
+        Action::Lock { modifiers } => ("hotkey-locked", bound(modifiers)),
+        Action::Unlock { modifiers } => ("hotkey-unlocked", bound(modifiers)),
+        Action::Cancel(CancelReason::ComboKey) => {
+            let _ = ctx.app_handle.emit("hotkey-cancelled", "combo-key");
+            return;
+        }
+        Action::Cancel(CancelReason::TooShort) => {
+            let _ = ctx.app_handle.emit("hotkey-cancelled", "too-short");
+            return;
+        }
+    };
+    // 沒有綁定動作的手勢（例如取消綁定 dictate）不通知前端
+    if let Some(action) = payload {
+        let _ = ctx.app_handle.emit(event, action.name());
     }
 }
 
 // MARK: - Public API
 
+/// 更換觸發鍵（設定變更時呼叫，立即生效）
+pub fn set_trigger(trigger: Trigger) {
+    let previous = std::mem::replace(&mut *TRIGGER.lock().unwrap(), trigger);
+    if previous == trigger {
+        return;
+    }
+    // 重置狀態機，避免舊觸發鍵的按壓狀態殘留
+    if let Some(ctx) = GLOBAL_CTX.get() {
+        *ctx.machine.lock().unwrap() = new_machine();
+    }
+    eprintln!("[keyboard] 觸發鍵改為 {}", trigger);
+}
+
+/// 更換長按閾值 / 最短錄音時長 / debounce（設定變更時呼叫，立即生效）
+pub fn set_timing(timing: Timing) {
+    *TIMING.lock().unwrap() = Some(timing);
+    if let Some(ctx) = GLOBAL_CTX.get() {
+        ctx.machine.lock().unwrap().set_timing(timing);
+    }
+}
+
+/// 更換動作綁定（設定變更時呼叫，立即生效）；進行中的按壓沿用原本的 layer
+pub fn set_bindings(bindings: Bindings) {
+    let layers = bindings.layers();
+    *BINDINGS.lock().unwrap() = Some(bindings);
+    if let Some(ctx) = GLOBAL_CTX.get() {
+        ctx.machine.lock().unwrap().set_layers(layers);
+    }
+}
+
 /// 啟動 fn 鍵全域監聽（在獨立執行緒的 CFRunLoop 中運行）
 /// 必須在應用啟動時呼叫，只能呼叫一次
 pub fn start_fn_key_listener(app: AppHandle) {
     std::thread::spawn(move || {
         let ctx = Arc::new(FnKeyContext {
-            state: Mutex::new(FnKeyState::Idle),
-            press_instant: Mutex::new(Instant::now()),
-            last_event_ms: AtomicU64::new(0),
+            machine: Mutex::new(new_machine()),
             app_handle: app,
             tap_ref: Mutex::new(None),
-            timer_cancelled: AtomicBool::new(false),
         });
 
         // 設定全域 context（OnceLock 只設定一次）
         let _ = GLOBAL_CTX.set(Arc::clone(&ctx));
 
-        // 監聽 kCGEventFlagsChanged (type 12) 和 kCGEventKeyDown (type 10)
-        let mask: u64 = (1u64 << K_CG_EVENT_FLAGS_CHANGED) | (1u64 << K_CG_EVENT_KEY_DOWN);
+        // 監聽 kCGEventFlagsChanged (type 12)、kCGEventKeyDown (type 10) 和 kCGEventKeyUp (type 11)
+        let mask: u64 = (1u64 << K_CG_EVENT_FLAGS_CHANGED)
+            | (1u64 << K_CG_EVENT_KEY_DOWN)
+            | (1u64 << K_CG_EVENT_KEY_UP);
 
         let tap = unsafe {
             CGEventTapCreate(
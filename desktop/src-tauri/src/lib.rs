@@ -2,10 +2,26 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 pub mod bridge;
+pub mod cleanup;
 pub mod commands;
+pub mod dictionary;
+pub mod formatting;
+pub mod framing;
+pub mod hotkey;
+#[cfg(target_os = "macos")]
 pub mod keyboard;
+pub mod llm;
 pub mod logger;  // L3: 日誌模組
 pub mod permissions; // v0.3.0 新增
+pub mod polish;
+pub mod profiles;
+pub mod protocol;
+pub mod recorder;
+pub mod replay;
+pub mod snippets;
+pub mod supervisor;
+pub mod translation;
+pub mod transport;
 pub mod tray;
 
 
@@ -29,7 +45,10 @@ pub fn run() {
             commands::get_context,
             commands::start_recording,
             commands::stop_recording,
+            commands::stop_translate_recording,
+            commands::stop_command_recording,
             commands::inject_text,
+            commands::undo_last_injection,
             commands::get_history,
             commands::get_settings,
             commands::set_setting,
@@ -40,12 +59,33 @@ pub fn run() {
             commands::request_microphone,
             commands::get_dictionary,
             commands::set_dictionary,
+            commands::get_snippets,
+            commands::set_snippets,
+            commands::get_profiles,
+            commands::set_profiles,
+            commands::get_hotkey_bindings,
+            commands::set_hotkey_bindings,
+            commands::get_helper_info,
+            commands::cancel_helper_command,
+            commands::restart_helper,
+            commands::start_bridge_recording,
+            commands::stop_bridge_recording,
         ])
         .setup(|app| {
+            // helper 推送的事件（音量、即時轉錄、裝置變更）轉發給前端
+            bridge::set_event_sink(app.handle().clone());
+
+            // 載入需在 Rust 端生效的設定（命令超時等）
+            tauri::async_runtime::spawn(commands::load_runtime_settings());
+
+            // helper 心跳：偵測卡死的進程
+            tauri::async_runtime::spawn(bridge::run_heartbeat());
+
             // 設定系統托盤
             tray::setup_tray(app)?;
 
             // v0.3.0: 啟動 CGEventTap fn 鍵監聽（取代 global-shortcut）
+            #[cfg(target_os = "macos")]
             keyboard::start_fn_key_listener(app.handle().clone());
 
             // 建立浮動錄音條視窗（初始隱藏，錄音時由前端控制顯示/隱藏）
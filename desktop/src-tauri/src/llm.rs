@@ -0,0 +1,260 @@
+// llm.rs — OpenAI 相容 `/v1/chat/completions` 後端（雲端 API 或本機 llama.cpp / Ollama）
+//
+// endpoint、模型與 API key 來自設定；api_base_url 可以是完整的 completions URL，
+// 也可以只是 base（例如 `http://localhost:11434/v1`）。本機伺服器不需要 key 時留空即可。
+// 每次請求都受延遲預算限制，超時視同失敗，由呼叫端退回原文。
+
+use crate::protocol::{AppContext, HelperSettings};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+pub const DEFAULT_ENDPOINT: &str = "https://api.openai.com/v1/chat/completions";
+pub const DEFAULT_MODEL: &str = "gpt-4o-mini";
+/// 潤飾在使用者放開快捷鍵後同步進行，超過這個時間寧可注入未經 LLM 的文字
+pub const DEFAULT_LATENCY_BUDGET: Duration = Duration::from_millis(4000);
+/// 語音指令模式（改寫選取文字）時使用者明確在等結果，可以等久一點
+pub const COMMAND_LATENCY_BUDGET: Duration = Duration::from_millis(10000);
+/// 放進 prompt 的周圍文字上限（字元數）
+const CONTEXT_CHARS: usize = 300;
+/// 回應長度上限的下限與上限（token）；實際值依輸入長度估算，見 max_tokens()
+const MIN_MAX_TOKENS: usize = 512;
+const MAX_MAX_TOKENS: usize = 8192;
+
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    pub endpoint: String,
+    pub model: String,
+    pub api_key: String,
+    pub budget: Duration,
+}
+
+impl LlmConfig {
+    /// 由 helper 設定建立；`api_key` 須另外以 get_api_key 取得（get_settings 的已遮罩）
+    pub fn from_settings(settings: &HelperSettings, api_key: String) -> LlmConfig {
+        let endpoint = match settings.api_base_url.trim() {
+            "" => DEFAULT_ENDPOINT.to_string(),
+            url => url.to_string(),
+        };
+        let model = match settings.polisher_model.trim() {
+            "" => default_model(&endpoint).to_string(),
+            model => model.to_string(),
+        };
+        LlmConfig { endpoint, model, api_key, budget: DEFAULT_LATENCY_BUDGET }
+    }
+
+    /// 實際 POST 的 URL
+    pub fn completions_url(&self) -> String {
+        let url = self.endpoint.trim_end_matches('/');
+        if url.ends_with("/chat/completions") {
+            url.to_string()
+        } else {
+            format!("{}/chat/completions", url)
+        }
+    }
+}
+
+/// 未設定模型時依 endpoint 挑選可用的預設值
+fn default_model(endpoint: &str) -> &'static str {
+    if endpoint.contains("groq.com") {
+        "llama-3.1-8b-instant"
+    } else {
+        DEFAULT_MODEL
+    }
+}
+
+// MARK: - Client
+
+pub struct LlmClient {
+    config: LlmConfig,
+    http: reqwest::Client,
+}
+
+impl LlmClient {
+    pub fn new(config: LlmConfig) -> LlmClient {
+        LlmClient { config, http: reqwest::Client::new() }
+    }
+
+    pub fn config(&self) -> &LlmConfig {
+        &self.config
+    }
+
+    /// 送出 chat completion，回傳第一個 choice 的內容；超過延遲預算回傳錯誤
+    pub async fn complete(&self, messages: Vec<Value>) -> Result<String, String> {
+        let budget = self.config.budget;
+        tokio::time::timeout(budget, self.request(messages))
+            .await
+            .map_err(|_| format!("LLM did not answer within {} ms", budget.as_millis()))?
+    }
+
+    async fn request(&self, messages: Vec<Value>) -> Result<String, String> {
+        let body = json!({
+            "model": self.config.model,
+            "messages": messages,
+            "max_tokens": max_tokens(&messages),
+            "temperature": 0.3,
+        });
+        let mut request = self.http.post(self.config.completions_url()).json(&body);
+        if !self.config.api_key.is_empty() {
+            request = request.bearer_auth(&self.config.api_key);
+        }
+
+        let response = request.send().await.map_err(|e| format!("LLM request failed: {}", e))?;
+        let status = response.status();
+        let text = response.text().await.map_err(|e| format!("Failed to read LLM response: {}", e))?;
+        if !status.is_success() {
+            let snippet: String = text.chars().take(200).collect();
+            return Err(format!("LLM returned {}: {}", status, snippet));
+        }
+
+        let value: Value = serde_json::from_str(&text).map_err(|e| format!("Invalid LLM response: {}", e))?;
+        let choice = &value["choices"][0];
+        // 被長度上限截斷的結果不能當成完整的文字注入
+        if choice["finish_reason"] == "length" {
+            return Err("LLM completion was truncated (finish_reason: length)".into());
+        }
+        let content = choice["message"]["content"].as_str().unwrap_or_default().trim();
+        if content.is_empty() {
+            return Err("LLM returned an empty completion".into());
+        }
+        Ok(content.to_string())
+    }
+}
+
+/// 依輸入長度估算回應上限：每個字元最多約一個 token（中日韓文），翻譯可能變長，取兩倍再加上餘裕
+fn max_tokens(messages: &[Value]) -> usize {
+    let chars: usize = messages.iter().map(|m| m["content"].as_str().unwrap_or_default().chars().count()).sum();
+    (chars * 2 + 256).clamp(MIN_MAX_TOKENS, MAX_MAX_TOKENS)
+}
+
+// MARK: - Prompts
+
+/// 潤飾用的 system + user 訊息；`tone` 由呼叫端決定（PolishContext::tone）
+pub fn polish_messages(transcript: &str, output_language: &str, tone: Option<&str>, ctx: &AppContext) -> Vec<Value> {
+    let mut system = String::from("你是書寫助手，負責潤飾語音轉錄文字：保留原意，修正語法與標點，使其自然流暢。");
+    system.push_str(language_instruction(output_language));
+    if let Some(tone) = tone {
+        system.push_str("\n語氣：");
+        system.push_str(tone);
+    }
+    system.push_str("\n保留原文的換行與條列格式。");
+    system.push_str("\n請只返回潤飾後的文字，不要加任何解釋。");
+
+    let mut user = String::new();
+    let app = [ctx.app_name.as_deref(), ctx.web_domain.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" / ");
+    if !app.is_empty() {
+        user.push_str(&format!("目前 App：{}\n", app));
+    }
+    if let Some(before) = ctx.context_before.as_deref().filter(|s| !s.trim().is_empty()) {
+        user.push_str(&format!("游標前的文字：{}\n", tail_chars(before, CONTEXT_CHARS)));
+    }
+    if let Some(after) = ctx.context_after.as_deref().filter(|s| !s.trim().is_empty()) {
+        user.push_str(&format!("游標後的文字：{}\n", head_chars(after, CONTEXT_CHARS)));
+    }
+    if !user.is_empty() {
+        user.push('\n');
+    }
+    user.push_str("請潤飾：");
+    user.push_str(transcript);
+
+    vec![
+        json!({ "role": "system", "content": system }),
+        json!({ "role": "user", "content": user }),
+    ]
+}
+
+/// 語音指令模式：把口述的指令（"make this more formal"、「改成條列」）套用在選取的文字上
+pub fn command_messages(instruction: &str, selection: &str, output_language: &str, tone: Option<&str>, ctx: &AppContext) -> Vec<Value> {
+    let mut system = String::from("你是文字編輯助手，依照使用者的指示改寫選取的文字（例如改變語氣、翻譯、整理成條列、摘要）。");
+    system.push_str("\n指示沒有指定語言時，");
+    system.push_str(language_instruction(output_language).trim_start());
+    if let Some(tone) = tone {
+        system.push_str("\n語氣：");
+        system.push_str(tone);
+    }
+    system.push_str("\n請只返回改寫後的文字（會直接取代選取範圍），不要加任何解釋或引號。");
+
+    let mut user = String::new();
+    if let Some(app) = ctx.app_name.as_deref() {
+        user.push_str(&format!("目前 App：{}\n", app));
+    }
+    user.push_str(&format!("指示：{}\n\n選取的文字：\n{}", instruction, selection));
+
+    vec![
+        json!({ "role": "system", "content": system }),
+        json!({ "role": "user", "content": user }),
+    ]
+}
+
+/// 翻譯聽寫：把轉錄（已經過規則清理）翻成目標語言，同時潤飾
+pub fn translate_messages(transcript: &str, target_language: &str, tone: Option<&str>, ctx: &AppContext) -> Vec<Value> {
+    let mut system = String::from("你是翻譯助手，負責把語音轉錄翻譯成目標語言：忠實傳達原意，譯文自然通順，符合目標語言的書寫習慣。");
+    system.push_str(&format!("\n目標語言：{}", target_language));
+    if let Some(instruction) = known_language_instruction(target_language) {
+        system.push_str(instruction);
+    }
+    if let Some(tone) = tone {
+        system.push_str("\n語氣：");
+        system.push_str(tone);
+    }
+    system.push_str("\n專有名詞、程式碼、網址保持原樣，並保留原文的換行與條列格式。");
+    system.push_str("\n請只返回譯文，不要加任何解釋。");
+
+    let mut user = String::new();
+    if let Some(app) = ctx.app_name.as_deref() {
+        user.push_str(&format!("目前 App：{}\n\n", app));
+    }
+    user.push_str("請翻譯：");
+    user.push_str(transcript);
+
+    vec![
+        json!({ "role": "system", "content": system }),
+        json!({ "role": "user", "content": user }),
+    ]
+}
+
+/// 輸出語言與地區用語
+pub fn language_instruction(output_language: &str) -> &'static str {
+    known_language_instruction(output_language).unwrap_or("\n請保持原語言。")
+}
+
+fn known_language_instruction(language: &str) -> Option<&'static str> {
+    Some(match language {
+        "zh-TW" => "\n請使用繁體中文（台灣用語），例如：軟體、網路、資料庫。",
+        "zh-HK" => "\n請使用繁體中文（香港用語），例如：軟件、網絡、數據庫。",
+        "zh-CN" => "\n請使用简体中文（中国大陆用语），例如：软件、网络、数据库。",
+        "zh-SG" => "\n請使用简体中文（新加坡用语）。",
+        "en-US" => "\nPlease use American English (e.g., color, organize, center).",
+        "en-GB" => "\nPlease use British English (e.g., colour, organise, centre).",
+        "en-AU" => "\nPlease use Australian English.",
+        "ja-JP" => "\n日本語で出力してください。",
+        "ko-KR" => "\n한국어로 출력해 주세요.",
+        _ => return None,
+    })
+}
+
+/// 依網域推測語氣（與 Swift CloudPolisher 相同）；設定檔可以覆寫（profiles.rs）
+pub fn tone_hint(ctx: &AppContext) -> Option<&'static str> {
+    let domain = ctx.web_domain.as_deref()?;
+    if domain.contains("slack") || domain.contains("discord") {
+        Some("輕鬆友善")
+    } else if domain.contains("gmail") || domain.contains("outlook") {
+        Some("正式專業")
+    } else if domain.contains("notion") || domain.contains("confluence") {
+        Some("清晰結構化")
+    } else {
+        None
+    }
+}
+
+fn head_chars(text: &str, n: usize) -> String {
+    text.chars().take(n).collect()
+}
+
+fn tail_chars(text: &str, n: usize) -> String {
+    let count = text.chars().count();
+    text.chars().skip(count.saturating_sub(n)).collect()
+}
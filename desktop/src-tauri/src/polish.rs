@@ -0,0 +1,388 @@
+// polish.rs — 轉錄文字潤飾 pipeline（stop_recording 在存入歷史與注入前套用）
+//
+// polisher_mode 決定 pipeline 由哪些階段組成：
+//   none   只轉錄，原文不做任何改動
+//   local  規則式清理 → 口述格式指令 → 標點 / 大小寫修復 → 詞典修正（完全離線）
+//   cloud  local 的所有階段，之後再交給 LLM 改寫（llm.rs）
+// local / cloud 最後展開語音片段（snippets.rs），片段內容因此不會被改寫。
+// 翻譯聽寫（translation.rs）在片段之前加上 LLM 翻譯，並取代 cloud 的 LLM 改寫（none 模式也會翻譯）。
+//
+// 每個階段實作 Polisher，可單獨測試。階段失敗不會讓錄音失敗：
+// 記錄警告後沿用上一階段的文字繼續（LLM 超時即退回規則處理後的轉錄）。
+
+use crate::cleanup;
+use crate::dictionary::{self, DictionaryEntry, ReplacementLog};
+use crate::formatting::{self, FormattingSettings};
+use crate::llm::{self, LlmClient, LlmConfig};
+use crate::log_warn;
+use crate::protocol::AppContext;
+use crate::snippets::{self, Placeholders, Snippet};
+use std::future::Future;
+use std::pin::Pin;
+
+pub type PolishFuture<'a> = Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>>;
+
+/// 潤飾時可用的上下文
+#[derive(Debug, Clone, Default)]
+pub struct PolishContext {
+    /// 焦點 App 的上下文（語氣、周圍文字）
+    pub app: AppContext,
+    /// ASR 偵測到的語言（例如 `zh`、`en`）
+    pub language: Option<String>,
+    /// 設定中的輸出語言（例如 `zh-TW`）
+    pub output_language: String,
+    /// 個人詞典
+    pub dictionary: Vec<DictionaryEntry>,
+    /// 詞典階段做了哪些替換（回傳給前端）
+    pub replacements: ReplacementLog,
+    /// 設定檔指定的 LLM 語氣；None 時依網域推測
+    pub tone: Option<String>,
+    /// 口述格式指令設定
+    pub formatting: FormattingSettings,
+    /// 語音片段與佔位符的值
+    pub snippets: Vec<Snippet>,
+    pub placeholders: Placeholders,
+}
+
+impl PolishContext {
+    /// LLM 語氣：設定檔指定的優先，否則依網域推測
+    pub fn tone(&self) -> Option<&str> {
+        self.tone.as_deref().or_else(|| llm::tone_hint(&self.app))
+    }
+}
+
+/// pipeline 的一個階段
+pub trait Polisher: Send + Sync {
+    /// 日誌用名稱
+    fn name(&self) -> &'static str;
+
+    fn polish<'a>(&'a self, text: &'a str, ctx: &'a PolishContext) -> PolishFuture<'a>;
+}
+
+// MARK: - Pipeline
+
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<Box<dyn Polisher>>,
+}
+
+impl Pipeline {
+    /// 依 polisher_mode 建立 pipeline；未知模式視同 none（不潤飾）。
+    /// cloud 模式需要 `llm`，缺少時只套用本地階段。
+    pub fn for_mode(mode: &str, llm: Option<LlmConfig>) -> Pipeline {
+        let local = || {
+            Pipeline::default()
+                .with_stage(Cleanup)
+                .with_stage(SpokenCommands)
+                .with_stage(Punctuation)
+                .with_stage(Dictionary)
+        };
+        let pipeline = match mode {
+            "none" | "" => return Pipeline::default(),
+            "local" => local(),
+            "cloud" => match llm {
+                Some(config) => local().with_stage(LlmRewrite::new(config)),
+                None => {
+                    log_warn!("[polish] Cloud polisher is not configured, using local stages only");
+                    local()
+                }
+            },
+            other => {
+                log_warn!("[polish] Unknown polisher mode {:?}, skipping polishing", other);
+                return Pipeline::default();
+            }
+        };
+        pipeline.with_stage(Snippets)
+    }
+
+    pub fn with_stage(mut self, stage: impl Polisher + 'static) -> Pipeline {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// 改為翻譯聽寫：翻譯放在片段展開之前，並取代 LLM 改寫（翻譯時已一併潤飾）
+    pub fn with_translation(mut self, config: LlmConfig, target_language: &str) -> Pipeline {
+        self.stages.retain(|s| s.name() != "llm_rewrite");
+        let position = self.stages.iter().position(|s| s.name() == "snippets").unwrap_or(self.stages.len());
+        self.stages.insert(position, Box::new(LlmTranslate::new(config, target_language)));
+        self
+    }
+
+    pub fn has_stage(&self, name: &str) -> bool {
+        self.stages.iter().any(|s| s.name() == name)
+    }
+
+    pub fn stage_names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    /// 依序套用所有階段；失敗的階段會被略過
+    pub async fn run(&self, text: &str, ctx: &PolishContext) -> String {
+        self.run_capturing(text, ctx, "").await.0
+    }
+
+    /// 與 run 相同，另外回傳 `stage` 成功時的輸入（例如翻譯前的原文）
+    pub async fn run_capturing(&self, text: &str, ctx: &PolishContext, stage: &str) -> (String, Option<String>) {
+        let mut text = text.to_string();
+        let mut captured = None;
+        for s in &self.stages {
+            match s.polish(&text, ctx).await {
+                Ok(polished) => {
+                    if s.name() == stage {
+                        captured = Some(std::mem::replace(&mut text, polished));
+                    } else {
+                        text = polished;
+                    }
+                }
+                Err(e) => log_warn!("[polish] Stage {} failed, keeping previous text: {}", s.name(), e),
+            }
+        }
+        (text, captured)
+    }
+}
+
+// MARK: - Cleanup
+
+/// 規則式清理：填充詞、口吃、自我更正、中英文空白與全形標點（cleanup.rs）
+pub struct Cleanup;
+
+impl Polisher for Cleanup {
+    fn name(&self) -> &'static str {
+        "cleanup"
+    }
+
+    fn polish<'a>(&'a self, text: &'a str, ctx: &'a PolishContext) -> PolishFuture<'a> {
+        Box::pin(async move { Ok(cleanup::clean(text, ctx.language.as_deref())) })
+    }
+}
+
+// MARK: - Spoken Commands
+
+/// 口述格式指令："new line"、「換行」、「句號」（formatting.rs）；依 bundle_id 停用
+pub struct SpokenCommands;
+
+impl Polisher for SpokenCommands {
+    fn name(&self) -> &'static str {
+        "spoken_commands"
+    }
+
+    fn polish<'a>(&'a self, text: &'a str, ctx: &'a PolishContext) -> PolishFuture<'a> {
+        Box::pin(async move { Ok(formatting::apply(text, &ctx.formatting, ctx.app.bundle_id.as_deref())) })
+    }
+}
+
+// MARK: - Punctuation
+
+const SENTENCE_END: &[char] = &['.', '!', '?', '。', '！', '？', '…'];
+const CLOSING: &[char] = &['"', '\'', '」', '』', ')', '）'];
+/// 句首的引號、括號與條列符號之後仍要大寫
+const OPENING: &[char] = &['"', '「', '『', '(', '（', '-'];
+
+/// 修復空白、句首（與每行開頭）大寫、單獨的 "i"，並補上句尾標點
+pub struct Punctuation;
+
+impl Polisher for Punctuation {
+    fn name(&self) -> &'static str {
+        "punctuation"
+    }
+
+    fn polish<'a>(&'a self, text: &'a str, _ctx: &'a PolishContext) -> PolishFuture<'a> {
+        Box::pin(async move { Ok(repair_punctuation(text)) })
+    }
+}
+
+fn repair_punctuation(text: &str) -> String {
+    // 口述的換行（formatting.rs）逐行處理；句尾標點只補在最後一行，條列項目不補
+    let lines: Vec<String> = text.split('\n').map(repair_line).collect();
+    let mut result = lines.join("\n");
+    let last_line = lines.last().map(String::as_str).unwrap_or_default();
+    if last_line.is_empty() || last_line.starts_with("- ") {
+        return result;
+    }
+
+    // 句尾標點：英文放在右引號內，中文放在「」外
+    let core = result.trim_end_matches(CLOSING);
+    if let Some(last) = core.chars().last() {
+        if !SENTENCE_END.contains(&last) {
+            if is_cjk(last) {
+                result.push('。');
+            } else {
+                result.insert(core.len(), '.');
+            }
+        }
+    }
+    result
+}
+
+/// 單行的空白、單獨的 "i" 與句首大寫
+fn repair_line(text: &str) -> String {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| match w {
+            "i" => "I".to_string(),
+            w if w.starts_with("i'") => format!("I{}", &w[1..]),
+            w => w.to_string(),
+        })
+        .collect();
+
+    // 標點前不留空白
+    let mut out = String::with_capacity(text.len() + 1);
+    for word in &words {
+        let attaches = word.starts_with([',', '.', '!', '?', ';', ':', '，', '。', '！', '？', '、']);
+        if !out.is_empty() && !attaches {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+
+    // 句首大寫
+    let mut capitalize = true;
+    let mut result = String::with_capacity(out.len() + 1);
+    for c in out.chars() {
+        if capitalize && c.is_alphabetic() {
+            result.extend(c.to_uppercase());
+            capitalize = false;
+        } else {
+            if SENTENCE_END.contains(&c) {
+                capitalize = true;
+            } else if !c.is_whitespace() && !CLOSING.contains(&c) && !OPENING.contains(&c) {
+                capitalize = false;
+            }
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// 中日韓文字（標點與空白規則不同於拉丁文字）
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // 平假名、片假名
+        | 0x3400..=0x4DBF   // CJK 擴充 A
+        | 0x4E00..=0x9FFF   // CJK 統一漢字
+        | 0xAC00..=0xD7AF   // 諺文
+        | 0xF900..=0xFAFF)  // CJK 相容漢字
+}
+
+// MARK: - Dictionary
+
+/// 以個人詞典修正誤辨識的詞（dictionary.rs），替換紀錄寫入 `ctx.replacements`
+pub struct Dictionary;
+
+impl Polisher for Dictionary {
+    fn name(&self) -> &'static str {
+        "dictionary"
+    }
+
+    fn polish<'a>(&'a self, text: &'a str, ctx: &'a PolishContext) -> PolishFuture<'a> {
+        Box::pin(async move {
+            let correction = dictionary::correct(text, &ctx.dictionary);
+            ctx.replacements.extend(correction.replacements);
+            Ok(correction.text)
+        })
+    }
+}
+
+// MARK: - Snippets
+
+/// 把觸發詞換成儲存的片段（snippets.rs）
+pub struct Snippets;
+
+impl Polisher for Snippets {
+    fn name(&self) -> &'static str {
+        "snippets"
+    }
+
+    fn polish<'a>(&'a self, text: &'a str, ctx: &'a PolishContext) -> PolishFuture<'a> {
+        Box::pin(async move { Ok(snippets::expand(text, &ctx.snippets, &ctx.placeholders)) })
+    }
+}
+
+// MARK: - LLM Rewrite
+
+/// 以 OpenAI 相容的 chat completions 改寫（語氣、語言依上下文調整）
+pub struct LlmRewrite {
+    client: LlmClient,
+}
+
+impl LlmRewrite {
+    pub fn new(config: LlmConfig) -> LlmRewrite {
+        LlmRewrite { client: LlmClient::new(config) }
+    }
+}
+
+impl Polisher for LlmRewrite {
+    fn name(&self) -> &'static str {
+        "llm_rewrite"
+    }
+
+    fn polish<'a>(&'a self, text: &'a str, ctx: &'a PolishContext) -> PolishFuture<'a> {
+        Box::pin(async move {
+            if text.trim().is_empty() {
+                return Ok(text.to_string());
+            }
+            let messages = llm::polish_messages(text, &ctx.output_language, ctx.tone(), &ctx.app);
+            self.client.complete(messages).await
+        })
+    }
+}
+
+// MARK: - LLM Translate
+
+/// 翻譯聽寫：以 LLM 把轉錄翻成依 App 決定的目標語言（translation.rs）
+pub struct LlmTranslate {
+    client: LlmClient,
+    target_language: String,
+}
+
+impl LlmTranslate {
+    pub fn new(config: LlmConfig, target_language: impl Into<String>) -> LlmTranslate {
+        LlmTranslate { client: LlmClient::new(config), target_language: target_language.into() }
+    }
+}
+
+impl Polisher for LlmTranslate {
+    fn name(&self) -> &'static str {
+        "llm_translate"
+    }
+
+    fn polish<'a>(&'a self, text: &'a str, ctx: &'a PolishContext) -> PolishFuture<'a> {
+        Box::pin(async move {
+            if text.trim().is_empty() {
+                return Ok(text.to_string());
+            }
+            let messages = llm::translate_messages(text, &self.target_language, ctx.tone(), &ctx.app);
+            self.client.complete(messages).await
+        })
+    }
+}
+
+// MARK: - LLM Command
+
+/// 語音指令模式：輸入是選取的文字，口述的指令決定怎麼改寫（stop_command_recording）
+pub struct LlmCommand {
+    client: LlmClient,
+    instruction: String,
+}
+
+impl LlmCommand {
+    pub fn new(config: LlmConfig, instruction: impl Into<String>) -> LlmCommand {
+        LlmCommand { client: LlmClient::new(config), instruction: instruction.into() }
+    }
+}
+
+impl Polisher for LlmCommand {
+    fn name(&self) -> &'static str {
+        "llm_command"
+    }
+
+    fn polish<'a>(&'a self, text: &'a str, ctx: &'a PolishContext) -> PolishFuture<'a> {
+        Box::pin(async move {
+            if self.instruction.trim().is_empty() {
+                return Err("No instruction was recognized".into());
+            }
+            let messages = llm::command_messages(&self.instruction, text, &ctx.output_language, ctx.tone(), &ctx.app);
+            self.client.complete(messages).await
+        })
+    }
+}
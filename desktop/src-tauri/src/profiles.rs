@@ -0,0 +1,168 @@
+// profiles.rs — 依 App / 網域覆寫設定的設定檔
+//
+// 設定存在 `profiles`（JSON 陣列），stop_recording 時由上而下取第一個符合焦點 App 的設定檔：
+//   [{"name": "Slack", "match": {"field": "bundle_id", "pattern": "com.tinyspeck.*"}, "tone": "輕鬆友善"},
+//    {"name": "Jira", "match": {"field": "web_domain", "pattern": "\\.atlassian\\.net$", "kind": "regex"},
+//     "polisher_mode": "cloud", "output_language": "en-US", "formatting_commands": false}]
+// glob 支援 `*` 與 `?`，比對整個值；regex 只要有部分符合即可（需要時自行加 `^`、`$`）。
+// 兩者都不分大小寫。沒有填的欄位沿用全域設定。
+
+use crate::formatting::FormattingSettings;
+use crate::log_warn;
+use crate::protocol::{AppContext, HelperSettings, InjectionMethod};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+/// 設定檔比對的 AppContext 欄位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchField {
+    #[default]
+    BundleId,
+    AppName,
+    WebDomain,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternKind {
+    #[default]
+    Glob,
+    Regex,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileMatch {
+    pub field: MatchField,
+    pub pattern: String,
+    #[serde(default)]
+    pub kind: PatternKind,
+}
+
+impl ProfileMatch {
+    fn regex(&self) -> Result<Regex, String> {
+        let source = match self.kind {
+            PatternKind::Glob => glob_to_regex(&self.pattern),
+            PatternKind::Regex => self.pattern.clone(),
+        };
+        RegexBuilder::new(&source)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| format!("Invalid pattern {:?}: {}", self.pattern, e))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    #[serde(rename = "match")]
+    pub rule: ProfileMatch,
+    /// "none" | "local" | "cloud"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub polisher_mode: Option<String>,
+    /// 取代依網域推測的 LLM 語氣（例如「正式專業」）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_language: Option<String>,
+    /// 口述格式指令的開關
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatting_commands: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub injection: Option<InjectionMethod>,
+}
+
+impl Profile {
+    pub fn matches(&self, ctx: &AppContext) -> bool {
+        let value = match self.rule.field {
+            MatchField::BundleId => ctx.bundle_id.as_deref(),
+            MatchField::AppName => ctx.app_name.as_deref(),
+            MatchField::WebDomain => ctx.web_domain.as_deref(),
+        };
+        let Some(value) = value.filter(|v| !v.is_empty()) else {
+            return false;
+        };
+        match self.rule.regex() {
+            Ok(regex) => regex.is_match(value),
+            Err(e) => {
+                log_warn!("[profiles] Skipping profile {:?}: {}", self.name, e);
+                false
+            }
+        }
+    }
+
+    /// 把設定檔覆寫的潤飾模式與輸出語言寫進設定
+    pub fn apply(&self, settings: &mut HelperSettings) {
+        if let Some(mode) = &self.polisher_mode {
+            settings.polisher_mode = mode.clone();
+        }
+        if let Some(language) = &self.output_language {
+            settings.output_language = language.clone();
+        }
+    }
+
+    /// 覆寫口述格式指令的開關；明確開啟時不再受停用 App 清單限制
+    pub fn apply_formatting(&self, formatting: &mut FormattingSettings) {
+        if let Some(enabled) = self.formatting_commands {
+            formatting.enabled = enabled;
+            if enabled {
+                formatting.disabled_apps.clear();
+            }
+        }
+    }
+
+    /// 設定檔指定的語氣（空字串視為未指定）
+    pub fn tone(&self) -> Option<String> {
+        self.tone.as_deref().map(str::trim).filter(|t| !t.is_empty()).map(String::from)
+    }
+}
+
+/// 解析設定值；空字串或格式錯誤時視為沒有設定檔
+pub fn parse(raw: &str) -> Vec<Profile> {
+    parse_strict(raw).unwrap_or_else(|e| {
+        log_warn!("[profiles] {}, ignoring", e);
+        Vec::new()
+    })
+}
+
+/// 解析設定值；格式錯誤時回傳錯誤（set_setting 儲存前使用）
+pub fn parse_strict(raw: &str) -> Result<Vec<Profile>, String> {
+    if raw.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(raw).map_err(|e| format!("Invalid profiles setting: {}", e))
+}
+
+/// 第一個符合目前 App 的設定檔
+pub fn resolve<'a>(profiles: &'a [Profile], ctx: &AppContext) -> Option<&'a Profile> {
+    profiles.iter().find(|p| p.matches(ctx))
+}
+
+/// 儲存前檢查：pattern 不可為空且必須能編譯，潤飾模式必須是已知值
+pub fn validate(profiles: &[Profile]) -> Result<(), String> {
+    for profile in profiles {
+        if profile.rule.pattern.trim().is_empty() {
+            return Err(format!("Profile {:?} has an empty pattern", profile.name));
+        }
+        profile.rule.regex()?;
+        if let Some(mode) = profile.polisher_mode.as_deref().filter(|m| !["none", "local", "cloud"].contains(m)) {
+            return Err(format!("Profile {:?} has an unknown polisher mode {:?}", profile.name, mode));
+        }
+    }
+    Ok(())
+}
+
+/// `*` → 任意字元、`?` → 單一字元，其他字元照字面比對；比對整個值
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => out.push_str(".*"),
+            '?' => out.push('.'),
+            c => out.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    out.push('$');
+    out
+}
@@ -0,0 +1,355 @@
+// protocol.rs — Rust ↔ EchoTypeHelper JSON-line 協定的型別定義
+//
+// 線上格式（一行一個 JSON）：
+//   → {"id": 7, "cmd": "inject_text", "args": {"text": "你好"}}
+//   ← {"id": 7, "ok": true, "result": true}
+//
+// 每個命令是一個請求 struct（欄位即 `args`），透過 HelperCommand 綁定回應型別；
+// HelperRequest enum 列出所有命令，是協定的唯一來源。新增命令時：
+//   1. 定義請求 struct（與回應型別）
+//   2. 在 helper_commands! 中加一行
+//   3. 在 Swift helper 的 dispatch() 與 supportedCommands 中實作
+
+use crate::dictionary::DictionaryEntry;
+use crate::snippets::Snippet;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 一個 helper 命令：請求本身可轉成 HelperRequest，並宣告回應的型別
+pub trait HelperCommand: Into<HelperRequest> {
+    type Response: DeserializeOwned;
+}
+
+macro_rules! helper_commands {
+    ($($variant:ident => $response:ty),* $(,)?) => {
+        /// 所有 helper 命令；serde 以 `cmd` 為 tag、`args` 為內容序列化
+        #[derive(Debug, Clone, Serialize)]
+        #[serde(tag = "cmd", content = "args", rename_all = "snake_case")]
+        pub enum HelperRequest {
+            $($variant($variant),)*
+        }
+
+        $(
+            impl From<$variant> for HelperRequest {
+                fn from(req: $variant) -> Self {
+                    HelperRequest::$variant(req)
+                }
+            }
+
+            impl HelperCommand for $variant {
+                type Response = $response;
+            }
+        )*
+    };
+}
+
+helper_commands! {
+    Hello => HelperInfo,
+    Cancel => bool,
+    Ping => bool,
+    GetContext => AppContext,
+    StartRecording => String,
+    StopRecording => AsrResult,
+    InjectText => bool,
+    DeleteBackward => bool,
+    GetMicrophones => Vec<AudioDevice>,
+    GetSettings => HelperSettings,
+    SetSetting => bool,
+    GetApiKey => String,
+    GetDictionary => Vec<DictionaryEntry>,
+    SetDictionary => bool,
+    GetSnippets => Vec<Snippet>,
+    SetSnippets => bool,
+    GetPlaceholderValues => PlaceholderValues,
+    GetHistory => Vec<HistoryEntry>,
+    SaveHistory => String,
+    PruneHistory => bool,
+    CheckModelStatus => ModelStatus,
+    DownloadModel => Value,
+    CheckMicrophonePermission => String,
+    RequestMicrophone => String,
+    CheckAccessibility => bool,
+    StreamAudio => bool,
+}
+
+impl HelperRequest {
+    /// 拆成 (`cmd`, `args`)，供 bridge 組成帶 id 的請求行
+    pub fn into_parts(self) -> Result<(String, Value), String> {
+        let mut value = serde_json::to_value(&self).map_err(|e| e.to_string())?;
+        let cmd = value["cmd"]
+            .as_str()
+            .ok_or("HelperRequest serialized without cmd")?
+            .to_string();
+        let args = value
+            .get_mut("args")
+            .map(Value::take)
+            .unwrap_or_else(|| Value::Object(Default::default()));
+        Ok((cmd, args))
+    }
+}
+
+// MARK: - Requests
+
+/// 握手：回報 app 使用的協定版本，並依偏好順序列出 app 支援的 framing
+#[derive(Debug, Clone, Serialize)]
+pub struct Hello {
+    pub protocol_version: u32,
+    pub framings: Vec<Framing>,
+}
+
+/// 請 helper 放棄進行中的請求（`request_id` 為該請求的 id）；回傳是否找到該請求
+#[derive(Debug, Clone, Serialize)]
+pub struct Cancel {
+    pub request_id: u64,
+}
+
+/// 心跳：helper 只需立即回應，用來偵測卡死的進程
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Ping {}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetContext {}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StartRecording {}
+
+/// 停止錄音並進行 ASR（耗時取決於錄音長度）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StopRecording {}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InjectText {
+    pub text: String,
+    pub method: InjectionMethod,
+}
+
+/// 在焦點欄位按 Backspace 刪除游標前的 `text`（helper 以字元叢集計算次數）
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteBackward {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetMicrophones {}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetSettings {}
+
+/// `key` 為 Swift SettingsKey 的 rawValue（camelCase，例如 `polisherMode`）
+#[derive(Debug, Clone, Serialize)]
+pub struct SetSetting {
+    pub key: String,
+    pub value: String,
+}
+
+/// 取得未遮罩的 API key（只供 Rust 端呼叫 LLM 使用，不可傳給前端）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetApiKey {}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetDictionary {}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetDictionary {
+    pub entries: Vec<DictionaryEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetSnippets {}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetSnippets {
+    pub snippets: Vec<Snippet>,
+}
+
+/// 片段佔位符的值；`clipboard` 為 false 時不讀取剪貼簿
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetPlaceholderValues {
+    pub clipboard: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GetHistory {
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SaveHistory {
+    pub transcript: String,
+    pub polished_text: Option<String>,
+    /// 翻譯聽寫的譯文（polished_text 為潤飾後的原文）
+    pub translated_text: Option<String>,
+    pub target_language: Option<String>,
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+    pub web_url: Option<String>,
+    pub web_domain: Option<String>,
+    pub web_title: Option<String>,
+    pub asr_engine: String,
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneHistory {
+    pub days: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CheckModelStatus {}
+
+/// `model_type`: "whisper" | "qwen3"
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadModel {
+    pub model_type: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CheckMicrophonePermission {}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RequestMicrophone {}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CheckAccessibility {}
+
+/// 開關錄音中的 `audio_chunk` 二進位事件（16kHz mono f32le PCM；需 length-prefixed framing）
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamAudio {
+    pub enabled: bool,
+}
+
+// MARK: - Responses
+
+/// 連線的訊息格式（見 framing.rs）；握手前一律為 JSON-line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Framing {
+    #[default]
+    JsonLines,
+    LengthPrefixed,
+}
+
+/// 文字注入方式；auto = 先試 AX API，失敗改用剪貼簿貼上
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectionMethod {
+    #[default]
+    Auto,
+    /// 只用 AX API（不動剪貼簿）
+    Accessibility,
+    /// 一律用剪貼簿 + Cmd+V（AX 插入不可靠的 Electron App）
+    Paste,
+}
+
+/// `hello` 握手回傳的 helper 資訊
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HelperInfo {
+    pub protocol_version: u32,
+    pub helper_version: String,
+    pub commands: Vec<String>,
+    /// helper 選定的 framing，hello 回應之後雙方改用此格式（舊 helper 不回報 = JSON-line）
+    #[serde(default)]
+    pub framing: Framing,
+}
+
+impl HelperInfo {
+    pub fn supports(&self, cmd: &str) -> bool {
+        self.commands.iter().any(|c| c == cmd)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppContext {
+    pub app_name: Option<String>,
+    pub bundle_id: Option<String>,
+    pub window_title: Option<String>,
+    pub web_url: Option<String>,
+    pub web_domain: Option<String>,
+    pub web_title: Option<String>,
+    pub context_before: Option<String>,
+    pub context_after: Option<String>,
+    pub selected_text: Option<String>,
+}
+
+/// `get_placeholder_values` 的結果（日期依 helper 的本機時區）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlaceholderValues {
+    pub date: String,
+    pub clipboard: Option<String>,
+}
+
+/// `stop_recording` 的 ASR 結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsrResult {
+    pub transcript: String,
+    pub language: Option<String>,
+    pub duration: f64,
+    pub asr_engine: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AudioDevice {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
+/// `get_settings` 的回應（鍵為 snake_case，API key 已遮罩）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HelperSettings {
+    pub asr_engine: String,
+    pub polisher_mode: String,
+    /// LLM 潤飾使用的模型（空字串 = 依 endpoint 的預設值）
+    pub polisher_model: String,
+    pub input_language: String,
+    pub output_language: String,
+    pub selected_mic_id: String,
+    pub api_key: String,
+    pub api_base_url: String,
+    pub history_retention_days: String,
+    pub hotkey: String,
+    /// JSON 物件字串，動作 → 觸發鍵手勢或快捷鍵（見 hotkey.rs 的 Bindings）
+    pub hotkey_bindings: String,
+    pub launch_at_login: String,
+    /// JSON 物件字串，cmd → 超時秒數，例如 `{"stop_recording": 300}`
+    pub helper_timeouts: String,
+    /// JSON 物件字串，口述格式指令設定（見 formatting.rs）
+    pub formatting_commands: String,
+    /// JSON 物件字串，翻譯聽寫設定（見 translation.rs）
+    pub translation: String,
+    /// JSON 陣列字串，依 App / 網域覆寫設定的設定檔（見 profiles.rs）
+    pub profiles: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub transcript: String,
+    pub polished_text: Option<String>,
+    pub translated_text: Option<String>,
+    pub target_language: Option<String>,
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+    pub web_url: Option<String>,
+    pub web_domain: Option<String>,
+    pub web_title: Option<String>,
+    pub asr_engine: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub name: String,
+    pub path: String,
+    pub downloaded: bool,
+    pub size: Option<Value>,
+    pub source: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelStatus {
+    pub whisper: ModelInfo,
+    pub qwen3: ModelInfo,
+}
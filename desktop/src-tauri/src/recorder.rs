@@ -0,0 +1,201 @@
+// recorder.rs — bridge 流量錄製（bug report 用，預設關閉）
+//
+// 開啟後 bridge 送出與收到的每一則訊息都附上時間戳寫入 JSONL session 檔：
+//   {"echotype_session": 1, "started_at_ms": 1760000000000, "redact_text": true}
+//   {"t_ms": 0,   "dir": "send", "message": {"id": 1, "cmd": "hello", "args": {...}}}
+//   {"t_ms": 3,   "dir": "recv", "message": {"id": 1, "ok": true, "result": {...}}}
+//   {"t_ms": 812, "dir": "recv", "message": {"event": "audio_chunk", ...}, "payload_len": 2048}
+// 二進位 payload 只記錄長度。session 檔可由 replay.rs 餵回 call_helper 重現問題。
+//
+// 遮蔽：API key（含 get_api_key 的回應）一律遮蔽；`redact_text` 時另外遮蔽轉錄文字、視窗標題、網址、詞典、片段、剪貼簿等
+// 使用者內容，只保留長度（`"[redacted: 12 chars]"`），回應的結構與時序不變。
+
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+pub const SESSION_VERSION: u32 = 1;
+
+/// 含使用者內容的欄位（`redact_text` 時遮蔽）
+const TEXT_KEYS: &[&str] = &[
+    "transcript", "polished_text", "text", "words",
+    "context_before", "context_after", "selected_text",
+    "window_title", "web_url", "web_title",
+    "trigger", "clipboard", "term", "sounds_like",
+];
+
+/// 回應本身就是機密的命令（整個 result 遮蔽）
+const SECRET_COMMANDS: &[&str] = &["get_api_key"];
+
+/// 回應陣列的元素可能是純文字的命令（`redact_text` 時遮蔽；舊版詞典是字串陣列）
+const TEXT_COMMANDS: &[&str] = &["get_dictionary"];
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecordOptions {
+    /// 遮蔽轉錄文字等使用者內容
+    pub redact_text: bool,
+}
+
+/// 訊息方向（相對於 app）
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    Send,
+    Recv,
+}
+
+impl Direction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Direction::Send => "send",
+            Direction::Recv => "recv",
+        }
+    }
+}
+
+struct Recorder {
+    path: PathBuf,
+    file: std::fs::File,
+    started: Instant,
+    options: RecordOptions,
+    /// 尚未收到回應的機密命令 id
+    secret_ids: HashSet<u64>,
+    /// 尚未收到回應的 TEXT_COMMANDS id
+    text_ids: HashSet<u64>,
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// 開始錄製到 `path`（覆寫既有檔案）；已在錄製時先結束上一個 session
+pub fn start(path: &Path, options: RecordOptions) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let mut file = std::fs::File::create(path)
+        .map_err(|e| format!("Failed to create session file {}: {}", path.display(), e))?;
+
+    let started_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let header = json!({
+        "echotype_session": SESSION_VERSION,
+        "started_at_ms": started_at_ms,
+        "redact_text": options.redact_text,
+    });
+    writeln!(file, "{}", header).map_err(|e| e.to_string())?;
+
+    *RECORDER.lock().unwrap() = Some(Recorder {
+        path: path.to_path_buf(),
+        file,
+        started: Instant::now(),
+        options,
+        secret_ids: HashSet::new(),
+        text_ids: HashSet::new(),
+    });
+    ACTIVE.store(true, Ordering::Release);
+    eprintln!("[recorder] Recording bridge session to {}", path.display());
+    Ok(())
+}
+
+/// 結束錄製，回傳 session 檔路徑（未在錄製時回傳 None）
+pub fn stop() -> Option<PathBuf> {
+    ACTIVE.store(false, Ordering::Release);
+    let recorder = RECORDER.lock().unwrap().take()?;
+    eprintln!("[recorder] Session saved to {}", recorder.path.display());
+    Some(recorder.path)
+}
+
+/// 是否正在錄製（bridge 據此決定是否需要額外解析訊息）
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Acquire)
+}
+
+/// 記錄一則訊息；`message` 為原始 JSON（無法解析時為字串）
+pub(crate) fn record(direction: Direction, message: &Value, payload_len: usize) {
+    let mut guard = RECORDER.lock().unwrap();
+    let Some(recorder) = guard.as_mut() else {
+        return;
+    };
+
+    let mut message = message.clone();
+    if let Some(id) = message["id"].as_u64() {
+        let cmd = message["cmd"].as_str().unwrap_or_default();
+        match direction {
+            Direction::Send if SECRET_COMMANDS.contains(&cmd) => {
+                recorder.secret_ids.insert(id);
+            }
+            Direction::Send if recorder.options.redact_text && TEXT_COMMANDS.contains(&cmd) => {
+                recorder.text_ids.insert(id);
+            }
+            Direction::Recv if recorder.secret_ids.remove(&id) && message.get("result").is_some() => {
+                message["result"] = json!("[redacted]");
+            }
+            Direction::Recv if recorder.text_ids.remove(&id) => {
+                if let Some(result) = message.get_mut("result") {
+                    redact_text(result);
+                }
+            }
+            _ => {}
+        }
+    }
+    match &message {
+        // 無法解析的原始行：內容不明，視同使用者文字
+        Value::String(raw) if recorder.options.redact_text => message = redacted(raw),
+        _ => redact(&mut message, recorder.options),
+    }
+    let mut entry = json!({
+        "t_ms": recorder.started.elapsed().as_millis() as u64,
+        "dir": direction.as_str(),
+        "message": message,
+    });
+    if payload_len > 0 {
+        entry["payload_len"] = json!(payload_len);
+    }
+    if let Err(e) = writeln!(recorder.file, "{}", entry) {
+        eprintln!("[recorder] Failed to write {}: {}", recorder.path.display(), e);
+    }
+}
+
+// MARK: - Redaction
+
+fn redacted(text: &str) -> Value {
+    json!(format!("[redacted: {} chars]", text.chars().count()))
+}
+
+/// 就地遮蔽訊息中的敏感內容
+fn redact(value: &mut Value, options: RecordOptions) {
+    match value {
+        Value::Object(map) => {
+            // set_setting 的 apiKey 值
+            if map.get("key").and_then(Value::as_str) == Some("apiKey") {
+                if let Some(v) = map.get_mut("value") {
+                    *v = json!("[redacted]");
+                }
+            }
+            for (key, v) in map.iter_mut() {
+                if key == "api_key" {
+                    *v = json!("[redacted]");
+                } else if options.redact_text && TEXT_KEYS.contains(&key.as_str()) {
+                    redact_text(v);
+                } else {
+                    redact(v, options);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| redact(v, options)),
+        _ => {}
+    }
+}
+
+fn redact_text(value: &mut Value) {
+    match value {
+        Value::String(text) => *value = redacted(text),
+        Value::Array(items) => items.iter_mut().for_each(redact_text),
+        _ => {}
+    }
+}
@@ -0,0 +1,169 @@
+// replay.rs — 以錄製的 session（recorder.rs）取代 helper，重現 bug report
+//
+// ReplayTransport 在記憶體中扮演 helper：每收到一個請求，就依 cmd 取出 session 中
+// 下一個同名請求當時收到的事件與回應，換上新的 id 後送回。因此 call_helper 與 Tauri
+// 命令層完全照常運作，bug report 附上的 session 檔可以直接寫成回歸測試。
+//
+//   - 比對只看 cmd 與順序，不比對 args
+//   - 錄製時沒有回應的請求（例如超時）重播時同樣不回應
+//   - session 用盡、或沒錄到的 cancel / ping 分別回傳錯誤與 true
+//   - 重播一律使用 JSON-line；帶二進位 payload 的事件（只錄了長度）不重播
+
+use crate::transport::{Connection, Transport};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream};
+use tokio::task::JoinHandle;
+
+/// 一個錄製下來的請求：回應前收到的事件，以及回應本身（None = 當時沒有回應）
+#[derive(Debug, Clone)]
+struct Exchange {
+    events: Vec<Value>,
+    response: Option<Value>,
+}
+
+/// 解析後的 session：cmd → 依送出順序排列的 exchange
+#[derive(Debug, Default)]
+pub struct Session {
+    exchanges: HashMap<String, VecDeque<Exchange>>,
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Session, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read session {}: {}", path.display(), e))?;
+
+        let mut lines = raw.lines().filter(|l| !l.trim().is_empty());
+        let header: Value = lines
+            .next()
+            .and_then(|l| serde_json::from_str(l).ok())
+            .ok_or_else(|| format!("{} is not an EchoType bridge session", path.display()))?;
+        if header["echotype_session"].as_u64() != Some(crate::recorder::SESSION_VERSION as u64) {
+            return Err(format!("Unsupported session version in {}: {}", path.display(), header));
+        }
+
+        // 依送出順序建立 exchange；回應依原始 id 對回，事件歸給其後第一個回應
+        let mut order: Vec<(String, u64)> = Vec::new();
+        let mut recorded: HashMap<u64, Exchange> = HashMap::new();
+        let mut events: Vec<Value> = Vec::new();
+
+        for (n, line) in lines.enumerate() {
+            let entry: Value = serde_json::from_str(line)
+                .map_err(|e| format!("{} line {}: {}", path.display(), n + 2, e))?;
+            let message = &entry["message"];
+            let id = message["id"].as_u64();
+
+            match (entry["dir"].as_str(), id) {
+                (Some("send"), Some(id)) => {
+                    let cmd = message["cmd"].as_str().unwrap_or_default().to_string();
+                    order.push((cmd, id));
+                    recorded.insert(id, Exchange { events: Vec::new(), response: None });
+                }
+                (Some("recv"), Some(id)) => {
+                    if let Some(exchange) = recorded.get_mut(&id) {
+                        exchange.events = std::mem::take(&mut events);
+                        exchange.response = Some(message.clone());
+                    }
+                }
+                (Some("recv"), None)
+                    if message.get("event").is_some() && entry.get("payload_len").is_none() =>
+                {
+                    events.push(message.clone());
+                }
+                _ => {}
+            }
+        }
+
+        let mut session = Session::default();
+        for (cmd, id) in order {
+            if let Some(exchange) = recorded.remove(&id) {
+                session.exchanges.entry(cmd).or_default().push_back(exchange);
+            }
+        }
+        Ok(session)
+    }
+
+    /// 取出 cmd 的下一個 exchange
+    fn next(&mut self, cmd: &str) -> Option<Exchange> {
+        self.exchanges.get_mut(cmd)?.pop_front()
+    }
+}
+
+pub struct ReplayTransport {
+    path: PathBuf,
+    server: JoinHandle<()>,
+}
+
+impl ReplayTransport {
+    /// 載入 session 並啟動記憶體中的假 helper
+    pub fn open(path: &Path) -> Result<Connection, String> {
+        let session = Session::load(path)?;
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(serve(session, server));
+        let (reader, writer) = tokio::io::split(client);
+
+        Ok(Connection {
+            transport: Box::new(ReplayTransport { path: path.to_path_buf(), server }),
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+        })
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn is_alive(&self) -> bool {
+        !self.server.is_finished()
+    }
+
+    fn close(&self) {
+        self.server.abort();
+    }
+
+    fn describe(&self) -> String {
+        format!("replay {}", self.path.display())
+    }
+}
+
+/// 假 helper：逐行讀取請求並送回錄製的事件與回應
+async fn serve(mut session: Session, stream: DuplexStream) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Ok(request) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let id = request["id"].clone();
+        let cmd = request["cmd"].as_str().unwrap_or_default();
+
+        let mut out: Vec<Value> = Vec::new();
+        match session.next(cmd) {
+            Some(exchange) => {
+                out.extend(exchange.events);
+                if let Some(mut response) = exchange.response {
+                    response["id"] = id;
+                    if cmd == "hello" {
+                        response["result"]["framing"] = json!("json_lines");
+                    }
+                    out.push(response);
+                }
+            }
+            None if cmd == "cancel" || cmd == "ping" => {
+                out.push(json!({ "id": id, "ok": true, "result": true }));
+            }
+            None => out.push(json!({
+                "id": id,
+                "ok": false,
+                "error": format!("Replay session has no more recorded responses for {}", cmd),
+            })),
+        }
+
+        for message in out {
+            let line = format!("{}\n", message);
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
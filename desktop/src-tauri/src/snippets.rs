@@ -0,0 +1,104 @@
+// snippets.rs — 語音觸發的文字片段（地址、署名、常用連結）
+//
+// 片段存在 helper 的 `snippets` 設定（JSON 陣列）：
+//   [{"trigger": "my address", "text": "台北市信義區…"}, {"trigger": "簽名", "text": "Best,\nAlex\n{date}"}]
+// 說出觸發詞就換成片段內容；觸發詞比對不分大小寫，並以整個字詞為單位。
+// 片段內容原樣插入（不再經過標點修復或 LLM），支援的佔位符：
+//   {date}           今天的日期（helper 依本機時區，YYYY-MM-DD）
+//   {clipboard}      剪貼簿文字
+//   {selected_text}  焦點 App 中選取的文字
+// 取不到的值換成空字串；其他大括號原樣保留。
+
+use crate::cleanup;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    /// 觸發詞（"my address"、「我的簽名」）
+    pub trigger: String,
+    /// 展開後的文字，可含佔位符
+    pub text: String,
+}
+
+impl Snippet {
+    pub fn new(trigger: impl Into<String>, text: impl Into<String>) -> Snippet {
+        Snippet { trigger: trigger.into(), text: text.into() }
+    }
+
+    /// 片段內容是否用到某個佔位符（例如 `"{clipboard}"`）
+    pub fn uses(&self, placeholder: &str) -> bool {
+        self.text.contains(placeholder)
+    }
+}
+
+/// 佔位符的值；stop_recording 只在有片段用到時才向 helper 取得
+#[derive(Debug, Clone, Default)]
+pub struct Placeholders {
+    pub date: Option<String>,
+    pub clipboard: Option<String>,
+    pub selected_text: Option<String>,
+}
+
+/// 把轉錄中的觸發詞換成片段內容
+pub fn expand(text: &str, snippets: &[Snippet], values: &Placeholders) -> String {
+    // 空觸發詞永遠比對成功，必須排除；較長的觸發詞優先（"my address" 先於 "address"）
+    let mut snippets: Vec<&Snippet> = snippets
+        .iter()
+        .filter(|s| cleanup::tokenize(&s.trigger).iter().any(|t| t.is_unit()))
+        .collect();
+    if snippets.is_empty() {
+        return text.to_string();
+    }
+    snippets.sort_by_key(|s| std::cmp::Reverse(s.trigger.chars().count()));
+
+    let tokens = cleanup::tokenize(text);
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    'tokens: while i < tokens.len() {
+        if tokens[i].is_unit() {
+            for snippet in &snippets {
+                let Some(end) = cleanup::match_phrase(&tokens, i, &snippet.trigger) else {
+                    continue;
+                };
+                // 整段話只有觸發詞時，標點階段補上的句號不該接在片段後面；其他標點照原文保留
+                let whole = out.trim().is_empty();
+                out.push_str(&render(&snippet.text, values));
+                let next = cleanup::skip_space(&tokens, end);
+                let added_period = tokens.get(next).is_some_and(|t| matches!(t.text.as_str(), "." | "。"))
+                    && cleanup::skip_space(&tokens, next + 1) == tokens.len();
+                i = if whole && added_period { tokens.len() } else { end };
+                continue 'tokens;
+            }
+        }
+        out.push_str(&tokens[i].text);
+        i += 1;
+    }
+    out
+}
+
+fn render(template: &str, values: &Placeholders) -> String {
+    let placeholders = [
+        ("{date}", &values.date),
+        ("{clipboard}", &values.clipboard),
+        ("{selected_text}", &values.selected_text),
+    ];
+    // 單次掃描，剪貼簿內容裡的大括號不會再被展開
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        match placeholders.iter().find(|(name, _)| tail.starts_with(name)) {
+            Some((name, value)) => {
+                out.push_str(value.as_deref().unwrap_or_default());
+                rest = &tail[name.len()..];
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
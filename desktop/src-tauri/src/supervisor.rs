@@ -0,0 +1,161 @@
+// supervisor.rs — EchoTypeHelper 監督：崩潰計數、重啟退避與狀態事件
+//
+// bridge 在 spawn / 握手 / 崩潰時回報給 supervisor：
+//   - 每次崩潰後的重啟以指數退避延遲（0.5s, 1s, 2s ... 上限 30s）
+//   - CRASH_WINDOW 內崩潰達 MAX_CRASHES 次即停止重啟，狀態轉為 degraded，
+//     並附上 helper_stderr.log 的最後幾行作為診斷；使用者可透過 restart_helper 重試
+//   - 每次狀態變化 emit `helper-status` 事件給前端與托盤
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const BACKOFF_BASE_MS: u64 = 500;
+const BACKOFF_MAX_MS: u64 = 30_000;
+const MAX_CRASHES: usize = 3;                          // 視窗內允許的崩潰次數
+const CRASH_WINDOW: Duration = Duration::from_secs(120);
+const STDERR_TAIL_LINES: usize = 20;
+const STDERR_TAIL_BYTES: u64 = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HelperStatus {
+    Starting,
+    Ready,
+    Crashed,
+    Degraded,
+}
+
+/// `helper-status` 事件內容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEvent {
+    pub status: HelperStatus,
+    pub crashes: usize,
+    pub message: Option<String>,
+}
+
+struct State {
+    crashes: VecDeque<Instant>,
+    last_crash: Option<Instant>,
+    diagnosis: Option<String>,   // Some = 已放棄重啟
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    crashes: VecDeque::new(),
+    last_crash: None,
+    diagnosis: None,
+});
+
+fn emit(status: HelperStatus, crashes: usize, message: Option<String>) {
+    let event = StatusEvent { status, crashes, message };
+    crate::bridge::emit_app_event("helper-status", event);
+}
+
+/// 第 n 次崩潰後的退避時間
+fn backoff(crashes: usize) -> Duration {
+    if crashes == 0 {
+        return Duration::ZERO;
+    }
+    let factor = 1u64 << (crashes - 1).min(16);
+    Duration::from_millis((BACKOFF_BASE_MS * factor).min(BACKOFF_MAX_MS))
+}
+
+/// spawn 前呼叫：已放棄時回傳診斷錯誤，否則回傳仍需等待的退避時間
+pub fn spawn_permit() -> Result<Duration, String> {
+    let mut state = STATE.lock().unwrap();
+    if let Some(diagnosis) = &state.diagnosis {
+        return Err(diagnosis.clone());
+    }
+
+    let now = Instant::now();
+    state.crashes.retain(|t| now.duration_since(*t) < CRASH_WINDOW);
+    let wait = match state.last_crash {
+        Some(at) => backoff(state.crashes.len()).saturating_sub(now.duration_since(at)),
+        None => Duration::ZERO,
+    };
+    let crashes = state.crashes.len();
+    drop(state);
+
+    emit(HelperStatus::Starting, crashes, None);
+    Ok(wait)
+}
+
+/// 握手成功
+pub fn helper_ready(helper_version: &str) {
+    let crashes = STATE.lock().unwrap().crashes.len();
+    emit(HelperStatus::Ready, crashes, Some(helper_version.to_string()));
+}
+
+/// 握手失敗等無法啟動的情況（不計入崩潰，但 UI 需要知道原因）
+pub fn helper_failed(reason: &str) {
+    let crashes = STATE.lock().unwrap().crashes.len();
+    emit(HelperStatus::Degraded, crashes, Some(reason.to_string()));
+}
+
+/// helper 意外結束或心跳無回應
+pub fn record_crash(reason: &str) {
+    let mut state = STATE.lock().unwrap();
+    let now = Instant::now();
+    state.crashes.push_back(now);
+    state.crashes.retain(|t| now.duration_since(*t) < CRASH_WINDOW);
+    state.last_crash = Some(now);
+    let crashes = state.crashes.len();
+
+    eprintln!("[supervisor] Helper crashed ({}), {} crash(es) in window", reason, crashes);
+
+    if crashes < MAX_CRASHES {
+        drop(state);
+        emit(HelperStatus::Crashed, crashes, Some(reason.to_string()));
+        return;
+    }
+
+    let stderr_log = stderr_log_path();
+    let diagnosis = format!(
+        "EchoTypeHelper crashed {} times within {}s and will not be restarted automatically.\n\
+         Last failure: {}\n--- {} (tail) ---\n{}",
+        crashes,
+        CRASH_WINDOW.as_secs(),
+        reason,
+        stderr_log.display(),
+        stderr_tail(&stderr_log)
+    );
+    state.diagnosis = Some(diagnosis.clone());
+    drop(state);
+    emit(HelperStatus::Degraded, crashes, Some(diagnosis));
+}
+
+/// 清除崩潰紀錄，允許再次重啟（使用者手動重試）
+pub fn reset() {
+    let mut state = STATE.lock().unwrap();
+    state.crashes.clear();
+    state.last_crash = None;
+    state.diagnosis = None;
+}
+
+/// stderr 日誌的最後幾行（只讀取檔尾，避免整個日誌載入記憶體）
+fn stderr_tail(path: &Path) -> String {
+    let read_tail = || -> std::io::Result<String> {
+        let mut file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        file.seek(SeekFrom::Start(len.saturating_sub(STDERR_TAIL_BYTES)))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    };
+
+    match read_tail() {
+        Ok(text) => {
+            let lines: Vec<&str> = text.lines().collect();
+            lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n")
+        }
+        Err(e) => format!("(unable to read stderr log: {})", e),
+    }
+}
+
+/// helper stderr 日誌位置
+pub fn stderr_log_path() -> PathBuf {
+    std::env::temp_dir().join("echotype").join("helper_stderr.log")
+}
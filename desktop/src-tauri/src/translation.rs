@@ -0,0 +1,73 @@
+// translation.rs — 翻譯聽寫：依 App / 網域決定目標語言
+//
+// 設定存在 `translation`（JSON）：
+//   {"enabled": true, "default_target": "", "targets": {"com.tinyspeck.slackmacgap": "en-US", "atlassian.net": "en-US"}}
+// targets 的鍵是 bundle id 或網域（子網域也算，"atlassian.net" 涵蓋 "acme.atlassian.net"）；
+// 都不符合時用 default_target，空字串代表不翻譯。ASR 偵測到的語言已是目標語言時也不翻譯。
+
+use crate::log_warn;
+use crate::protocol::AppContext;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranslationSettings {
+    pub enabled: bool,
+    /// 沒有符合的 App / 網域時的目標語言（例如 `en-US`）
+    pub default_target: String,
+    /// bundle id 或網域 → 目標語言
+    pub targets: BTreeMap<String, String>,
+}
+
+impl TranslationSettings {
+    /// 解析設定值；空字串或格式錯誤時視為停用
+    pub fn parse(raw: &str) -> TranslationSettings {
+        if raw.trim().is_empty() {
+            return TranslationSettings::default();
+        }
+        serde_json::from_str(raw).unwrap_or_else(|e| {
+            log_warn!("[translation] Invalid translation setting, translation disabled: {}", e);
+            TranslationSettings::default()
+        })
+    }
+
+    /// 目前 App 的目標語言；`detected` 是 ASR 偵測到的語言
+    pub fn target_for(&self, ctx: &AppContext, detected: Option<&str>) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        let target = self.configured_target(ctx)?;
+        if detected.is_some_and(|lang| same_language(lang, &target)) {
+            return None;
+        }
+        Some(target)
+    }
+
+    /// 目前 App 設定的目標語言，不論是否啟用翻譯聽寫；沒有設定（或設成空字串）時回傳 None
+    pub fn configured_target(&self, ctx: &AppContext) -> Option<String> {
+        let by_app = ctx.bundle_id.as_deref().and_then(|id| {
+            self.targets.iter().find(|(key, _)| key.eq_ignore_ascii_case(id)).map(|(_, target)| target)
+        });
+        let by_domain = || {
+            let domain = ctx.web_domain.as_deref()?.to_ascii_lowercase();
+            // 最長的網域優先（"jira.acme.com" 先於 "acme.com"）
+            self.targets
+                .iter()
+                .filter(|(key, _)| {
+                    let key = key.to_ascii_lowercase();
+                    domain == key || domain.ends_with(&format!(".{}", key))
+                })
+                .max_by_key(|(key, _)| key.len())
+                .map(|(_, target)| target)
+        };
+        let target = by_app.or_else(by_domain).unwrap_or(&self.default_target).trim();
+        (!target.is_empty()).then(|| target.to_string())
+    }
+}
+
+/// 主要語言相同（"en" 與 "en-US"、"zh" 與 "zh-TW"）
+fn same_language(a: &str, b: &str) -> bool {
+    let primary = |code: &str| code.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    primary(a) == primary(b)
+}
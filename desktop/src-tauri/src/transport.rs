@@ -0,0 +1,164 @@
+// transport.rs — bridge 與 EchoTypeHelper 之間的連線方式
+//
+//   StdioTransport  — spawn helper 子進程，經由 stdin/stdout 溝通（預設、fallback）
+//   SocketTransport — 連線到已在運行的 helper 所監聽的 Unix domain socket
+//
+// 獨立啟動 helper（可掛 debugger，或讓 app 與 CLI 共用同一個 helper）：
+//   EchoTypeHelper --socket [path]
+// app 先嘗試連線 socket（路徑見 socket_path()），連不上才 spawn 子進程。
+// 兩種連線上跑的都是同一套 JSON-line 協定，bridge 不需區分。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::process::{Child, Command};
+
+pub type BoxReader = Box<dyn AsyncRead + Send + Unpin>;
+pub type BoxWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// 一條已建立的 helper 連線的控制端（讀寫兩端由 Connection 分別交給 bridge）
+pub trait Transport: Send + Sync {
+    /// 連線是否仍可用（reader EOF 之外的額外檢查，例如子進程已結束）
+    fn is_alive(&self) -> bool;
+    /// 結束連線：stdio 會殺掉子進程；socket 只斷線，不影響 helper 本身
+    fn close(&self);
+    /// 供日誌與錯誤訊息使用，例如 helper 路徑或 socket 路徑
+    fn describe(&self) -> String;
+}
+
+/// 新建立的連線
+pub struct Connection {
+    pub transport: Box<dyn Transport>,
+    pub reader: BoxReader,
+    pub writer: BoxWriter,
+}
+
+/// app 嘗試連線的 socket 路徑：`ECHOTYPE_HELPER_SOCKET` 優先，否則為暫存目錄下的預設位置
+pub fn socket_path() -> PathBuf {
+    match std::env::var_os("ECHOTYPE_HELPER_SOCKET") {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => std::env::temp_dir().join("echotype").join("helper.sock"),
+    }
+}
+
+// MARK: - Stdio
+
+pub struct StdioTransport {
+    path: PathBuf,
+    child: Mutex<Child>,
+}
+
+impl StdioTransport {
+    /// spawn helper 子進程，stderr 附加到 `stderr_log`
+    pub fn spawn(helper_path: &Path, stderr_log: &Path) -> Result<Connection, String> {
+        if !helper_path.exists() {
+            return Err(format!(
+                "EchoTypeHelper not found at {}. Run: cd EchoType/helper && swift build",
+                helper_path.display()
+            ));
+        }
+
+        // M1 修復：將 stderr 重定向到日誌文件而非 /dev/null
+        if let Some(log_dir) = stderr_log.parent() {
+            std::fs::create_dir_all(log_dir).ok();
+        }
+        let stderr_file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(stderr_log)
+            .map_err(|e| format!("Failed to open stderr log: {}", e))?;
+
+        let mut child = Command::new(helper_path)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(stderr_file)
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to spawn EchoTypeHelper: {}", e))?;
+
+        eprintln!("[bridge] Helper stderr logging to: {}", stderr_log.display());
+
+        let stdin = child.stdin.take().ok_or("No stdin")?;
+        let stdout = child.stdout.take().ok_or("No stdout")?;
+
+        Ok(Connection {
+            transport: Box::new(StdioTransport {
+                path: helper_path.to_path_buf(),
+                child: Mutex::new(child),
+            }),
+            reader: Box::new(stdout),
+            writer: Box::new(stdin),
+        })
+    }
+}
+
+impl Transport for StdioTransport {
+    fn is_alive(&self) -> bool {
+        match self.child.lock().unwrap().try_wait() {
+            Ok(Some(status)) => {
+                eprintln!("[bridge] Helper process exited with status: {}", status);
+                false
+            }
+            Ok(None) => true,  // 進程仍在運行
+            Err(e) => {
+                eprintln!("[bridge] Failed to check helper status: {}", e);
+                true
+            }
+        }
+    }
+
+    fn close(&self) {
+        if let Err(e) = self.child.lock().unwrap().start_kill() {
+            eprintln!("[bridge] Failed to kill helper: {}", e);
+        }
+    }
+
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+// MARK: - Unix socket
+
+#[cfg(unix)]
+pub struct SocketTransport {
+    path: PathBuf,
+    control: std::os::unix::net::UnixStream,  // 與讀寫端共用同一個 socket，用於 close()
+}
+
+#[cfg(unix)]
+impl SocketTransport {
+    /// 連線到 `path`；沒有 helper 在監聽時回傳 Err，由呼叫者改為 spawn
+    pub async fn connect(path: &Path) -> Result<Connection, String> {
+        let stream = tokio::net::UnixStream::connect(path)
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", path.display(), e))?;
+
+        let std_stream = stream.into_std().map_err(|e| e.to_string())?;
+        let control = std_stream.try_clone().map_err(|e| e.to_string())?;
+        let stream = tokio::net::UnixStream::from_std(std_stream).map_err(|e| e.to_string())?;
+        let (reader, writer) = stream.into_split();
+
+        Ok(Connection {
+            transport: Box::new(SocketTransport { path: path.to_path_buf(), control }),
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Transport for SocketTransport {
+    fn is_alive(&self) -> bool {
+        true  // 斷線由 reader 讀到 EOF 得知
+    }
+
+    fn close(&self) {
+        // 兩個方向都關閉：reader 立即讀到 EOF，helper 端看到此連線結束
+        let _ = self.control.shutdown(std::net::Shutdown::Both);
+    }
+
+    fn describe(&self) -> String {
+        format!("socket {}", self.path.display())
+    }
+}
@@ -1,9 +1,22 @@
+use crate::supervisor::{HelperStatus, StatusEvent};
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    App, Emitter, Manager,
+    App, Emitter, Listener, Manager,
 };
 
+const TOOLTIP_READY: &str = "EchoType — 語音輸入助手";
+
+/// helper 狀態 → 托盤提示文字
+fn status_tooltip(event: &StatusEvent) -> String {
+    match event.status {
+        HelperStatus::Starting => "EchoType — 正在啟動 Helper…".to_string(),
+        HelperStatus::Ready => TOOLTIP_READY.to_string(),
+        HelperStatus::Crashed => format!("EchoType — Helper 已崩潰（{} 次），正在重新啟動", event.crashes),
+        HelperStatus::Degraded => "EchoType — Helper 無法啟動，請開啟控制台查看".to_string(),
+    }
+}
+
 pub fn setup_tray(app: &mut App) -> tauri::Result<()> {
     let quit = MenuItem::with_id(app, "quit", "結束 EchoType", true, None::<&str>)?;
     let show = MenuItem::with_id(app, "show", "開啟控制台", true, None::<&str>)?;
@@ -20,8 +33,8 @@ pub fn setup_tray(app: &mut App) -> tauri::Result<()> {
         tray_builder
     };
 
-    let _tray = tray_builder
-        .tooltip("EchoType — 語音輸入助手")
+    let tray = tray_builder
+        .tooltip(TOOLTIP_READY)
         .on_tray_icon_event(|tray, event| match event {
             TrayIconEvent::Click {
                 button: MouseButton::Left,
@@ -78,5 +91,17 @@ pub fn setup_tray(app: &mut App) -> tauri::Result<()> {
         })
         .build(app)?;
 
+    // helper 狀態變化反映在托盤提示
+    app.listen_any("helper-status", move |event| {
+        match serde_json::from_str::<StatusEvent>(event.payload()) {
+            Ok(status) => {
+                if let Err(e) = tray.set_tooltip(Some(status_tooltip(&status))) {
+                    eprintln!("[tray] Failed to update tooltip: {}", e);
+                }
+            }
+            Err(e) => eprintln!("[tray] Invalid helper-status payload: {}", e),
+        }
+    });
+
     Ok(())
 }
@@ -0,0 +1,119 @@
+// cleanup.rs — 規則式清理的表格測試
+//
+// 每一列：(ASR 語言, 輸入, 預期輸出)。語言為 None 時套用所有語言的規則。
+
+use echotype_lib::cleanup::clean;
+
+fn check(cases: &[(Option<&str>, &str, &str)]) {
+    let failures: Vec<String> = cases
+        .iter()
+        .filter_map(|&(language, input, expected)| {
+            let actual = clean(input, language);
+            (actual != expected).then(|| format!("{:?} {:?}\n    expected {:?}\n    actual   {:?}", language, input, expected, actual))
+        })
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn fillers() {
+    check(&[
+        (Some("en"), "um so I think uh we should go", "so I think we should go"),
+        (Some("en"), "Uh, can you, you know, send it", "can you, send it"),
+        (Some("en"), "Hmm, that works um.", "that works."),
+        (Some("en"), "umbrella and error", "umbrella and error"),
+        (Some("en"), "I know you know the answer", "I know you know the answer"),
+        (Some("zh"), "嗯，今天天氣很好", "今天天氣很好"),
+        (Some("zh"), "呃我覺得那個，可以再想想", "我覺得可以再想想"),
+        (Some("zh"), "那個人很高", "那個人很高"),
+        (Some("zh"), "然後我們就走了", "然後我們就走了"),
+        (Some("zh"), "然後，我們就走了。就是，很累", "我們就走了。很累"),
+        (Some("zh"), "這個，這個方案不錯", "這個方案不錯"),
+        (Some("ja"), "えっと、明日は晴れです", "明日は晴れです"),
+        (Some("ja"), "あの、すみません", "すみません"),
+        (Some("ja"), "あの人は先生です", "あの人は先生です"),
+        // 語言未知時各語言的填充詞都會移除
+        (None, "um 嗯，好", "好"),
+        // 指定語言時只套用該語言
+        (Some("en"), "嗯 hello", "嗯 hello"),
+    ]);
+}
+
+#[test]
+fn stutters() {
+    check(&[
+        (Some("en"), "I I think it's fine", "I think it's fine"),
+        (Some("en"), "the the the report", "the report"),
+        (Some("en"), "I, I don't know", "I don't know"),
+        (Some("en"), "we should th- think about it", "we should think about it"),
+        (Some("en"), "I know that that is true", "I know that that is true"),
+        (Some("en"), "very very good", "very very good"),
+        (Some("zh"), "我我我覺得可以", "我覺得可以"),
+        (Some("zh"), "我我覺得可以", "我覺得可以"),
+        (Some("zh"), "我們，我們明天去", "我們明天去"),
+        (Some("zh"), "謝謝你", "謝謝你"),
+        (Some("zh"), "我們研究研究再說", "我們研究研究再說"),
+        (Some("zh"), "哈哈哈好好笑", "哈哈哈好好笑"),
+    ]);
+}
+
+#[test]
+fn self_corrections() {
+    check(&[
+        (Some("en"), "Let's meet at 3 pm, no wait, 4 pm", "Let's meet at 4 pm"),
+        (Some("en"), "send it to John, no wait, send it to Mary", "send it to Mary"),
+        (Some("en"), "email John, I mean, email Mary about it", "email Mary about it"),
+        (Some("en"), "It costs 5 dollars, actually, no, 6 dollars", "It costs 6 dollars"),
+        (Some("en"), "I went to the store, no wait, the mall", "I went to the mall"),
+        (Some("en"), "I mean it", "I mean it"),
+        (Some("en"), "Done. I'll be late scratch that I'll be on time", "Done. I'll be on time"),
+        (Some("zh"), "明天三點，不對，四點開會", "明天四點開會"),
+        (Some("zh"), "我們週二，我是說，我們週三見", "我們週三見"),
+        (Some("zh"), "我想要咖啡，不對，我想要茶", "我想要茶"),
+        (Some("zh"), "好的。剛剛那句不算，改成下週", "好的。改成下週"),
+        (Some("ja"), "三時に、いや、四時に会いましょう", "四時に会いましょう"),
+    ]);
+}
+
+#[test]
+fn leaves_ordinary_sentences_alone() {
+    check(&[
+        // 更正標記不是獨立子句
+        (Some("en"), "There is no wait time at the restaurant.", "There is no wait time at the restaurant."),
+        (Some("en"), "It was actually no problem.", "It was actually no problem."),
+        (Some("en"), "Do you know what I mean, right?", "Do you know what I mean, right?"),
+        (Some("en"), "Let's meet at 3 no wait 4 pm", "Let's meet at 3 no wait 4 pm"),
+        (Some("zh"), "這樣不對。", "這樣不對。"),
+        (Some("ja"), "三時じゃなくて四時に会いましょう", "三時じゃなくて四時に会いましょう"),
+        // 前文找不到更正對應的詞時不猜測要刪掉哪裡
+        (Some("en"), "email John, I mean, Mary about it", "email John, I mean, Mary about it"),
+        // 像填充詞的單字與句中的「就是」「然後」
+        (Some("en"), "Use 10 mm screws.", "Use 10 mm screws."),
+        (Some("en"), "Send the er diagram.", "Send the er diagram."),
+        (Some("zh"), "問題就是，我們沒有錢", "問題就是，我們沒有錢"),
+        (Some("zh"), "吃完飯然後，我們去散步", "吃完飯然後，我們去散步"),
+    ]);
+}
+
+#[test]
+fn spacing_and_punctuation_width() {
+    check(&[
+        (Some("zh"), "我用iPhone打字", "我用 iPhone 打字"),
+        (Some("zh"), "版本 3 已經發布", "版本 3 已經發布"),
+        (Some("zh"), "你好,今天好嗎?", "你好，今天好嗎？"),
+        (Some("zh"), "OK,我知道了.", "OK，我知道了。"),
+        (Some("zh"), "我有12.", "我有 12。"),
+        (Some("zh"), "價格是 3.5 元", "價格是 3.5 元"),
+        (Some("zh"), "今天 天氣 很好 。", "今天天氣很好。"),
+        (Some("en"), "hello，world！", "hello, world!"),
+        (Some("en"), "wait ,what ?", "wait, what?"),
+        (Some("en"), "meet at 3:30 ok", "meet at 3:30 ok"),
+        (Some("en"), "it costs 1,000 dollars", "it costs 1,000 dollars"),
+        (Some("en"), "about 12,345 people, 3 teams", "about 12,345 people, 3 teams"),
+        (Some("zh"), "價格是1,000元", "價格是 1,000 元"),
+        (Some("zh"), "有12,345人", "有 12,345 人"),
+        (Some("en"), "so...", "so..."),
+        (Some("ja"), "はい,わかりました.", "はい、わかりました。"),
+        (Some("en"), "  lots   of   space  ", "lots of space"),
+    ]);
+}
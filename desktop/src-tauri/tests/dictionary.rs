@@ -0,0 +1,123 @@
+// dictionary.rs — 詞典詞條解析與誤辨識修正的表格測試
+
+use echotype_lib::dictionary::{correct, CaseRule, DictionaryEntry, MatchKind, Replacement};
+
+fn entry(term: &str, sounds_like: &[&str]) -> DictionaryEntry {
+    DictionaryEntry { sounds_like: sounds_like.iter().map(|s| s.to_string()).collect(), ..DictionaryEntry::new(term) }
+}
+
+#[test]
+fn parses_plain_strings_and_entries() {
+    let raw = r#"["EchoType", {"term": "Kubernetes", "sounds_like": ["cooper netties"]}, {"term": "Go", "case": "sensitive"}]"#;
+    let entries: Vec<DictionaryEntry> = serde_json::from_str(raw).unwrap();
+    assert_eq!(
+        entries,
+        [
+            DictionaryEntry::new("EchoType"),
+            entry("Kubernetes", &["cooper netties"]),
+            DictionaryEntry { case: CaseRule::Sensitive, ..DictionaryEntry::new("Go") },
+        ]
+    );
+
+    // 寫回時一律是物件格式
+    let json = serde_json::to_value(&entries[0]).unwrap();
+    assert_eq!(json, serde_json::json!({ "term": "EchoType", "sounds_like": [], "case": "canonical" }));
+}
+
+#[test]
+fn latin_terms() {
+    let dictionary = [
+        DictionaryEntry::new("GitHub"),
+        DictionaryEntry::new("Git"),
+        DictionaryEntry::new("Notion"),
+        DictionaryEntry::new("EchoType"),
+        entry("Kubernetes", &["cooper netties"]),
+        DictionaryEntry::new("Anthropic"),
+    ];
+    let cases = [
+        // 大小寫與空白
+        ("push it to github", "push it to GitHub"),
+        ("push it to git hub.", "push it to GitHub."),
+        ("git and GITHUB", "Git and GitHub"),
+        ("try echo type today", "try EchoType today"),
+        // 別名
+        ("deploy to cooper netties", "deploy to Kubernetes"),
+        // 語音相近
+        ("deploy to kubernetis", "deploy to Kubernetes"),
+        // 編輯距離
+        ("I work at anthrophic", "I work at Anthropic"),
+        ("the echotipe app", "the EchoType app"),
+        // 不該改的
+        ("digital gits", "digital gits"),
+        ("the nation and the motion", "the nation and the motion"),
+        ("notes in notion", "notes in Notion"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(correct(input, &dictionary).text, expected, "{input}");
+    }
+}
+
+#[test]
+fn cjk_terms() {
+    let dictionary = [
+        entry("台積電", &["台雞電"]),
+        entry("語音輸入", &["雨音輸入"]),
+        DictionaryEntry::new("張小明"),
+        DictionaryEntry::new("プログラミング"),
+        DictionaryEntry::new("EchoType"),
+    ];
+    let cases = [
+        ("我在台雞電上班", "我在台積電上班"),
+        ("我用echotype打字", "我用EchoType打字"),
+        ("雨音輸入很方便", "語音輸入很方便"),
+        ("ぷろぐらみんぐが好き", "プログラミングが好き"),
+        ("我今天見到張小明", "我今天見到張小明"),
+        // 中日文不做模糊比對：字不同就是不同的詞
+        ("我在台機電上班", "我在台機電上班"),
+        ("我今天見到張小姐", "我今天見到張小姐"),
+        ("他是王小明", "他是王小明"),
+        ("這是語音輸出功能", "這是語音輸出功能"),
+        ("台電", "台電"),
+        ("今天天氣很好", "今天天氣很好"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(correct(input, &dictionary).text, expected, "{input}");
+    }
+}
+
+#[test]
+fn sensitive_terms_only_replace_aliases() {
+    let dictionary = [DictionaryEntry {
+        sounds_like: vec!["go lang".into()],
+        case: CaseRule::Sensitive,
+        ..DictionaryEntry::new("Go")
+    }];
+    let cases = [
+        ("let's go home", "let's go home"),
+        ("written in go lang", "written in Go"),
+        ("Go is fast", "Go is fast"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(correct(input, &dictionary).text, expected, "{input}");
+    }
+}
+
+#[test]
+fn reports_replacements() {
+    let dictionary = [DictionaryEntry::new("GitHub"), entry("台積電", &["台雞電"]), entry("Kubernetes", &["cooper netties"])];
+    let correction = correct("GitHub 和 git hub，台雞電跟 cooper netties", &dictionary);
+    assert_eq!(correction.text, "GitHub 和 GitHub，台積電跟 Kubernetes");
+    let replacement = |original: &str, replacement: &str, kind| Replacement {
+        original: original.into(),
+        replacement: replacement.into(),
+        kind,
+    };
+    assert_eq!(
+        correction.replacements,
+        [
+            replacement("git hub", "GitHub", MatchKind::Exact),
+            replacement("台雞電", "台積電", MatchKind::Alias),
+            replacement("cooper netties", "Kubernetes", MatchKind::Alias),
+        ]
+    );
+}
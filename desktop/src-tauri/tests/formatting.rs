@@ -0,0 +1,98 @@
+// formatting.rs — 口述格式指令的表格測試
+
+use echotype_lib::formatting::{apply, FormattingSettings};
+
+/// 口述指令預設關閉，測試時開啟
+fn enabled() -> FormattingSettings {
+    FormattingSettings { enabled: true, ..Default::default() }
+}
+
+#[test]
+fn english_commands() {
+    let settings = enabled();
+    let cases = [
+        ("Hello period, how are you question mark", "Hello. how are you?"),
+        ("dear team comma new line thanks", "dear team,\nthanks"),
+        ("first point. New paragraph. Second point", "first point.\n\nSecond point"),
+        ("Groceries, bullet point, milk, bullet point, eggs", "Groceries\n- milk\n- eggs"),
+        ("He said, open quote, hello, close quote, and left", "He said \"hello\" and left"),
+        ("see the docs, open paren, page 3, close paren", "see the docs (page 3)"),
+        // ASR 自動加上的標點不重複
+        ("Thanks, period. See you. New line. Bye", "Thanks. See you.\nBye"),
+        ("New Line", "\n"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(apply(input, &settings, None), expected, "{input}");
+    }
+}
+
+#[test]
+fn chinese_and_japanese_commands() {
+    let settings = enabled();
+    let cases = [
+        ("今天天氣很好，句號，我們去散步吧", "今天天氣很好。我們去散步吧"),
+        ("第一點，換行，第二點", "第一點\n第二點"),
+        ("各位好逗號新段落會議改到明天", "各位好，\n\n會議改到明天"),
+        ("購物清單，項目符號，牛奶，項目符號，雞蛋", "購物清單\n- 牛奶\n- 雞蛋"),
+        ("他說，上引號，好的，下引號", "他說「好的」"),
+        ("真的嗎問號", "真的嗎？"),
+        ("明日は晴れです句点改行かっこはいかっことじ", "明日は晴れです。\n「はい」"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(apply(input, &settings, None), expected, "{input}");
+    }
+}
+
+#[test]
+fn commands_inside_a_clause_are_left_alone() {
+    let settings = enabled();
+    let cases = [
+        "the period ended",
+        "I want a period of rest",
+        "The new line is broken",
+        "put a comma here",
+        "今天天氣很好句號我們去散步吧",
+        "這是句號嗎",
+    ];
+    for input in cases {
+        assert_eq!(apply(input, &settings, None), input);
+    }
+}
+
+#[test]
+fn commands_are_opt_in() {
+    assert!(!FormattingSettings::default().enabled);
+    assert_eq!(apply("one, new line, two", &FormattingSettings::default(), None), "one, new line, two");
+}
+
+#[test]
+fn languages_can_be_disabled() {
+    let english_only = FormattingSettings { languages: vec!["en".into()], ..enabled() };
+    assert_eq!(apply("第一點，換行，第二點", &english_only, None), "第一點，換行，第二點");
+    assert_eq!(apply("one, new line, two", &english_only, None), "one\ntwo");
+}
+
+#[test]
+fn apps_can_opt_out_by_bundle_id() {
+    let settings = FormattingSettings {
+        disabled_apps: vec!["com.microsoft.VSCode".into()],
+        ..enabled()
+    };
+    assert_eq!(apply("git commit new line", &settings, Some("com.microsoft.vscode")), "git commit new line");
+    assert_eq!(apply("git commit new line", &settings, Some("com.apple.Notes")), "git commit\n");
+
+    // 終端機預設停用
+    assert_eq!(apply("ls new line", &enabled(), Some("com.apple.Terminal")), "ls new line");
+}
+
+#[test]
+fn settings_parse_with_defaults() {
+    assert_eq!(FormattingSettings::parse(""), FormattingSettings::default());
+    assert_eq!(FormattingSettings::parse("not json"), FormattingSettings::default());
+
+    let parsed = FormattingSettings::parse(r#"{"enabled": true, "languages": ["zh"]}"#);
+    assert!(parsed.enabled);
+    assert_eq!(parsed.languages, ["zh"]);
+    assert_eq!(parsed.disabled_apps, FormattingSettings::default().disabled_apps);
+    assert!(!FormattingSettings::parse(r#"{"languages": ["zh"]}"#).enabled);
+}
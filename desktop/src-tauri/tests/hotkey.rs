@@ -0,0 +1,611 @@
+// hotkey.rs — 觸發鍵狀態機：debounce、組合鍵取消、300/500ms 閾值（虛擬時鐘）
+
+use echotype_lib::hotkey::{
+    Action, Binding, Bindings, CancelReason, HotkeyAction, HotkeyConfig, HotkeyMachine, Key, KeyEvent,
+    ManualClock, ModifierKey, Modifiers, State, Timing, Trigger, DEBOUNCE, DOUBLE_TAP, HOLD_THRESHOLD,
+    MIN_RECORDING,
+};
+use std::time::Duration;
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+fn machine() -> (HotkeyMachine<ManualClock>, ManualClock) {
+    let clock = ManualClock::default();
+    // 避開 0 起點，確認 debounce 不依賴時鐘讀數
+    clock.advance(ms(1_000));
+    (HotkeyMachine::new(clock.clone()), clock)
+}
+
+fn down(m: Modifiers) -> KeyEvent {
+    KeyEvent::Trigger { down: true, modifiers: m }
+}
+
+fn up(m: Modifiers) -> KeyEvent {
+    KeyEvent::Trigger { down: false, modifiers: m }
+}
+
+/// 按下 → 經過 300ms → poll，回到已進入 Holding 的狀態
+fn start_holding(hk: &mut HotkeyMachine<ManualClock>, clock: &ManualClock, m: Modifiers) {
+    assert_eq!(hk.handle(down(m)), None);
+    clock.advance(HOLD_THRESHOLD);
+    assert_eq!(hk.poll(), Some(Action::HoldStart { modifiers: m }));
+    assert_eq!(hk.state(), State::Holding);
+}
+
+// MARK: - Thresholds
+
+#[test]
+fn short_press_is_a_tap() {
+    let (mut hk, clock) = machine();
+    assert_eq!(hk.handle(down(Modifiers::NONE)), None);
+    assert_eq!(hk.state(), State::Pending);
+    clock.advance(ms(120));
+    assert_eq!(hk.handle(up(Modifiers::NONE)), Some(Action::Tap { modifiers: Modifiers::NONE }));
+    assert_eq!(hk.state(), State::Idle);
+    // 之後到期的計時器不會有動作
+    clock.advance(ms(500));
+    assert_eq!(hk.poll(), None);
+}
+
+#[test]
+fn hold_starts_exactly_at_threshold() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    assert_eq!(hk.deadline(), Some(ms(1_000) + HOLD_THRESHOLD));
+    clock.advance(HOLD_THRESHOLD - ms(1));
+    assert_eq!(hk.poll(), None);
+    assert_eq!(hk.state(), State::Pending);
+    clock.advance(ms(1));
+    assert_eq!(hk.poll(), Some(Action::HoldStart { modifiers: Modifiers::NONE }));
+    assert_eq!(hk.deadline(), None);
+    // 重複 poll 不會再觸發
+    assert_eq!(hk.poll(), None);
+}
+
+#[test]
+fn hold_released_after_min_recording_ends() {
+    let (mut hk, clock) = machine();
+    start_holding(&mut hk, &clock, Modifiers::NONE);
+    clock.advance(MIN_RECORDING - HOLD_THRESHOLD);
+    assert_eq!(hk.handle(up(Modifiers::NONE)), Some(Action::HoldEnd { modifiers: Modifiers::NONE }));
+    assert_eq!(hk.state(), State::Idle);
+}
+
+#[test]
+fn hold_released_before_min_recording_is_too_short() {
+    let (mut hk, clock) = machine();
+    start_holding(&mut hk, &clock, Modifiers::NONE);
+    clock.advance(MIN_RECORDING - HOLD_THRESHOLD - ms(1));
+    assert_eq!(
+        hk.handle(up(Modifiers::NONE)),
+        Some(Action::Cancel(CancelReason::TooShort))
+    );
+    assert_eq!(hk.state(), State::Idle);
+}
+
+// MARK: - Debounce
+
+#[test]
+fn bounce_within_debounce_window_is_ignored() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(DEBOUNCE - ms(1));
+    // 抖動：放開又按下，都在 50ms 內
+    assert_eq!(hk.handle(up(Modifiers::NONE)), None);
+    assert_eq!(hk.handle(down(Modifiers::NONE)), None);
+    assert_eq!(hk.state(), State::Pending);
+    clock.advance(HOLD_THRESHOLD);
+    assert_eq!(hk.poll(), Some(Action::HoldStart { modifiers: Modifiers::NONE }));
+}
+
+#[test]
+fn release_after_debounce_window_counts() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(DEBOUNCE);
+    assert_eq!(hk.handle(up(Modifiers::NONE)), Some(Action::Tap { modifiers: Modifiers::NONE }));
+}
+
+#[test]
+fn debounced_release_resolves_to_tap_at_poll() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(10));
+    assert_eq!(hk.handle(up(Modifiers::NONE)), None);
+    assert_eq!(hk.state(), State::Pending);
+    clock.advance(HOLD_THRESHOLD);
+    // 按鍵其實已放開，不能卡在 Holding
+    assert_eq!(hk.poll(), Some(Action::Tap { modifiers: Modifiers::NONE }));
+    assert_eq!(hk.state(), State::Idle);
+}
+
+#[test]
+fn quick_second_press_after_tap_is_debounced() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(up(Modifiers::NONE)), Some(Action::Tap { modifiers: Modifiers::NONE }));
+    clock.advance(ms(20));
+    assert_eq!(hk.handle(down(Modifiers::NONE)), None);
+    assert_eq!(hk.state(), State::Idle);
+    assert_eq!(hk.deadline(), None);
+}
+
+#[test]
+fn modifier_changes_do_not_refresh_debounce() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(40));
+    // 按著觸發鍵時 Ctrl 變化：不算觸發鍵事件
+    hk.handle(down(Modifiers::CTRL));
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(20));
+    assert_eq!(hk.handle(up(Modifiers::NONE)), Some(Action::Tap { modifiers: Modifiers::CTRL }));
+}
+
+// MARK: - Combo cancellation
+
+#[test]
+fn other_key_during_pending_cancels_silently() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(KeyEvent::KeyDown), None);
+    assert_eq!(hk.state(), State::Idle);
+    clock.advance(HOLD_THRESHOLD);
+    assert_eq!(hk.poll(), None);
+    // 放開觸發鍵也不會被當成點按
+    assert_eq!(hk.handle(up(Modifiers::NONE)), None);
+}
+
+#[test]
+fn other_key_during_hold_cancels_recording() {
+    let (mut hk, clock) = machine();
+    start_holding(&mut hk, &clock, Modifiers::NONE);
+    assert_eq!(hk.handle(KeyEvent::KeyDown), Some(Action::Cancel(CancelReason::ComboKey)));
+    assert_eq!(hk.state(), State::Idle);
+    clock.advance(MIN_RECORDING);
+    assert_eq!(hk.handle(up(Modifiers::NONE)), None);
+}
+
+#[test]
+fn non_ctrl_modifier_cancels() {
+    for m in [
+        Modifiers { shift: true, ..Modifiers::NONE },
+        Modifiers { alt: true, ..Modifiers::NONE },
+        Modifiers { cmd: true, ..Modifiers::NONE },
+        Modifiers { ctrl: true, cmd: true, ..Modifiers::NONE },
+    ] {
+        let (mut hk, _) = machine();
+        hk.handle(down(Modifiers::NONE));
+        assert_eq!(hk.handle(down(m)), None, "{m:?}");
+        assert_eq!(hk.state(), State::Idle, "{m:?}");
+
+        let (mut hk, clock) = machine();
+        start_holding(&mut hk, &clock, Modifiers::NONE);
+        assert_eq!(hk.handle(down(m)), Some(Action::Cancel(CancelReason::ComboKey)), "{m:?}");
+    }
+}
+
+#[test]
+fn trigger_pressed_with_other_modifier_never_starts() {
+    let (mut hk, clock) = machine();
+    let cmd = Modifiers { cmd: true, ..Modifiers::NONE };
+    assert_eq!(hk.handle(down(cmd)), None);
+    assert_eq!(hk.state(), State::Idle);
+    clock.advance(HOLD_THRESHOLD);
+    assert_eq!(hk.poll(), None);
+}
+
+// MARK: - Command mode (Ctrl)
+
+#[test]
+fn ctrl_held_with_trigger_is_command_mode() {
+    let (mut hk, clock) = machine();
+    start_holding(&mut hk, &clock, Modifiers::CTRL);
+    // 錄音中 Ctrl 仍按著不算組合鍵
+    assert_eq!(hk.handle(down(Modifiers::CTRL)), None);
+    clock.advance(MIN_RECORDING);
+    assert_eq!(hk.handle(up(Modifiers::CTRL)), Some(Action::HoldEnd { modifiers: Modifiers::CTRL }));
+}
+
+#[test]
+fn ctrl_added_while_pending_switches_to_command_mode() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(down(Modifiers::CTRL)), None);
+    assert_eq!(hk.state(), State::Pending);
+    clock.advance(HOLD_THRESHOLD);
+    assert_eq!(hk.poll(), Some(Action::HoldStart { modifiers: Modifiers::CTRL }));
+}
+
+#[test]
+fn ctrl_added_during_plain_hold_cancels() {
+    let (mut hk, clock) = machine();
+    start_holding(&mut hk, &clock, Modifiers::NONE);
+    assert_eq!(hk.handle(down(Modifiers::CTRL)), Some(Action::Cancel(CancelReason::ComboKey)));
+}
+
+#[test]
+fn ctrl_tap_is_command_tap_and_mode_resets() {
+    let (mut hk, clock) = machine();
+    hk.set_timing(Timing { double_tap: Duration::ZERO, ..Timing::default() });
+    hk.handle(down(Modifiers::CTRL));
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(up(Modifiers::CTRL)), Some(Action::Tap { modifiers: Modifiers::CTRL }));
+
+    clock.advance(ms(200));
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(up(Modifiers::NONE)), Some(Action::Tap { modifiers: Modifiers::NONE }));
+}
+
+// MARK: - Timers
+
+#[test]
+fn stale_timer_does_not_fire_for_new_press() {
+    let (mut hk, clock) = machine();
+    hk.set_timing(Timing { double_tap: Duration::ZERO, ..Timing::default() });
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(100));
+    hk.handle(up(Modifiers::NONE));
+    clock.advance(ms(100));
+    hk.handle(down(Modifiers::NONE));
+    // 第一次按壓的計時器在 300ms 到期，但新按壓只過了 100ms
+    clock.advance(ms(100));
+    assert_eq!(hk.poll(), None);
+    assert_eq!(hk.state(), State::Pending);
+    clock.advance(ms(200));
+    assert_eq!(hk.poll(), Some(Action::HoldStart { modifiers: Modifiers::NONE }));
+}
+
+// MARK: - Double-tap lock
+
+/// 點按一下，回傳時鐘已停在放開的時間點
+fn tap(hk: &mut HotkeyMachine<ManualClock>, clock: &ManualClock, m: Modifiers) -> Option<Action> {
+    hk.handle(down(m));
+    clock.advance(ms(100));
+    hk.handle(up(m))
+}
+
+#[test]
+fn double_tap_locks_and_single_tap_unlocks() {
+    let (mut hk, clock) = machine();
+    assert_eq!(tap(&mut hk, &clock, Modifiers::NONE), Some(Action::Tap { modifiers: Modifiers::NONE }));
+    clock.advance(DOUBLE_TAP);
+    assert_eq!(hk.handle(down(Modifiers::NONE)), Some(Action::Lock { modifiers: Modifiers::NONE }));
+    assert_eq!(hk.state(), State::Locked);
+    clock.advance(ms(100));
+    // 第二下放開不影響鎖定，也不啟動長按計時器
+    assert_eq!(hk.handle(up(Modifiers::NONE)), None);
+    assert_eq!(hk.deadline(), None);
+    clock.advance(ms(5_000));
+    assert_eq!(hk.poll(), None);
+    assert_eq!(hk.state(), State::Locked);
+
+    assert_eq!(hk.handle(down(Modifiers::NONE)), Some(Action::Unlock { modifiers: Modifiers::NONE }));
+    assert_eq!(hk.state(), State::Idle);
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(up(Modifiers::NONE)), None);
+    // 解鎖那一下不算點按，緊接的按壓不會再次鎖定
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(down(Modifiers::NONE)), None);
+    assert_eq!(hk.state(), State::Pending);
+}
+
+#[test]
+fn second_tap_after_window_is_a_plain_tap() {
+    let (mut hk, clock) = machine();
+    tap(&mut hk, &clock, Modifiers::NONE);
+    clock.advance(DOUBLE_TAP + ms(1));
+    assert_eq!(tap(&mut hk, &clock, Modifiers::NONE), Some(Action::Tap { modifiers: Modifiers::NONE }));
+}
+
+#[test]
+fn hold_does_not_arm_double_tap() {
+    let (mut hk, clock) = machine();
+    start_holding(&mut hk, &clock, Modifiers::NONE);
+    clock.advance(MIN_RECORDING);
+    assert_eq!(hk.handle(up(Modifiers::NONE)), Some(Action::HoldEnd { modifiers: Modifiers::NONE }));
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(down(Modifiers::NONE)), None);
+    assert_eq!(hk.state(), State::Pending);
+}
+
+#[test]
+fn escape_unlocks_and_typing_does_not() {
+    let (mut hk, clock) = machine();
+    tap(&mut hk, &clock, Modifiers::CTRL);
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(down(Modifiers::CTRL)), Some(Action::Lock { modifiers: Modifiers::CTRL }));
+    hk.handle(up(Modifiers::NONE));
+    assert_eq!(hk.handle(KeyEvent::KeyDown), None);
+    assert_eq!(hk.state(), State::Locked);
+    assert_eq!(hk.handle(KeyEvent::Escape), Some(Action::Unlock { modifiers: Modifiers::CTRL }));
+    assert_eq!(hk.state(), State::Idle);
+}
+
+#[test]
+fn escape_outside_lock_cancels_like_other_keys() {
+    let (mut hk, clock) = machine();
+    start_holding(&mut hk, &clock, Modifiers::NONE);
+    assert_eq!(hk.handle(KeyEvent::Escape), Some(Action::Cancel(CancelReason::ComboKey)));
+    assert_eq!(hk.handle(KeyEvent::Escape), None);
+}
+
+#[test]
+fn debounced_release_tap_arms_double_tap() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(10));
+    hk.handle(up(Modifiers::NONE));
+    clock.advance(HOLD_THRESHOLD);
+    assert_eq!(hk.poll(), Some(Action::Tap { modifiers: Modifiers::NONE }));
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(down(Modifiers::NONE)), Some(Action::Lock { modifiers: Modifiers::NONE }));
+}
+
+#[test]
+fn zero_window_disables_double_tap() {
+    let (mut hk, clock) = machine();
+    hk.set_timing(Timing { double_tap: Duration::ZERO, ..Timing::default() });
+    tap(&mut hk, &clock, Modifiers::NONE);
+    clock.advance(ms(100));
+    assert_eq!(tap(&mut hk, &clock, Modifiers::NONE), Some(Action::Tap { modifiers: Modifiers::NONE }));
+}
+
+// MARK: - Timing
+
+#[test]
+fn custom_timing_moves_thresholds() {
+    let (mut hk, clock) = machine();
+    hk.set_timing(Timing { hold_threshold: ms(150), min_recording: ms(200), debounce: ms(0), ..Timing::default() });
+    hk.handle(down(Modifiers::NONE));
+    assert_eq!(hk.until_deadline(), Some(ms(150)));
+    clock.advance(ms(150));
+    assert_eq!(hk.poll(), Some(Action::HoldStart { modifiers: Modifiers::NONE }));
+    clock.advance(ms(60));
+    assert_eq!(hk.handle(up(Modifiers::NONE)), Some(Action::HoldEnd { modifiers: Modifiers::NONE }));
+
+    // debounce 為 0：緊接的按壓也會進入 Pending
+    assert_eq!(hk.handle(down(Modifiers::NONE)), None);
+    assert_eq!(hk.state(), State::Pending);
+}
+
+#[test]
+fn longer_debounce_swallows_slower_bounce() {
+    let (mut hk, clock) = machine();
+    hk.set_timing(Timing { debounce: ms(120), ..Timing::default() });
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(ms(100));
+    assert_eq!(hk.handle(up(Modifiers::NONE)), None);
+    clock.advance(ms(200));
+    // 被濾掉的放開在 poll 時判定為點按
+    assert_eq!(hk.poll(), Some(Action::Tap { modifiers: Modifiers::NONE }));
+}
+
+#[test]
+fn timing_change_applies_to_press_in_progress() {
+    let (mut hk, clock) = machine();
+    hk.handle(down(Modifiers::NONE));
+    clock.advance(HOLD_THRESHOLD);
+    hk.set_timing(Timing { hold_threshold: ms(600), ..Timing::default() });
+    assert_eq!(hk.poll(), None);
+    assert_eq!(hk.until_deadline(), Some(ms(300)));
+    clock.advance(ms(300));
+    assert_eq!(hk.until_deadline(), Some(Duration::ZERO));
+    assert_eq!(hk.poll(), Some(Action::HoldStart { modifiers: Modifiers::NONE }));
+}
+
+#[test]
+fn hotkey_config_timing_bounds() {
+    assert_eq!(HotkeyConfig::default().timing(), Ok(Timing::default()));
+    assert_eq!(HotkeyConfig::parse("toggle").unwrap().timing(), Ok(Timing::default()));
+
+    let config = HotkeyConfig::parse(r#"{"holdThresholdMs": 200, "minRecordingMs": 0, "debounceMs": 80}"#).unwrap();
+    assert_eq!(
+        config.timing(),
+        Ok(Timing { hold_threshold: ms(200), min_recording: ms(0), debounce: ms(80), double_tap: DOUBLE_TAP })
+    );
+    assert_eq!(config.trigger(), Ok(Trigger::Fn));
+
+    for raw in [
+        r#"{"holdThresholdMs": 50}"#,
+        r#"{"holdThresholdMs": 1500}"#,
+        r#"{"minRecordingMs": 10000}"#,
+        r#"{"debounceMs": 500}"#,
+        r#"{"doubleTapMs": 2000}"#,
+    ] {
+        assert!(HotkeyConfig::parse(raw).unwrap().timing().is_err(), "{raw}");
+    }
+    assert!(HotkeyConfig::parse(r#"{"debounceMs": -1}"#).is_err());
+}
+
+// MARK: - Trigger spec
+
+#[test]
+fn parses_trigger_specs() {
+    let cmd_alt = Modifiers { alt: true, cmd: true, ..Modifiers::NONE };
+    let cases = [
+        ("fn", Trigger::Fn),
+        ("Globe", Trigger::Fn),
+        ("right_option", Trigger::Modifier(ModifierKey::RightOption)),
+        ("Right Alt", Trigger::Modifier(ModifierKey::RightOption)),
+        ("right-command", Trigger::Modifier(ModifierKey::RightCmd)),
+        ("F13", Trigger::Key { key: Key::Function(13), modifiers: Modifiers::NONE }),
+        ("cmd+option+K", Trigger::Key { key: Key::Char('k'), modifiers: cmd_alt }),
+        (" alt + cmd + space ", Trigger::Key { key: Key::Space, modifiers: cmd_alt }),
+    ];
+    for (spec, expected) in cases {
+        assert_eq!(spec.parse::<Trigger>(), Ok(expected), "{spec}");
+    }
+}
+
+#[test]
+fn trigger_display_round_trips() {
+    for spec in ["fn", "right_option", "left_cmd", "F13", "alt+shift+cmd+9", "cmd+F5"] {
+        let trigger: Trigger = spec.parse().unwrap();
+        assert_eq!(trigger.to_string().parse::<Trigger>(), Ok(trigger), "{spec}");
+    }
+    assert_eq!("cmd+shift+alt+k".parse::<Trigger>().unwrap().to_string(), "alt+shift+cmd+K");
+}
+
+#[test]
+fn rejects_impossible_triggers() {
+    for spec in [
+        "",
+        "cmd+",
+        "hyper",
+        "F21",
+        "F0",
+        "cmd+shift",           // 只有 modifier
+        "fn+F13",              // fn 只能單獨使用
+        "right_option+k",      // 單側 modifier 只能單獨使用
+        "cmd+a+b",             // 兩個按鍵
+        "cmd+cmd+k",           // 重複
+        "k",                   // 一般打字會觸發
+        "shift+space",
+        "cmd+space",           // Spotlight
+    ] {
+        assert!(spec.parse::<Trigger>().is_err(), "{spec:?} should be rejected");
+    }
+}
+
+#[test]
+fn hotkey_config_accepts_legacy_mode_string() {
+    assert_eq!(HotkeyConfig::parse("").unwrap(), HotkeyConfig::default());
+    let legacy = HotkeyConfig::parse("toggle").unwrap();
+    assert_eq!(legacy.mode, "toggle");
+    assert_eq!(legacy.trigger(), Ok(Trigger::Fn));
+
+    let config = HotkeyConfig::parse(r#"{"mode":"push_to_talk","triggerKey":"F13"}"#).unwrap();
+    assert_eq!(config.trigger(), Ok(Trigger::Key { key: Key::Function(13), modifiers: Modifiers::NONE }));
+
+    assert!(HotkeyConfig::parse("hold_forever").is_err());
+    assert!(HotkeyConfig::parse(r#"{"triggerKey":"ctrl+k"}"#).unwrap().trigger().is_err());
+}
+
+#[test]
+fn modifier_set_operations() {
+    let cmd = Modifiers { cmd: true, ..Modifiers::NONE };
+    let ctrl_cmd = Modifiers { ctrl: true, cmd: true, ..Modifiers::NONE };
+    assert!(ctrl_cmd.contains(cmd));
+    assert!(!cmd.contains(ctrl_cmd));
+    assert!(cmd.contains(Modifiers::NONE));
+    assert_eq!(ctrl_cmd.without(cmd), Modifiers::CTRL);
+}
+
+// MARK: - Bindings
+
+const SHIFT: Modifiers = Modifiers { shift: true, ..Modifiers::NONE };
+const CTRL_CMD: Modifiers = Modifiers { ctrl: true, cmd: true, ..Modifiers::NONE };
+
+fn bindings(specs: &[(HotkeyAction, &str)]) -> Result<Bindings, String> {
+    Bindings::from_specs(&specs.iter().map(|(action, spec)| (*action, spec.to_string())).collect())
+}
+
+#[test]
+fn default_bindings_match_built_in_shortcuts() {
+    let defaults = Bindings::default();
+    assert_eq!(defaults.validate(Trigger::Fn), Ok(()));
+    assert_eq!(defaults.get(HotkeyAction::Dictate), Some(Binding::Trigger(Modifiers::NONE)));
+    assert_eq!(defaults.trigger_action(Modifiers::CTRL), Some(HotkeyAction::CommandOnSelection));
+    assert_eq!(defaults.trigger_action(SHIFT), Some(HotkeyAction::DictateAndTranslate));
+    assert_eq!(defaults.trigger_action(Modifiers { alt: true, ..Modifiers::NONE }), None);
+    assert_eq!(defaults.layers(), vec![SHIFT, Modifiers::CTRL]);
+    assert_eq!(defaults.shortcut_action(Key::Char('v'), CTRL_CMD), Some((HotkeyAction::PasteLast, false)));
+    assert_eq!(defaults.get(HotkeyAction::Cancel), None);
+
+    // 空字串與缺少的 key 都用預設值
+    assert_eq!(Bindings::parse("").unwrap(), defaults);
+    assert_eq!(Bindings::parse("{}").unwrap(), defaults);
+    assert_eq!(defaults.to_specs()[&HotkeyAction::UndoLast], "ctrl+cmd+Z");
+    assert_eq!(defaults.to_specs()[&HotkeyAction::Cancel], "");
+}
+
+#[test]
+fn undo_shortcut_with_shift_restores_transcript() {
+    let defaults = Bindings::default();
+    assert_eq!(defaults.shortcut_action(Key::Char('z'), CTRL_CMD), Some((HotkeyAction::UndoLast, false)));
+    let shifted = Modifiers { shift: true, ..CTRL_CMD };
+    assert_eq!(defaults.shortcut_action(Key::Char('z'), shifted), Some((HotkeyAction::UndoLast, true)));
+    // 其他快捷鍵多按 Shift 不算
+    assert_eq!(defaults.shortcut_action(Key::Char('v'), shifted), None);
+}
+
+#[test]
+fn parses_custom_bindings() {
+    let raw = r#"{"dictate-and-translate": "", "command-on-selection": "Trigger + Alt", "cancel": "ctrl+cmd+x"}"#;
+    let custom = Bindings::parse(raw).unwrap();
+    assert_eq!(custom.validate(Trigger::Fn), Ok(()));
+    assert_eq!(custom.get(HotkeyAction::DictateAndTranslate), None);
+    assert_eq!(custom.trigger_action(Modifiers { alt: true, ..Modifiers::NONE }), Some(HotkeyAction::CommandOnSelection));
+    assert_eq!(custom.shortcut_action(Key::Char('x'), CTRL_CMD), Some((HotkeyAction::Cancel, false)));
+    assert_eq!(custom.to_specs()[&HotkeyAction::CommandOnSelection], "trigger+alt");
+    assert_eq!(Bindings::from_specs(&custom.to_specs()), Ok(custom));
+
+    assert!(Bindings::parse(r#"{"launch-rockets": "F5"}"#).is_err());
+    assert!(Bindings::parse("[]").is_err());
+}
+
+#[test]
+fn rejects_bindings_of_the_wrong_kind() {
+    for specs in [
+        [(HotkeyAction::Dictate, "ctrl+cmd+d")],      // 錄音動作只能綁觸發鍵
+        [(HotkeyAction::PasteLast, "trigger+cmd")],   // 其他動作只能綁快捷鍵
+        [(HotkeyAction::Cancel, "x")],                // 一般打字會觸發
+        [(HotkeyAction::Cancel, "shift+x")],
+        [(HotkeyAction::Cancel, "cmd+space")],
+        [(HotkeyAction::Cancel, "ctrl+cmd")],         // 沒有按鍵
+        [(HotkeyAction::Dictate, "trigger+k")],
+    ] {
+        assert!(bindings(&specs).is_err(), "{specs:?} should be rejected");
+    }
+    assert!(bindings(&[(HotkeyAction::Cancel, "F19")]).is_ok());
+}
+
+#[test]
+fn detects_binding_conflicts() {
+    let conflicts = [
+        // 兩個動作同一個綁定
+        bindings(&[(HotkeyAction::CommandOnSelection, "trigger+shift")]),
+        bindings(&[(HotkeyAction::Cancel, "ctrl+cmd+v")]),
+        // undo-last 的 Shift 變體也被佔用
+        bindings(&[(HotkeyAction::Cancel, "ctrl+shift+cmd+z")]),
+    ];
+    for result in conflicts {
+        assert!(result.unwrap().validate(Trigger::Fn).is_err());
+    }
+
+    // chord 觸發鍵本身的 modifier 不能再當 layer（原本 parse 時拒絕的 ctrl+F13）
+    let ctrl_f13: Trigger = "ctrl+F13".parse().unwrap();
+    assert!(Bindings::default().validate(ctrl_f13).is_err());
+    let without_command = bindings(&[(HotkeyAction::CommandOnSelection, "trigger+alt")]).unwrap();
+    assert_eq!(without_command.validate(ctrl_f13), Ok(()));
+
+    // 快捷鍵被 chord 觸發鍵攔截
+    let cmd_v: Trigger = "alt+cmd+v".parse().unwrap();
+    assert_eq!(Bindings::default().validate(cmd_v), Ok(()));
+    let paste = bindings(&[(HotkeyAction::PasteLast, "ctrl+alt+cmd+v")]).unwrap();
+    assert!(paste.validate(cmd_v).is_err());
+}
+
+#[test]
+fn machine_follows_binding_layers() {
+    let (mut hk, clock) = machine();
+    hk.set_layers(Bindings::default().layers());
+    hk.set_timing(Timing { double_tap: Duration::ZERO, ..Timing::default() });
+    start_holding(&mut hk, &clock, SHIFT);
+    clock.advance(MIN_RECORDING);
+    assert_eq!(hk.handle(up(SHIFT)), Some(Action::HoldEnd { modifiers: SHIFT }));
+
+    // 沒有綁定任何動作的 layer 視為組合鍵
+    clock.advance(ms(200));
+    hk.set_layers(vec![]);
+    hk.handle(down(Modifiers::NONE));
+    assert_eq!(hk.handle(down(Modifiers::CTRL)), None);
+    assert_eq!(hk.state(), State::Idle);
+}
@@ -0,0 +1,249 @@
+// llm.rs — 以本機 HTTP stub 取代 OpenAI 相容伺服器，驗證 LLM 潤飾階段
+
+use echotype_lib::llm::{self, LlmConfig};
+use echotype_lib::polish::{LlmCommand, LlmRewrite, LlmTranslate, Pipeline, PolishContext, Polisher, Punctuation};
+use echotype_lib::protocol::{AppContext, HelperSettings};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// 收到的請求（header 與 JSON body）
+#[derive(Debug, Clone, Default)]
+struct Received {
+    head: String,
+    body: Value,
+}
+
+struct Stub {
+    url: String,
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+/// 啟動一個回應固定內容的 stub；`delay` 模擬慢速模型
+async fn stub(status: u16, response: Value, delay: Duration) -> Stub {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/v1", listener.local_addr().unwrap());
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = received.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let (head, body) = loop {
+                let n = socket.read(&mut chunk).await.unwrap_or(0);
+                if n == 0 {
+                    return;
+                }
+                buf.extend_from_slice(&chunk[..n]);
+                let text = String::from_utf8_lossy(&buf).to_string();
+                let Some(split) = text.find("\r\n\r\n") else { continue };
+                let head = text[..split].to_string();
+                let length = head
+                    .lines()
+                    .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                    .unwrap_or(0);
+                if buf.len() >= split + 4 + length {
+                    break (head, serde_json::from_slice(&buf[split + 4..split + 4 + length]).unwrap_or(Value::Null));
+                }
+            };
+            log.lock().unwrap().push(Received { head, body });
+
+            tokio::time::sleep(delay).await;
+            let payload = response.to_string();
+            let reply = format!(
+                "HTTP/1.1 {} Stub\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status,
+                payload.len(),
+                payload
+            );
+            let _ = socket.write_all(reply.as_bytes()).await;
+        }
+    });
+    Stub { url, received }
+}
+
+fn completion(content: &str) -> Value {
+    json!({ "choices": [{ "message": { "role": "assistant", "content": content } }] })
+}
+
+fn config(url: &str, budget_ms: u64) -> LlmConfig {
+    LlmConfig {
+        endpoint: url.to_string(),
+        model: "local-test".into(),
+        api_key: String::new(),
+        budget: Duration::from_millis(budget_ms),
+    }
+}
+
+#[tokio::test]
+async fn rewrites_through_chat_completions() {
+    let server = stub(200, completion("  Let's meet tomorrow at 3 PM.  "), Duration::ZERO).await;
+    let mut config = config(&server.url, 2000);
+    config.api_key = "sk-test".into();
+
+    let ctx = PolishContext {
+        app: AppContext {
+            app_name: Some("Slack".into()),
+            web_domain: Some("app.slack.com".into()),
+            context_before: Some("Are you free this week?".into()),
+            ..Default::default()
+        },
+        output_language: "en-US".into(),
+        ..Default::default()
+    };
+    let polished = LlmRewrite::new(config).polish("lets meet tomorrow at three", &ctx).await.unwrap();
+    assert_eq!(polished, "Let's meet tomorrow at 3 PM.");
+
+    let received = server.received.lock().unwrap().clone();
+    assert_eq!(received.len(), 1);
+    assert!(received[0].head.starts_with("POST /v1/chat/completions "), "{}", received[0].head);
+    assert!(received[0].head.to_ascii_lowercase().contains("authorization: bearer sk-test"));
+    assert_eq!(received[0].body["model"], "local-test");
+    let system = received[0].body["messages"][0]["content"].as_str().unwrap();
+    assert!(system.contains("American English") && system.contains("輕鬆友善"), "{system}");
+    let user = received[0].body["messages"][1]["content"].as_str().unwrap();
+    assert!(user.contains("Slack / app.slack.com") && user.contains("Are you free this week?"), "{user}");
+    assert!(user.ends_with("lets meet tomorrow at three"), "{user}");
+}
+
+#[tokio::test]
+async fn local_server_without_key_sends_no_authorization() {
+    let server = stub(200, completion("好的。"), Duration::ZERO).await;
+    let polished = LlmRewrite::new(config(&server.url, 2000))
+        .polish("好的", &PolishContext::default())
+        .await
+        .unwrap();
+    assert_eq!(polished, "好的。");
+    let head = server.received.lock().unwrap()[0].head.to_ascii_lowercase();
+    assert!(!head.contains("authorization"), "{head}");
+}
+
+#[tokio::test]
+async fn slow_model_falls_back_within_budget() {
+    let server = stub(200, completion("too late"), Duration::from_secs(5)).await;
+    let pipeline = Pipeline::default()
+        .with_stage(Punctuation)
+        .with_stage(LlmRewrite::new(config(&server.url, 300)));
+
+    let started = Instant::now();
+    let polished = pipeline.run("see you soon", &PolishContext::default()).await;
+    assert_eq!(polished, "See you soon.");
+    assert!(started.elapsed() < Duration::from_secs(2), "{:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn server_errors_and_empty_completions_fall_back() {
+    let failing = stub(500, json!({ "error": "model not loaded" }), Duration::ZERO).await;
+    let err = LlmRewrite::new(config(&failing.url, 2000))
+        .polish("hello", &PolishContext::default())
+        .await
+        .unwrap_err();
+    assert!(err.contains("500") && err.contains("model not loaded"), "{err}");
+
+    let empty = stub(200, completion("   "), Duration::ZERO).await;
+    let pipeline = Pipeline::default().with_stage(LlmRewrite::new(config(&empty.url, 2000)));
+    assert_eq!(pipeline.run("hello", &PolishContext::default()).await, "hello");
+}
+
+#[tokio::test]
+async fn truncated_completions_fall_back() {
+    let truncated = json!({
+        "choices": [{ "message": { "role": "assistant", "content": "The meeting moved to" }, "finish_reason": "length" }]
+    });
+    let server = stub(200, truncated, Duration::ZERO).await;
+    let err = LlmRewrite::new(config(&server.url, 2000))
+        .polish("會議改到週五", &PolishContext::default())
+        .await
+        .unwrap_err();
+    assert!(err.contains("truncated"), "{err}");
+
+    let pipeline = Pipeline::default().with_stage(LlmTranslate::new(config(&server.url, 2000), "en-US"));
+    assert_eq!(pipeline.run("會議改到週五", &PolishContext::default()).await, "會議改到週五");
+
+    // 長篇聽寫的回應上限隨輸入放大
+    let long = "今天的會議討論了下一季的規劃。".repeat(100);
+    LlmRewrite::new(config(&server.url, 2000)).polish(&long, &PolishContext::default()).await.unwrap_err();
+    let received = server.received.lock().unwrap().clone();
+    let short_limit = received[0].body["max_tokens"].as_u64().unwrap();
+    let long_limit = received[2].body["max_tokens"].as_u64().unwrap();
+    assert!(long_limit > long.chars().count() as u64 && long_limit > short_limit, "{short_limit} / {long_limit}");
+}
+
+#[tokio::test]
+async fn command_rewrites_selection_with_spoken_instruction() {
+    let server = stub(200, completion("- 週一開會\n- 週三交報告"), Duration::ZERO).await;
+    let ctx = PolishContext {
+        app: AppContext { app_name: Some("Notes".into()), ..Default::default() },
+        output_language: "zh-TW".into(),
+        ..Default::default()
+    };
+    let rewritten = LlmCommand::new(config(&server.url, 2000), "改成條列")
+        .polish("週一開會，週三交報告", &ctx)
+        .await
+        .unwrap();
+    assert_eq!(rewritten, "- 週一開會\n- 週三交報告");
+
+    let body = server.received.lock().unwrap()[0].body.clone();
+    let user = body["messages"][1]["content"].as_str().unwrap();
+    assert!(user.contains("指示：改成條列"), "{user}");
+    assert!(user.ends_with("選取的文字：\n週一開會，週三交報告"), "{user}");
+
+    // 沒有辨識出指令時不送出請求
+    let err = LlmCommand::new(config(&server.url, 2000), "  ").polish("text", &ctx).await.unwrap_err();
+    assert!(err.contains("instruction"), "{err}");
+    assert_eq!(server.received.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn translation_keeps_source_text_for_history() {
+    let server = stub(200, completion("The meeting moved to Friday."), Duration::ZERO).await;
+    let ctx = PolishContext {
+        app: AppContext { app_name: Some("Slack".into()), ..Default::default() },
+        language: Some("zh".into()),
+        output_language: "zh-TW".into(),
+        ..Default::default()
+    };
+    let pipeline = Pipeline::for_mode("local", None).with_translation(config(&server.url, 2000), "en-US");
+    let (translated, source) = pipeline.run_capturing("會議改到週五", &ctx, "llm_translate").await;
+    assert_eq!(translated, "The meeting moved to Friday.");
+    assert_eq!(source.as_deref(), Some("會議改到週五。"));
+
+    let body = server.received.lock().unwrap()[0].body.clone();
+    let system = body["messages"][0]["content"].as_str().unwrap();
+    assert!(system.contains("目標語言：en-US") && system.contains("American English"), "{system}");
+    let user = body["messages"][1]["content"].as_str().unwrap();
+    assert!(user.ends_with("請翻譯：會議改到週五。"), "{user}");
+
+    // 翻譯失敗時輸出原文，也不回報翻譯前的文字
+    let failing = stub(500, json!({ "error": "overloaded" }), Duration::ZERO).await;
+    let pipeline = Pipeline::default().with_stage(LlmTranslate::new(config(&failing.url, 2000), "en-US"));
+    assert_eq!(pipeline.run_capturing("你好", &ctx, "llm_translate").await, ("你好".to_string(), None));
+}
+
+#[test]
+fn config_is_built_from_settings() {
+    let settings = HelperSettings {
+        api_base_url: "https://api.groq.com/openai/v1/chat/completions".into(),
+        ..Default::default()
+    };
+    let config = LlmConfig::from_settings(&settings, "gsk-1".into());
+    assert_eq!(config.model, "llama-3.1-8b-instant");
+    assert_eq!(config.completions_url(), "https://api.groq.com/openai/v1/chat/completions");
+    assert_eq!(config.budget, llm::DEFAULT_LATENCY_BUDGET);
+
+    let settings = HelperSettings {
+        api_base_url: "http://localhost:11434/v1/".into(),
+        polisher_model: "qwen2.5:3b".into(),
+        ..Default::default()
+    };
+    let config = LlmConfig::from_settings(&settings, String::new());
+    assert_eq!(config.model, "qwen2.5:3b");
+    assert_eq!(config.completions_url(), "http://localhost:11434/v1/chat/completions");
+
+    let config = LlmConfig::from_settings(&HelperSettings::default(), String::new());
+    assert_eq!(config.completions_url(), llm::DEFAULT_ENDPOINT);
+    assert_eq!(config.model, llm::DEFAULT_MODEL);
+}
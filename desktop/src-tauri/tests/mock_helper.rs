@@ -0,0 +1,788 @@
+// mock_helper.rs — 以 echotype-mock-helper 取代 Swift helper，驗證 bridge 與 Tauri 命令層
+//
+// bridge 的 helper 是全域 singleton，因此所有測試以 SERIAL 序列化執行；
+// 每個測試寫入自己的劇本並重置 helper，下一次呼叫便會以新劇本 spawn mock。
+
+use echotype_lib::dictionary::MatchKind;
+use echotype_lib::{bridge, commands, protocol, recorder, supervisor};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tauri::Listener;
+use tokio::sync::{Mutex, MutexGuard};
+
+static SERIAL: Mutex<()> = Mutex::const_new(());
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+struct Fixture {
+    dir: PathBuf,
+    _serial: MutexGuard<'static, ()>,
+}
+
+impl Fixture {
+    /// 寫入劇本、指向 mock helper，並關閉上一個測試留下的 helper
+    async fn new(mut script: Value) -> Fixture {
+        let serial = SERIAL.lock().await;
+        bridge::replay_session(None).await;  // 同時關閉上一個測試留下的 helper
+        recorder::stop();
+        supervisor::reset();
+        bridge::set_timeout_overrides(BTreeMap::new());
+
+        let dir = std::env::temp_dir().join(format!(
+            "echotype-mock-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        script["log"] = json!(dir.join("requests.jsonl"));
+        let script_path = dir.join("script.json");
+        std::fs::write(&script_path, script.to_string()).unwrap();
+
+        std::env::set_var("ECHOTYPE_HELPER_PATH", env!("CARGO_BIN_EXE_echotype-mock-helper"));
+        std::env::set_var("ECHOTYPE_MOCK_SCRIPT", &script_path);
+        // 預設不連線任何 socket helper（避免連到開發機上正在運行的 helper）
+        std::env::set_var("ECHOTYPE_HELPER_SOCKET", dir.join("helper.sock"));
+
+        Fixture { dir, _serial: serial }
+    }
+
+    /// mock 收到的所有請求（依收到順序）
+    fn requests(&self) -> Vec<Value> {
+        std::fs::read_to_string(self.dir.join("requests.jsonl"))
+            .unwrap_or_default()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    fn requests_for(&self, cmd: &str) -> Vec<Value> {
+        self.requests().into_iter().filter(|r| r["cmd"] == cmd).collect()
+    }
+
+    /// 等待 mock 記錄到 `count` 個 `cmd` 請求（cancel 等不等回應的請求用）
+    async fn wait_for_requests(&self, cmd: &str, count: usize) -> Vec<Value> {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        loop {
+            let requests = self.requests_for(cmd);
+            if requests.len() >= count || Instant::now() >= deadline {
+                return requests;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    fn spawns(&self) -> u32 {
+        std::fs::read_to_string(self.dir.join("script.spawns"))
+            .map(|s| s.trim().parse().unwrap())
+            .unwrap_or(0)
+    }
+}
+
+#[tokio::test]
+async fn stop_recording_returns_transcript_and_saves_history() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "result": { "transcript": "今天天氣很好", "language": "zh", "duration": 2.5, "asr_engine": "mock" }
+            }
+        }
+    }))
+    .await;
+
+    let result = commands::stop_recording().await.unwrap();
+    assert_eq!(result.transcript, "今天天氣很好");
+    assert_eq!(result.polished_text, "今天天氣很好");
+    assert_eq!(result.duration_seconds, 2.5);
+    assert_eq!(result.asr_engine, "mock");
+
+    let saved = fx.requests_for("save_history");
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0]["args"]["transcript"], "今天天氣很好");
+    assert_eq!(saved[0]["args"]["app_name"], "MockApp");
+}
+
+#[tokio::test]
+async fn requests_are_tagged_with_unique_ids() {
+    let fx = Fixture::new(json!({})).await;
+
+    commands::get_context().await.unwrap();
+    commands::get_microphones().await.unwrap();
+
+    let requests = fx.requests();
+    assert_eq!(requests[0]["cmd"], "hello");
+    let mut ids: Vec<u64> = requests.iter().map(|r| r["id"].as_u64().unwrap()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), requests.len());
+}
+
+#[tokio::test]
+async fn slow_command_does_not_block_others() {
+    let _fx = Fixture::new(json!({
+        "commands": { "stop_recording": { "delay_ms": 1500 } }
+    }))
+    .await;
+    // 先完成握手，避免計時包含 spawn
+    commands::get_context().await.unwrap();
+
+    let slow = tokio::spawn(commands::stop_recording());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let start = Instant::now();
+    commands::get_context().await.unwrap();
+    assert!(start.elapsed() < Duration::from_millis(1000));
+    assert!(!slow.is_finished());
+
+    slow.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn inject_failure_emits_fallback_event() {
+    let _fx = Fixture::new(json!({
+        "commands": { "inject_text": { "error": "AX injection failed" } }
+    }))
+    .await;
+
+    let app = tauri::test::mock_app();
+    let (tx, rx) = mpsc::channel();
+    app.listen_any("inject-failed", move |event| {
+        let _ = tx.send(event.payload().to_string());
+    });
+
+    let err = commands::inject_text(app.handle().clone(), "要貼上的文字".into(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(err, "AX injection failed");
+
+    let payload: Value = serde_json::from_str(&rx.recv_timeout(Duration::from_secs(2)).unwrap()).unwrap();
+    assert_eq!(payload["text"], "要貼上的文字");
+}
+
+#[tokio::test]
+async fn crashed_helper_is_restarted_on_next_call() {
+    let fx = Fixture::new(json!({
+        "commands": { "stop_recording": [{ "spawn": 1, "crash": true }] }
+    }))
+    .await;
+
+    let err = commands::stop_recording().await.unwrap_err();
+    assert!(err.contains("closed unexpectedly"), "{err}");
+
+    let result = commands::stop_recording().await.unwrap();
+    assert_eq!(result.transcript, "hello from the mock helper");
+    assert_eq!(fx.spawns(), 2);
+}
+
+#[tokio::test]
+async fn malformed_lines_are_skipped() {
+    let _fx = Fixture::new(json!({
+        "commands": { "get_context": { "malformed": true } }
+    }))
+    .await;
+
+    let ctx = commands::get_context().await.unwrap();
+    assert_eq!(ctx.bundle_id.as_deref(), Some("com.echotype.mock"));
+}
+
+#[tokio::test]
+async fn incompatible_protocol_is_rejected() {
+    let fx = Fixture::new(json!({ "protocol_version": 99, "helper_version": "stale" })).await;
+
+    let err = commands::get_context().await.unwrap_err();
+    assert!(err.contains("protocol v99"), "{err}");
+    assert!(fx.requests_for("get_context").is_empty());
+}
+
+#[tokio::test]
+async fn unsupported_command_fails_fast() {
+    let fx = Fixture::new(json!({ "unsupported": ["download_model"] })).await;
+
+    let start = Instant::now();
+    let err = bridge::call_helper(protocol::DownloadModel { model_type: "qwen3".into() })
+        .await
+        .unwrap_err();
+    assert!(err.contains("does not support command: download_model"), "{err}");
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(fx.requests_for("download_model").is_empty());
+}
+
+#[tokio::test]
+async fn timeout_abandons_request_without_killing_helper() {
+    let fx = Fixture::new(json!({
+        "commands": { "get_microphones": [{ "silent": true }, {}] }
+    }))
+    .await;
+    bridge::set_timeout_overrides(BTreeMap::from([("get_microphones".to_string(), 1)]));
+
+    let err = commands::get_microphones().await.unwrap_err();
+    assert!(err.contains("timeout after 1s"), "{err}");
+
+    // 同一個 helper 繼續服務，並收到針對逾時請求的 cancel
+    assert_eq!(commands::get_microphones().await.unwrap().len(), 1);
+    assert_eq!(fx.spawns(), 1);
+    let timed_out = fx.requests_for("get_microphones")[0]["id"].clone();
+    let cancels = fx.requests_for("cancel");
+    assert_eq!(cancels.len(), 1);
+    assert_eq!(cancels[0]["args"]["request_id"], timed_out);
+}
+
+#[tokio::test]
+async fn cancel_command_aborts_in_flight_request() {
+    let fx = Fixture::new(json!({
+        "commands": { "stop_recording": { "delay_ms": 3000 } }
+    }))
+    .await;
+    commands::get_context().await.unwrap();
+
+    let pending = tokio::spawn(commands::stop_recording());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let start = Instant::now();
+    let cancelled = commands::cancel_helper_command("stop_recording".into()).await.unwrap();
+    assert_eq!(cancelled, 1);
+    let err = pending.await.unwrap().unwrap_err();
+    assert!(err.contains("Cancelled"), "{err}");
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    assert_eq!(fx.spawns(), 1);
+    assert_eq!(fx.wait_for_requests("cancel", 1).await.len(), 1);
+}
+
+#[tokio::test]
+async fn repeated_crashes_stop_respawning_with_diagnosis() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": [
+                { "spawn": 1, "crash": true },
+                { "spawn": 2, "crash": true },
+                { "spawn": 3, "crash": true }
+            ]
+        }
+    }))
+    .await;
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        let err = commands::stop_recording().await.unwrap_err();
+        assert!(err.contains("closed unexpectedly"), "{err}");
+    }
+    // 第 2、3 次啟動前分別退避 0.5s 與 1s
+    assert!(start.elapsed() >= Duration::from_millis(1500));
+
+    // 達到上限後不再 spawn，錯誤附上 stderr 日誌尾端
+    let err = commands::stop_recording().await.unwrap_err();
+    assert!(err.contains("will not be restarted"), "{err}");
+    assert!(err.contains("[mock-helper] crashing on stop_recording"), "{err}");
+    assert_eq!(fx.spawns(), 3);
+
+    // 使用者手動重試後恢復
+    commands::restart_helper().await.unwrap();
+    let result = commands::stop_recording().await.unwrap();
+    assert_eq!(result.transcript, "hello from the mock helper");
+    assert_eq!(fx.spawns(), 4);
+}
+
+#[tokio::test]
+async fn connects_to_running_helper_over_socket() {
+    let fx = Fixture::new(json!({})).await;
+    let socket = fx.dir.join("helper.sock");
+    let mut server = std::process::Command::new(env!("CARGO_BIN_EXE_echotype-mock-helper"))
+        .arg("--socket")
+        .arg(&socket)
+        .spawn()
+        .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !socket.exists() {
+        assert!(Instant::now() < deadline, "mock helper did not create {}", socket.display());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let ctx = commands::get_context().await.unwrap();
+    assert_eq!(ctx.app_name.as_deref(), Some("MockApp"));
+    assert_eq!(fx.spawns(), 1);  // 只有獨立啟動的那一個
+
+    // 斷線不會結束獨立運行的 helper，下次呼叫重新連線
+    bridge::shutdown_helper().await;
+    commands::get_microphones().await.unwrap();
+    assert_eq!(fx.spawns(), 1);
+    assert_eq!(fx.requests_for("hello").len(), 2);
+
+    bridge::shutdown_helper().await;
+    server.kill().unwrap();
+    server.wait().unwrap();
+}
+
+#[tokio::test]
+async fn stale_socket_falls_back_to_spawning() {
+    let fx = Fixture::new(json!({})).await;
+    std::fs::write(fx.dir.join("helper.sock"), "").unwrap();
+
+    commands::get_context().await.unwrap();
+    assert_eq!(fx.spawns(), 1);
+}
+
+#[tokio::test]
+async fn incompatible_socket_helper_falls_back_to_spawning() {
+    let fx = Fixture::new(json!({})).await;
+    let socket = fx.dir.join("helper.sock");
+    let old_script = fx.dir.join("old-helper.json");
+    std::fs::write(&old_script, json!({ "protocol_version": 99 }).to_string()).unwrap();
+    let mut server = std::process::Command::new(env!("CARGO_BIN_EXE_echotype-mock-helper"))
+        .arg("--socket")
+        .arg(&socket)
+        .env("ECHOTYPE_MOCK_SCRIPT", &old_script)
+        .spawn()
+        .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !socket.exists() {
+        assert!(Instant::now() < deadline, "mock helper did not create {}", socket.display());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // socket 上的 helper 握手失敗，改用 spawn 的 helper
+    let ctx = commands::get_context().await.unwrap();
+    assert_eq!(ctx.app_name.as_deref(), Some("MockApp"));
+    assert_eq!(fx.spawns(), 1);
+    assert_eq!(fx.requests_for("get_context").len(), 1);
+
+    bridge::shutdown_helper().await;
+    server.kill().unwrap();
+    server.wait().unwrap();
+}
+
+#[tokio::test]
+async fn binary_payloads_round_trip_over_framed_connection() {
+    let fx = Fixture::new(json!({})).await;
+
+    let info = bridge::helper_info().await.unwrap();
+    assert_eq!(info.framing, protocol::Framing::LengthPrefixed);
+
+    let pcm = [0u8, 1, 2, 0xff, b'\n', 0];
+    let (result, payload) = bridge::call_helper_binary("get_context", json!({}), &pcm).await.unwrap();
+    assert_eq!(result["app_name"], "MockApp");
+    assert_eq!(payload, pcm);
+    assert_eq!(fx.requests_for("get_context")[0]["payload_len"], pcm.len());
+
+    // 一般命令在 framed 連線上照常運作
+    assert_eq!(commands::get_microphones().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn binary_events_reach_subscribers() {
+    let _fx = Fixture::new(json!({
+        "commands": {
+            "get_context": {
+                "events": [{ "event": "audio_chunk", "data": { "seq": 1 }, "payload": "pcm-bytes" }]
+            }
+        }
+    }))
+    .await;
+    let mut events = bridge::subscribe_binary_events();
+
+    commands::get_context().await.unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(2), events.recv()).await.unwrap().unwrap();
+    assert_eq!(event.event, "audio_chunk");
+    assert_eq!(event.data["seq"], 1);
+    assert_eq!(event.payload.as_slice(), b"pcm-bytes");
+}
+
+#[tokio::test]
+async fn legacy_helper_keeps_json_lines() {
+    let _fx = Fixture::new(json!({ "framing": "json_lines" })).await;
+
+    let info = bridge::helper_info().await.unwrap();
+    assert_eq!(info.framing, protocol::Framing::JsonLines);
+    commands::get_context().await.unwrap();
+
+    let err = bridge::call_helper_binary("get_context", json!({}), b"pcm").await.unwrap_err();
+    assert!(err.contains("does not support binary framing"), "{err}");
+}
+
+#[tokio::test]
+async fn recorded_session_replays_through_call_helper() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "events": [{ "event": "partial_transcript", "data": { "text": "今天" } }],
+                "result": { "transcript": "今天天氣很好", "language": "zh", "duration": 2.5, "asr_engine": "mock" }
+            }
+        }
+    }))
+    .await;
+    let session = fx.dir.join("session.jsonl");
+
+    recorder::start(&session, recorder::RecordOptions::default()).unwrap();
+    let recorded = commands::stop_recording().await.unwrap();
+    assert_eq!(recorder::stop(), Some(session.clone()));
+
+    // 重播不再經過 mock helper
+    bridge::replay_session(Some(session)).await;
+    let replayed = commands::stop_recording().await.unwrap();
+    assert_eq!(replayed.transcript, recorded.transcript);
+    assert_eq!(replayed.duration_seconds, 2.5);
+    assert_eq!(fx.spawns(), 1);
+
+    // session 用盡後明確失敗，而不是卡住
+    let err = commands::stop_recording().await.unwrap_err();
+    assert!(err.contains("no more recorded responses for stop_recording"), "{err}");
+}
+
+#[tokio::test]
+async fn recorder_redacts_user_text() {
+    let fx = Fixture::new(json!({
+        "commands": { "get_api_key": { "result": "sk-live-secret" } }
+    }))
+    .await;
+    let session = fx.dir.join("session.jsonl");
+
+    recorder::start(&session, recorder::RecordOptions { redact_text: true }).unwrap();
+    commands::inject_text(tauri::test::mock_app().handle().clone(), "我的密碼是一二三".into(), None)
+        .await
+        .unwrap();
+    bridge::call_helper(protocol::SetSetting { key: "apiKey".into(), value: "sk-secret".into() })
+        .await
+        .unwrap();
+    assert_eq!(bridge::call_helper(protocol::GetApiKey {}).await.unwrap(), "sk-live-secret");
+    recorder::stop();
+
+    let raw = std::fs::read_to_string(&session).unwrap();
+    assert!(!raw.contains("我的密碼"), "{raw}");
+    assert!(!raw.contains("sk-secret"), "{raw}");
+    assert!(!raw.contains("sk-live-secret"), "{raw}");
+    assert!(raw.contains("[redacted: 8 chars]"), "{raw}");
+    assert!(raw.contains(r#""cmd":"inject_text""#), "{raw}");
+}
+
+#[tokio::test]
+async fn recorder_redacts_snippets_and_clipboard() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            "get_snippets": { "result": [{ "trigger": "my address", "text": "1 Main St" }] },
+            "get_placeholder_values": { "result": { "date": "2026-01-01", "clipboard": "hunter2 password" } }
+        }
+    }))
+    .await;
+    let session = fx.dir.join("session.jsonl");
+
+    recorder::start(&session, recorder::RecordOptions { redact_text: true }).unwrap();
+    bridge::call_helper(protocol::GetSnippets {}).await.unwrap();
+    let values = bridge::call_helper(protocol::GetPlaceholderValues { clipboard: true }).await.unwrap();
+    assert_eq!(values.clipboard.as_deref(), Some("hunter2 password"));
+    recorder::stop();
+
+    let raw = std::fs::read_to_string(&session).unwrap();
+    assert!(!raw.contains("hunter2"), "{raw}");
+    assert!(!raw.contains("my address"), "{raw}");
+    assert!(!raw.contains("1 Main St"), "{raw}");
+    assert!(raw.contains(r#""clipboard":"[redacted: 16 chars]""#), "{raw}");
+    // 請求的 clipboard 旗標不是文字，原樣保留
+    assert!(raw.contains(r#""clipboard":true"#), "{raw}");
+}
+
+#[tokio::test]
+async fn recorder_redacts_dictionary_entries() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            // 舊版字串與新版物件混在一起
+            "get_dictionary": { "result": ["Acme Secret", { "term": "Project Falcon", "sounds_like": ["project falkon"], "case": "canonical" }] }
+        }
+    }))
+    .await;
+    let session = fx.dir.join("session.jsonl");
+
+    recorder::start(&session, recorder::RecordOptions { redact_text: true }).unwrap();
+    let entries = bridge::call_helper(protocol::GetDictionary {}).await.unwrap();
+    bridge::call_helper(protocol::SetDictionary { entries }).await.unwrap();
+    recorder::stop();
+
+    let raw = std::fs::read_to_string(&session).unwrap();
+    for secret in ["Acme", "Falcon", "falkon"] {
+        assert!(!raw.contains(secret), "{raw}");
+    }
+    assert!(raw.contains(r#""term":"[redacted: 14 chars]""#), "{raw}");
+    assert!(raw.contains(r#""case":"canonical""#), "{raw}");
+}
+
+#[tokio::test]
+async fn bug_report_session_reproduces_stop_recording() {
+    let fx = Fixture::new(json!({})).await;
+    let session = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/session-stop-recording.jsonl");
+    bridge::replay_session(Some(session)).await;
+
+    // 錄製時 get_context 沒有回應：重播時同樣逾時，stop_recording 仍要完成並存入歷史
+    bridge::set_timeout_overrides(BTreeMap::from([("get_context".to_string(), 1)]));
+    let result = commands::stop_recording().await.unwrap();
+    assert_eq!(result.transcript, "會議改到明天下午三點");
+    assert_eq!(result.asr_engine, "whisper_turbo");
+    assert_eq!(fx.spawns(), 0);
+}
+
+#[tokio::test]
+async fn stop_recording_polishes_with_local_pipeline() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "result": { "transcript": "um i pushed it to github", "language": "en", "duration": 1.0, "asr_engine": "mock" }
+            },
+            "get_settings": { "result": { "polisher_mode": "local", "output_language": "en-US" } },
+            "get_dictionary": { "result": ["GitHub"] }
+        }
+    }))
+    .await;
+
+    let result = commands::stop_recording().await.unwrap();
+    assert_eq!(result.transcript, "um i pushed it to github");
+    assert_eq!(result.polished_text, "I pushed it to GitHub.");
+    assert_eq!(result.replacements.len(), 1);
+    assert_eq!((result.replacements[0].original.as_str(), result.replacements[0].kind), ("github", MatchKind::Exact));
+
+    let saved = fx.requests_for("save_history");
+    assert_eq!(saved[0]["args"]["polished_text"], "I pushed it to GitHub.");
+}
+
+#[tokio::test]
+async fn spoken_formatting_applies_unless_app_opts_out() {
+    let _fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "result": { "transcript": "第一點，換行，第二點", "language": "zh", "duration": 1.0, "asr_engine": "mock" }
+            },
+            "get_context": [
+                { "result": { "app_name": "Notes", "bundle_id": "com.apple.Notes" } },
+                { "result": { "app_name": "Code", "bundle_id": "com.microsoft.VSCode" } }
+            ],
+            "get_settings": {
+                "result": { "polisher_mode": "local", "formatting_commands": "{\"enabled\": true, \"disabled_apps\": [\"com.microsoft.VSCode\"]}" }
+            }
+        }
+    }))
+    .await;
+
+    assert_eq!(commands::stop_recording().await.unwrap().polished_text, "第一點\n第二點。");
+    assert_eq!(commands::stop_recording().await.unwrap().polished_text, "第一點，換行，第二點。");
+}
+
+#[tokio::test]
+async fn stop_recording_expands_snippets() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "result": { "transcript": "sign off", "language": "en", "duration": 1.0, "asr_engine": "mock" }
+            },
+            "get_settings": { "result": { "polisher_mode": "local" } },
+            "get_snippets": [
+                { "result": [{ "trigger": "sign off", "text": "Best,\nAlex" }] },
+                { "result": [{ "trigger": "sign off", "text": "Best,\nAlex\n{date}" }] }
+            ]
+        }
+    }))
+    .await;
+
+    // 沒有片段用到佔位符時不向 helper 讀取剪貼簿或日期
+    assert_eq!(commands::stop_recording().await.unwrap().polished_text, "Best,\nAlex");
+    assert!(fx.requests_for("get_placeholder_values").is_empty());
+
+    assert_eq!(commands::stop_recording().await.unwrap().polished_text, "Best,\nAlex\n2026-01-01");
+    let requests = fx.requests_for("get_placeholder_values");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["args"]["clipboard"], false);
+}
+
+#[tokio::test]
+async fn translation_falls_back_to_source_when_llm_is_unreachable() {
+    let translation = json!({ "enabled": true, "default_target": "en-US" }).to_string();
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": [
+                { "result": { "transcript": "明天見", "language": "zh", "duration": 1.0, "asr_engine": "mock" } },
+                { "result": { "transcript": "see you tomorrow", "language": "en", "duration": 1.0, "asr_engine": "mock" } }
+            ],
+            "get_settings": {
+                "result": { "polisher_mode": "none", "api_base_url": "http://127.0.0.1:1/v1", "translation": translation }
+            }
+        }
+    }))
+    .await;
+
+    // none 模式也為翻譯取得 LLM 設定；翻譯失敗時注入原文，歷史不記錄譯文
+    let result = commands::stop_recording().await.unwrap();
+    assert_eq!(result.polished_text, "明天見");
+    assert_eq!(result.target_language, None);
+    assert_eq!(fx.requests_for("get_api_key").len(), 1);
+    let saved = fx.requests_for("save_history");
+    assert_eq!(saved[0]["args"]["polished_text"], "明天見");
+    assert!(saved[0]["args"]["translated_text"].is_null());
+
+    // 已經是目標語言時不翻譯
+    let result = commands::stop_recording().await.unwrap();
+    assert_eq!(result.polished_text, "see you tomorrow");
+    assert_eq!(fx.requests_for("get_api_key").len(), 1);
+}
+
+#[tokio::test]
+async fn translate_recording_translates_even_when_translation_is_disabled() {
+    let translation = json!({ "enabled": false, "default_target": "en-US" }).to_string();
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "result": { "transcript": "明天見", "language": "zh", "duration": 1.0, "asr_engine": "mock" }
+            },
+            "get_settings": {
+                "result": { "polisher_mode": "none", "api_base_url": "http://127.0.0.1:1/v1", "translation": translation }
+            }
+        }
+    }))
+    .await;
+
+    // 一般聽寫：翻譯聽寫關閉時不取得 LLM 設定
+    commands::stop_recording().await.unwrap();
+    assert!(fx.requests_for("get_api_key").is_empty());
+
+    // dictate-and-translate：照樣翻譯成目標語言（LLM 無法連線時保留原文）
+    let result = commands::stop_translate_recording().await.unwrap();
+    assert_eq!(result.polished_text, "明天見");
+    assert_eq!(fx.requests_for("get_api_key").len(), 1);
+}
+
+#[tokio::test]
+async fn translate_recording_fails_without_a_target() {
+    let translation = json!({ "enabled": true, "targets": { "com.tinyspeck.slackmacgap": "en-US" } }).to_string();
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "result": { "transcript": "明天見", "language": "zh", "duration": 1.0, "asr_engine": "mock" }
+            },
+            "get_context": { "result": { "bundle_id": "com.apple.Notes" } },
+            "get_settings": { "result": { "polisher_mode": "none", "translation": translation } }
+        }
+    }))
+    .await;
+
+    // 不悄悄退回一般聽寫
+    let err = commands::stop_translate_recording().await.unwrap_err();
+    assert!(err.contains("No translation target"), "{err}");
+    assert!(fx.requests_for("save_history").is_empty());
+}
+
+#[tokio::test]
+async fn stop_recording_applies_matching_profile() {
+    let profiles = json!([
+        { "name": "Terminal", "match": { "field": "bundle_id", "pattern": "com.apple.Terminal" }, "formatting_commands": true },
+        { "name": "Mock", "match": { "field": "app_name", "pattern": "mock*" }, "polisher_mode": "local", "injection": "paste" }
+    ])
+    .to_string();
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "result": { "transcript": "um see you soon", "language": "en", "duration": 1.0, "asr_engine": "mock" }
+            },
+            "get_settings": { "result": { "polisher_mode": "none", "profiles": profiles } }
+        }
+    }))
+    .await;
+
+    // 全域是 none，設定檔把 MockApp 改成 local 潤飾並指定注入方式
+    let result = commands::stop_recording().await.unwrap();
+    assert_eq!(result.polished_text, "See you soon.");
+    assert_eq!(result.profile.as_deref(), Some("Mock"));
+    assert_eq!(result.injection, protocol::InjectionMethod::Paste);
+    assert_eq!(fx.requests_for("get_dictionary").len(), 1);
+}
+
+#[tokio::test]
+async fn undo_removes_last_injection_and_can_restore_transcript() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "result": { "transcript": "um see you soon", "language": "en", "duration": 1.0, "asr_engine": "mock" }
+            },
+            "get_settings": { "result": { "polisher_mode": "local" } }
+        }
+    }))
+    .await;
+    let app = tauri::test::mock_app();
+
+    let result = commands::stop_recording().await.unwrap();
+    commands::inject_text(app.handle().clone(), result.polished_text.clone(), None).await.unwrap();
+    let restored = commands::undo_last_injection(Some(true)).await.unwrap();
+    assert_eq!(restored.as_deref(), Some("um see you soon"));
+    assert_eq!(fx.requests_for("delete_backward")[0]["args"]["text"], "See you soon.");
+    let injected = fx.requests_for("inject_text");
+    assert_eq!(injected[1]["args"], json!({ "text": "um see you soon", "method": "auto" }));
+
+    // 放回的原文也可以撤銷，之後就沒有可撤銷的注入
+    assert_eq!(commands::undo_last_injection(None).await.unwrap(), None);
+    assert_eq!(fx.requests_for("delete_backward")[1]["args"]["text"], "um see you soon");
+    assert!(commands::undo_last_injection(None).await.unwrap_err().contains("Nothing to undo"));
+}
+
+#[tokio::test]
+async fn undo_refuses_when_focus_or_text_changed() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            "get_context": [
+                { "result": { "bundle_id": "com.apple.Notes", "window_title": "Groceries" } },
+                { "result": { "bundle_id": "com.apple.mail", "window_title": "Inbox" } },
+                { "result": { "bundle_id": "com.apple.Notes", "window_title": "Groceries", "context_before": "milk, eggs" } },
+                { "result": { "bundle_id": "com.apple.Notes", "window_title": "Groceries", "context_before": "- milk, bread" } }
+            ]
+        }
+    }))
+    .await;
+    let app = tauri::test::mock_app();
+
+    commands::inject_text(app.handle().clone(), "milk, bread".into(), None).await.unwrap();
+    let err = commands::undo_last_injection(None).await.unwrap_err();
+    assert!(err.contains("focused field changed"), "{err}");
+    let err = commands::undo_last_injection(None).await.unwrap_err();
+    assert!(err.contains("no longer before the cursor"), "{err}");
+    assert!(fx.requests_for("delete_backward").is_empty());
+
+    // 回到原本的欄位後仍可撤銷；不是錄音結果的注入沒有原文可放回
+    assert_eq!(commands::undo_last_injection(Some(true)).await.unwrap(), None);
+    assert_eq!(fx.requests_for("delete_backward")[0]["args"]["text"], "milk, bread");
+}
+
+#[tokio::test]
+async fn undo_compares_only_the_visible_end_of_long_injections() {
+    let long = "word ".repeat(100);
+    let fx = Fixture::new(json!({
+        "commands": {
+            "get_context": [
+                { "result": { "bundle_id": "com.apple.Notes" } },
+                // helper 只回傳游標前的最後 200 字
+                { "result": { "bundle_id": "com.apple.Notes", "context_before": long[long.len() - 200..].replace("word", "edit") } },
+                { "result": { "bundle_id": "com.apple.Notes", "context_before": &long[long.len() - 200..] } }
+            ]
+        }
+    }))
+    .await;
+    let app = tauri::test::mock_app();
+
+    commands::inject_text(app.handle().clone(), long.clone(), None).await.unwrap();
+    let err = commands::undo_last_injection(None).await.unwrap_err();
+    assert!(err.contains("no longer before the cursor"), "{err}");
+    assert_eq!(commands::undo_last_injection(None).await.unwrap(), None);
+    assert_eq!(fx.requests_for("delete_backward")[0]["args"]["text"], long.as_str());
+}
+
+#[tokio::test]
+async fn command_mode_requires_a_selection() {
+    let fx = Fixture::new(json!({
+        "commands": {
+            "stop_recording": {
+                "result": { "transcript": "make this more formal", "language": "en", "duration": 1.0, "asr_engine": "mock" }
+            },
+            "get_context": { "result": { "app_name": "Mail", "selected_text": "  " } }
+        }
+    }))
+    .await;
+
+    let err = commands::stop_command_recording().await.unwrap_err();
+    assert!(err.contains("selected"), "{err}");
+    assert!(fx.requests_for("get_api_key").is_empty());
+    assert!(fx.requests_for("save_history").is_empty());
+}
@@ -0,0 +1,114 @@
+// polish.rs — 潤飾 pipeline 各階段的單獨測試（不需要 helper）
+
+use echotype_lib::llm::LlmConfig;
+use echotype_lib::polish::{
+    Cleanup, Dictionary, Pipeline, PolishContext, PolishFuture, Polisher, Punctuation,
+};
+
+async fn run(stage: impl Polisher, text: &str, ctx: &PolishContext) -> String {
+    stage.polish(text, ctx).await.unwrap()
+}
+
+#[tokio::test]
+async fn cleanup_stage_removes_fillers() {
+    let ctx = PolishContext::default();
+    let cases = [
+        ("um so I think uh we should go", "so I think we should go"),
+        ("Hmm, that works um.", "that works."),
+        ("嗯，今天天氣很好", "今天天氣很好"),
+        ("那個，我們明天開會", "我們明天開會"),
+        ("那個人很高", "那個人很高"),
+        ("然後我們就走了", "然後我們就走了"),
+        ("umbrella and error", "umbrella and error"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(run(Cleanup, input, &ctx).await, expected, "{input}");
+    }
+}
+
+#[tokio::test]
+async fn punctuation_and_capitalization_are_repaired() {
+    let ctx = PolishContext::default();
+    let cases = [
+        ("hello world", "Hello world."),
+        ("i think i'm late . see you", "I think I'm late. See you."),
+        ("is it ready ?", "Is it ready?"),
+        ("version 3.5 ships today", "Version 3.5 ships today."),
+        ("今天天氣很好", "今天天氣很好。"),
+        ("他說「好」", "他說「好」。"),
+        ("done!", "Done!"),
+        ("dear team,\nthanks", "Dear team,\nThanks."),
+        ("groceries\n- milk\n- eggs", "Groceries\n- Milk\n- Eggs"),
+        ("", ""),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(run(Punctuation, input, &ctx).await, expected, "{input}");
+    }
+}
+
+#[tokio::test]
+async fn dictionary_restores_term_casing() {
+    let ctx = PolishContext {
+        dictionary: vec!["GitHub".into(), "Git".into(), "EchoType".into(), "語音輸入".into()],
+        ..Default::default()
+    };
+    let cases = [
+        ("push it to github", "push it to GitHub"),
+        ("git and GITHUB", "Git and GitHub"),
+        ("digital gits", "digital gits"),
+        ("我用echotype打字", "我用EchoType打字"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(run(Dictionary, input, &ctx).await, expected, "{input}");
+    }
+}
+
+struct Failing;
+
+impl Polisher for Failing {
+    fn name(&self) -> &'static str {
+        "failing"
+    }
+
+    fn polish<'a>(&'a self, _text: &'a str, _ctx: &'a PolishContext) -> PolishFuture<'a> {
+        Box::pin(async { Err("backend unavailable".to_string()) })
+    }
+}
+
+#[tokio::test]
+async fn pipeline_is_chosen_by_polisher_mode() {
+    // none 與未知模式原文照錄
+    assert!(Pipeline::for_mode("none", None).stage_names().is_empty());
+    assert!(Pipeline::for_mode("", None).stage_names().is_empty());
+    assert!(Pipeline::for_mode("something-else", None).stage_names().is_empty());
+    assert_eq!(
+        Pipeline::for_mode("local", None).stage_names(),
+        ["cleanup", "spoken_commands", "punctuation", "dictionary", "snippets"]
+    );
+    // 片段在 LLM 之後展開，內容不會被改寫
+    let llm = LlmConfig::from_settings(&Default::default(), String::new());
+    assert_eq!(
+        Pipeline::for_mode("cloud", Some(llm)).stage_names(),
+        ["cleanup", "spoken_commands", "punctuation", "dictionary", "llm_rewrite", "snippets"]
+    );
+    // 翻譯聽寫取代 LLM 改寫，none 模式也會翻譯
+    let llm = LlmConfig::from_settings(&Default::default(), String::new());
+    assert_eq!(
+        Pipeline::for_mode("cloud", Some(llm.clone())).with_translation(llm.clone(), "en").stage_names(),
+        ["cleanup", "spoken_commands", "punctuation", "dictionary", "llm_translate", "snippets"]
+    );
+    assert_eq!(
+        Pipeline::for_mode("none", None).with_translation(llm, "en").stage_names(),
+        ["llm_translate"]
+    );
+}
+
+#[tokio::test]
+async fn failing_stage_keeps_previous_text() {
+    let pipeline = Pipeline::default()
+        .with_stage(Cleanup)
+        .with_stage(Failing)
+        .with_stage(Punctuation);
+    let polished = pipeline.run("uh hello there", &PolishContext::default()).await;
+    assert_eq!(polished, "Hello there.");
+}
@@ -0,0 +1,89 @@
+// profiles.rs — 設定檔的比對與覆寫
+
+use echotype_lib::formatting::FormattingSettings;
+use echotype_lib::profiles::{self, MatchField, PatternKind, Profile, ProfileMatch};
+use echotype_lib::protocol::{AppContext, HelperSettings, InjectionMethod};
+
+fn profile(name: &str, field: MatchField, pattern: &str, kind: PatternKind) -> Profile {
+    Profile {
+        name: name.into(),
+        rule: ProfileMatch { field, pattern: pattern.into(), kind },
+        ..Default::default()
+    }
+}
+
+fn ctx(bundle_id: &str, app_name: &str, web_domain: Option<&str>) -> AppContext {
+    AppContext {
+        bundle_id: Some(bundle_id.into()),
+        app_name: Some(app_name.into()),
+        web_domain: web_domain.map(Into::into),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn first_matching_profile_wins() {
+    let profiles = [
+        profile("Jira", MatchField::WebDomain, r"\.atlassian\.net$", PatternKind::Regex),
+        profile("Slack", MatchField::BundleId, "com.tinyspeck.*", PatternKind::Glob),
+        profile("Browsers", MatchField::AppName, "*chrome*", PatternKind::Glob),
+    ];
+    let name = |ctx: &AppContext| profiles::resolve(&profiles, ctx).map(|p| p.name.as_str());
+
+    assert_eq!(name(&ctx("com.tinyspeck.slackmacgap", "Slack", None)), Some("Slack"));
+    assert_eq!(name(&ctx("com.google.Chrome", "Google Chrome", Some("acme.atlassian.net"))), Some("Jira"));
+    assert_eq!(name(&ctx("com.google.Chrome", "Google Chrome", Some("github.com"))), Some("Browsers"));
+    // glob 比對整個值，`.` 照字面比對
+    assert_eq!(name(&ctx("comXtinyspeck.app", "Other", None)), None);
+    assert_eq!(name(&ctx("org.com.tinyspeck.app", "Other", None)), None);
+    assert_eq!(name(&AppContext::default()), None);
+}
+
+#[test]
+fn profile_overrides_settings() {
+    let raw = r#"[{
+        "name": "Mail",
+        "match": {"field": "bundle_id", "pattern": "com.apple.mail"},
+        "polisher_mode": "cloud",
+        "tone": " 正式專業 ",
+        "output_language": "en-US",
+        "formatting_commands": true,
+        "injection": "paste"
+    }]"#;
+    let profiles = profiles::parse(raw);
+    let profile = profiles::resolve(&profiles, &ctx("com.apple.Mail", "Mail", None)).unwrap();
+
+    let mut settings = HelperSettings { polisher_mode: "local".into(), output_language: "zh-TW".into(), ..Default::default() };
+    profile.apply(&mut settings);
+    assert_eq!((settings.polisher_mode.as_str(), settings.output_language.as_str()), ("cloud", "en-US"));
+    assert_eq!(profile.tone().as_deref(), Some("正式專業"));
+    assert_eq!(profile.injection, Some(InjectionMethod::Paste));
+
+    let mut formatting = FormattingSettings { enabled: false, disabled_apps: vec!["com.apple.mail".into()], ..Default::default() };
+    profile.apply_formatting(&mut formatting);
+    assert!(formatting.applies_to(Some("com.apple.mail")));
+
+    // 沒有填的欄位沿用全域設定
+    let mut settings = HelperSettings { polisher_mode: "local".into(), ..Default::default() };
+    Profile::default().apply(&mut settings);
+    assert_eq!(settings.polisher_mode, "local");
+    assert_eq!(Profile { tone: Some("  ".into()), ..Default::default() }.tone(), None);
+}
+
+#[test]
+fn invalid_profiles_are_rejected() {
+    let ok = profile("ok", MatchField::BundleId, "com.apple.*", PatternKind::Glob);
+    assert!(profiles::validate(std::slice::from_ref(&ok)).is_ok());
+
+    let err = profiles::validate(&[profile("bad", MatchField::WebDomain, "([a-z", PatternKind::Regex)]).unwrap_err();
+    assert!(err.contains("([a-z"), "{err}");
+    assert!(profiles::validate(&[profile("empty", MatchField::AppName, " ", PatternKind::Glob)]).is_err());
+    let unknown_mode = Profile { polisher_mode: Some("fancy".into()), ..ok };
+    assert!(profiles::validate(&[unknown_mode]).unwrap_err().contains("fancy"));
+
+    assert!(profiles::parse("").is_empty());
+    assert!(profiles::parse("{not json").is_empty());
+    // set_setting 儲存前用嚴格解析，格式錯誤不會悄悄變成沒有設定檔
+    assert_eq!(profiles::parse_strict("").unwrap(), []);
+    assert!(profiles::parse_strict("{not json").unwrap_err().contains("Invalid profiles setting"));
+}
@@ -0,0 +1,54 @@
+// snippets.rs — 語音片段展開的表格測試
+
+use echotype_lib::snippets::{expand, Placeholders, Snippet};
+
+#[test]
+fn triggers_expand_to_stored_text() {
+    let snippets = [
+        Snippet::new("my address", "1 Main St, Taipei"),
+        Snippet::new("address", "(wrong snippet)"),
+        Snippet::new("sign off", "Best,\nAlex"),
+        Snippet::new("我的信箱", "alex@example.com"),
+        Snippet::new("  ", "(empty trigger)"),
+    ];
+    let cases = [
+        ("Send it to my address, please.", "Send it to 1 Main St, Taipei, please."),
+        ("Send it to My Address.", "Send it to 1 Main St, Taipei."),
+        ("寄到我的信箱。", "寄到alex@example.com。"),
+        ("我的信箱是什麼？", "alex@example.com是什麼？"),
+        // 整段話只有觸發詞時不接上補的句號
+        ("Sign off.", "Best,\nAlex"),
+        ("Sign off!", "Best,\nAlex!"),
+        // 觸發詞以字詞為單位
+        ("the addresses are wrong", "the addresses are wrong"),
+        ("nothing to expand", "nothing to expand"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(expand(input, &snippets, &Placeholders::default()), expected, "{input}");
+    }
+}
+
+#[test]
+fn placeholders_are_filled_once() {
+    let snippets = [
+        Snippet::new("today", "{date}"),
+        Snippet::new("paste it", "{clipboard}"),
+        Snippet::new("quote it", "「{selected_text}」{unknown}"),
+    ];
+    let values = Placeholders {
+        date: Some("2026-10-18".into()),
+        clipboard: Some("{selected_text}".into()),
+        selected_text: Some("選取".into()),
+    };
+    let cases = [
+        ("Today", "2026-10-18"),
+        ("What is today?", "What is 2026-10-18?"),
+        ("paste it", "{selected_text}"),
+        ("quote it", "「選取」{unknown}"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(expand(input, &snippets, &values), expected, "{input}");
+    }
+    assert_eq!(expand("today", &snippets, &Placeholders::default()), "");
+    assert_eq!(expand("quote it", &snippets, &Placeholders::default()), "「」{unknown}");
+}
@@ -0,0 +1,62 @@
+// translation.rs — 翻譯聽寫目標語言的選擇
+
+use echotype_lib::protocol::AppContext;
+use echotype_lib::translation::TranslationSettings;
+
+fn settings() -> TranslationSettings {
+    TranslationSettings::parse(
+        r#"{
+            "enabled": true,
+            "default_target": "",
+            "targets": {
+                "com.tinyspeck.slackmacgap": "en-US",
+                "atlassian.net": "en-US",
+                "jira.acme.atlassian.net": "ja",
+                "mail.google.com": ""
+            }
+        }"#,
+    )
+}
+
+fn app(bundle_id: Option<&str>, web_domain: Option<&str>) -> AppContext {
+    AppContext {
+        bundle_id: bundle_id.map(Into::into),
+        web_domain: web_domain.map(Into::into),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn target_is_chosen_by_app_then_domain() {
+    let settings = settings();
+    assert_eq!(settings.target_for(&app(Some("com.tinyspeck.SlackMacGap"), None), None).as_deref(), Some("en-US"));
+    // 子網域也符合，較長的網域優先
+    assert_eq!(settings.target_for(&app(None, Some("acme.atlassian.net")), None).as_deref(), Some("en-US"));
+    assert_eq!(settings.target_for(&app(None, Some("JIRA.acme.atlassian.net")), None).as_deref(), Some("ja"));
+    assert_eq!(settings.target_for(&app(None, Some("notatlassian.net")), None), None);
+    // 空字串代表這個網域不翻譯
+    assert_eq!(settings.target_for(&app(None, Some("mail.google.com")), None), None);
+    assert_eq!(settings.target_for(&app(Some("com.apple.Notes"), None), None), None);
+
+    // 停用時仍可查詢設定的目標語言（翻譯聽寫綁定用）
+    let disabled = TranslationSettings { enabled: false, ..settings.clone() };
+    assert_eq!(disabled.configured_target(&app(None, Some("acme.atlassian.net"))).as_deref(), Some("en-US"));
+    assert_eq!(disabled.configured_target(&app(None, Some("mail.google.com"))), None);
+
+    let with_default = TranslationSettings { default_target: "en".into(), ..settings };
+    assert_eq!(with_default.target_for(&app(Some("com.apple.Notes"), None), None).as_deref(), Some("en"));
+}
+
+#[test]
+fn no_translation_when_disabled_or_already_in_target_language() {
+    let settings = settings();
+    let slack = app(Some("com.tinyspeck.slackmacgap"), None);
+    assert_eq!(settings.target_for(&slack, Some("zh")).as_deref(), Some("en-US"));
+    assert_eq!(settings.target_for(&slack, Some("en")), None);
+    assert_eq!(settings.target_for(&slack, Some("EN_GB")), None);
+
+    let disabled = TranslationSettings { enabled: false, ..settings };
+    assert_eq!(disabled.target_for(&slack, None), None);
+    assert_eq!(TranslationSettings::parse(""), TranslationSettings::default());
+    assert_eq!(TranslationSettings::parse("{not json"), TranslationSettings::default());
+}
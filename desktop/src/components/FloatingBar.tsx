@@ -9,13 +9,33 @@ import "./FloatingBar.css";
 
 type RecordingState = "idle" | "recording" | "processing" | "done" | "inject-failed";
 
+interface Replacement {
+  original: string;
+  replacement: string;
+  kind: "exact" | "alias" | "phonetic" | "fuzzy";
+}
+
 interface RecordingResult {
   transcript: string;
   polished_text: string;
   duration_seconds: number;
   asr_engine: string;
+  /** 詞典修正做了哪些替換 */
+  replacements: Replacement[];
+  /** 翻譯聽寫的目標語言；有值時 polished_text 是譯文 */
+  target_language: string | null;
+  /** 套用的設定檔與其指定的注入方式 */
+  profile: string | null;
+  injection: "auto" | "accessibility" | "paste";
 }
 
+/** 觸發鍵手勢綁定的錄音動作（hotkey-* 事件的 payload，見 hotkey.rs 的 HotkeyAction）與對應的停止命令 */
+const STOP_COMMANDS: Record<string, string> = {
+  dictate: "stop_recording",
+  "dictate-and-translate": "stop_translate_recording",
+  "command-on-selection": "stop_command_recording",
+};
+
 const appWindow = getCurrentWebviewWindow();
 
 /** 顯示浮動條並置中於螢幕底部偏上 */
@@ -53,15 +73,25 @@ export default function FloatingBar() {
   const timerRef = useRef<ReturnType<typeof setInterval> | null>(null);
   const waveRef = useRef<ReturnType<typeof setInterval> | null>(null);
   const stateRef = useRef<RecordingState>(state);
+  const levelRef = useRef(0); // helper 推送的即時音量（audio-level 事件）
+  // 本次錄音的動作；語音指令模式（預設觸發鍵 + Ctrl）口述的是改寫選取文字的指令
+  const actionRef = useRef("dictate");
+  const [commandMode, setCommandMode] = useState(false);
+  // 雙擊觸發鍵鎖定的免持錄音
+  const [locked, setLocked] = useState(false);
 
   // 同步 stateRef 與 state
   useEffect(() => { stateRef.current = state; }, [state]);
 
-  const handleStartRecording = useCallback(async () => {
+  const handleStartRecording = useCallback(async (action = "dictate") => {
     if (stateRef.current !== "idle") return;
+    actionRef.current = action;
+    setCommandMode(action === "command-on-selection");
+    setLocked(false);
     setState("recording");
     setDuration(0);
     setResult(null);
+    levelRef.current = 0;
 
     // 顯示浮動條
     await showFloatingBar();
@@ -76,13 +106,15 @@ export default function FloatingBar() {
       setDuration(d => d + 1);
     }, 1000);
 
-    // 波形動畫
+    // 波形動畫：高度跟隨 helper 推送的音量
     waveRef.current = setInterval(() => {
-      setWaveHeights(Array(12).fill(0).map(() => 4 + Math.random() * 20));
+      const level = Math.min(1, levelRef.current * 8);
+      setWaveHeights(Array(12).fill(0).map(() => 4 + level * (8 + Math.random() * 12)));
     }, 80);
   }, []);
 
   const handleCancelRecording = useCallback(() => {
+    setLocked(false);
     if (timerRef.current) clearInterval(timerRef.current);
     if (waveRef.current) clearInterval(waveRef.current);
     setWaveHeights(Array(12).fill(4));
@@ -97,6 +129,7 @@ export default function FloatingBar() {
 
   const handleStopRecording = useCallback(async () => {
     if (stateRef.current !== "recording") return;
+    setLocked(false);
     setState("processing");
 
     // 播放停止音效
@@ -107,13 +140,13 @@ export default function FloatingBar() {
     setWaveHeights(Array(12).fill(4));
 
     try {
-      const res = await invoke<RecordingResult>("stop_recording");
+      const res = await invoke<RecordingResult>(STOP_COMMANDS[actionRef.current] ?? "stop_recording");
       setResult(res);
       setState("done");
 
       // 自動注入文字
       try {
-        await invoke("inject_text", { text: res.polished_text });
+        await invoke("inject_text", { text: res.polished_text, method: res.injection });
         // 注入成功，2 秒後隱藏
         setTimeout(() => { setState("idle"); hideFloatingBar(); }, 2000);
       } catch (injectError) {
@@ -144,9 +177,9 @@ export default function FloatingBar() {
 
     // 長按模式：fn 按住 > 300ms → 開始錄音
     listeners.push(
-      listen("hotkey-pressed", () => {
+      listen<string>("hotkey-pressed", (e) => {
         if (stateRef.current === "idle") {
-          handleStartRecording();
+          handleStartRecording(e.payload);
         }
       })
     );
@@ -162,18 +195,45 @@ export default function FloatingBar() {
 
     // 點按模式：fn 短按 < 300ms → toggle 錄音
     listeners.push(
-      listen("hotkey-tap", () => {
+      listen<string>("hotkey-tap", (e) => {
+        if (stateRef.current === "idle") {
+          handleStartRecording(e.payload);
+        } else if (stateRef.current === "recording") {
+          handleStopRecording();
+        }
+      })
+    );
+
+    // 雙擊觸發鍵 → 鎖定錄音（第一下的點按可能已開始錄音）
+    listeners.push(
+      listen<string>("hotkey-locked", async (e) => {
         if (stateRef.current === "idle") {
-          handleStartRecording();
+          await handleStartRecording(e.payload);
+          setLocked(true);
         } else if (stateRef.current === "recording") {
+          setLocked(true);
+        }
+      })
+    );
+
+    // 鎖定中再按一次觸發鍵或 Esc → 停止錄音
+    listeners.push(
+      listen("hotkey-unlocked", () => {
+        setLocked(false);
+        if (stateRef.current === "recording") {
           handleStopRecording();
         }
       })
     );
 
-    // 錄音太短（300ms~500ms 之間放開）→ 取消，顯示提示
+    // 錄音太短（300ms~500ms 之間放開）或按了取消快捷鍵 → 取消，顯示提示
     listeners.push(
-      listen("hotkey-cancelled", () => {
+      listen<string>("hotkey-cancelled", (e) => {
+        // 取消快捷鍵在轉錄中也有效，同 Esc
+        if (e.payload === "shortcut" && stateRef.current === "processing") {
+          invoke("cancel_helper_command", { cmd: "stop_recording" }).catch(console.error);
+          return;
+        }
         if (stateRef.current === "recording") {
           handleCancelRecording();
           // H9 修復：添加音訊播放錯誤處理
@@ -185,7 +245,7 @@ export default function FloatingBar() {
       })
     );
 
-    // Ctrl+Cmd+V → 貼上最後一次轉錄
+    // paste-last 快捷鍵（預設 Ctrl+Cmd+V）→ 貼上最後一次轉錄
     listeners.push(
       listen("paste-last-transcript", async () => {
         if (stateRef.current !== "idle") return;
@@ -206,6 +266,22 @@ export default function FloatingBar() {
       })
     );
 
+    // undo-last 快捷鍵（預設 Ctrl+Cmd+Z）→ 撤銷最後一次注入；加 Shift 時改放回原始轉錄
+    listeners.push(
+      listen<string>("undo-last-injection", async (event) => {
+        if (stateRef.current !== "idle" && stateRef.current !== "done") return;
+        try {
+          await invoke("undo_last_injection", { restoreTranscript: event.payload === "restore-transcript" });
+        } catch (e) {
+          // 焦點已換欄位或沒有可撤銷的注入，播放提示音
+          console.error("Undo last injection failed:", e);
+          new Audio("/sounds/Basso.aiff").play().catch((err) => {
+            console.error("Failed to play error sound:", err);
+          });
+        }
+      })
+    );
+
     return () => {
       // 正確清理：等待所有 Promise resolve 後再調用 unlisten
       Promise.all(listeners).then((unlisteners) => {
@@ -215,6 +291,31 @@ export default function FloatingBar() {
   }, [handleStartRecording, handleStopRecording, handleCancelRecording]);
 
 
+  // helper 推送的即時事件：音量與部分轉錄
+  useEffect(() => {
+    const listeners = [
+      listen<{ level: number }>("audio-level", (e) => {
+        levelRef.current = e.payload.level;
+      }),
+    ];
+    return () => {
+      Promise.all(listeners).then((unlisteners) => {
+        unlisteners.forEach((unlisten) => unlisten());
+      });
+    };
+  }, []);
+
+  // Esc：放棄進行中的轉錄（只取消該請求，helper 不會被重啟）
+  useEffect(() => {
+    const onKeyDown = (e: KeyboardEvent) => {
+      if (e.key === "Escape" && stateRef.current === "processing") {
+        invoke("cancel_helper_command", { cmd: "stop_recording" }).catch(console.error);
+      }
+    };
+    window.addEventListener("keydown", onKeyDown);
+    return () => window.removeEventListener("keydown", onKeyDown);
+  }, []);
+
   // 監聽注入失敗事件
   useEffect(() => {
     const unlisten = listen<{ text: string }>("inject-failed", () => {
@@ -261,6 +362,9 @@ export default function FloatingBar() {
                 />
               ))}
             </div>
+            {locked && (
+              <span className="lock-badge" title="已鎖定錄音：再按一次觸發鍵或 Esc 停止">🔒</span>
+            )}
             <span className="duration">{formatDuration(duration)}</span>
             <button className="btn btn-ghost stop-btn" onClick={handleStopRecording}>
               停止
@@ -277,7 +381,7 @@ export default function FloatingBar() {
             exit={{ opacity: 0 }}
           >
             <div className="spinner" />
-            <span>AI 潤飾中…</span>
+            <span>{commandMode ? "AI 改寫選取文字…" : "AI 潤飾中…"}</span>
           </motion.div>
         )}
 
@@ -291,6 +395,19 @@ export default function FloatingBar() {
           >
             <span className="done-icon">✓</span>
             <span className="done-text">{truncateText(result.polished_text, 40)}</span>
+            {result.replacements.length > 0 && (
+              <span
+                className="replacements-badge"
+                title={result.replacements.map(r => `${r.original} → ${r.replacement}`).join("\n")}
+              >
+                詞典 {result.replacements.length}
+              </span>
+            )}
+            {result.target_language && (
+              <span className="replacements-badge" title={result.transcript}>
+                → {result.target_language}
+              </span>
+            )}
           </motion.div>
         )}
 
@@ -1,5 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useNavigate } from "react-router-dom";
 import ReactECharts from "echarts-for-react";
 import "./Hub.css";
@@ -8,6 +9,9 @@ interface HistoryEntry {
   id: string;
   transcript: string;
   polished_text: string | null;
+  /** 翻譯聽寫的譯文；此時 polished_text 是原文 */
+  translated_text: string | null;
+  target_language: string | null;
   app_name: string | null;
   window_title: string | null;
   web_url: string | null;
@@ -17,10 +21,18 @@ interface HistoryEntry {
   created_at: string;
 }
 
+interface HelperStatusEvent {
+  status: "starting" | "ready" | "crashed" | "degraded";
+  crashes: number;
+  message: string | null;
+}
+
 export default function Hub() {
   const [history, setHistory] = useState<HistoryEntry[]>([]);
   const [search, setSearch] = useState("");
   const [activeTab, setActiveTab] = useState<"history" | "stats">("history");
+  const [helperStatus, setHelperStatus] = useState<HelperStatusEvent | null>(null);
+  const [restarting, setRestarting] = useState(false);
   const navigate = useNavigate();
 
   useEffect(() => {
@@ -29,8 +41,29 @@ export default function Hub() {
       .catch(console.error);
   }, []);
 
+  // helper 連續崩潰停止重啟時顯示診斷資訊
+  useEffect(() => {
+    const unlisten = listen<HelperStatusEvent>("helper-status", (event) => {
+      setHelperStatus(event.payload);
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  const restartHelper = async () => {
+    setRestarting(true);
+    try {
+      await invoke("restart_helper");
+    } catch (e) {
+      console.error(e);
+    } finally {
+      setRestarting(false);
+    }
+  };
+
   const filtered = history.filter(e =>
-    e.transcript.includes(search) || (e.polished_text ?? "").includes(search)
+    e.transcript.includes(search) || (e.polished_text ?? "").includes(search) || (e.translated_text ?? "").includes(search)
   );
 
   // 按日期分組歷史記錄
@@ -95,7 +128,7 @@ export default function Hub() {
       });
 
       const wordCount = dayEntries.reduce((sum, e) => {
-        const text = e.polished_text ?? e.transcript;
+        const text = e.translated_text ?? e.polished_text ?? e.transcript;
         return sum + text.length;
       }, 0);
 
@@ -139,7 +172,7 @@ export default function Hub() {
   };
 
   // 計算統計數據
-  const totalChars = history.reduce((sum, e) => sum + (e.polished_text ?? e.transcript).length, 0);
+  const totalChars = history.reduce((sum, e) => sum + (e.translated_text ?? e.polished_text ?? e.transcript).length, 0);
   const totalEntries = history.length;
   // 假設平均打字速度 40 字/分鐘，語音輸入節省 70% 時間
   const savedMinutes = Math.round((totalChars / 40) * 0.7);
@@ -166,6 +199,17 @@ export default function Hub() {
 
       {/* 主內容 */}
       <main className="hub-main">
+        {helperStatus?.status === "degraded" && (
+          <div className="helper-banner">
+            <div className="helper-banner-header">
+              <span>⚠️ EchoType Helper 無法啟動，錄音與文字輸入暫時無法使用</span>
+              <button className="helper-banner-retry" onClick={restartHelper} disabled={restarting}>
+                {restarting ? "重新啟動中…" : "重新啟動 Helper"}
+              </button>
+            </div>
+            {helperStatus.message && <pre className="helper-banner-detail">{helperStatus.message}</pre>}
+          </div>
+        )}
         {activeTab === "history" && (
           <>
             <div className="hub-header">
@@ -188,7 +232,12 @@ export default function Hub() {
                     <div className="history-group-header">{group.label}</div>
                     {group.entries.map(entry => (
                       <div key={entry.id} className="history-item glass-card">
-                        <div className="history-text">{entry.polished_text ?? entry.transcript}</div>
+                        <div className="history-text">{entry.translated_text ?? entry.polished_text ?? entry.transcript}</div>
+                        {entry.translated_text && (
+                          <div className="history-original" title={`翻譯為 ${entry.target_language ?? ""}`}>
+                            原文：{entry.polished_text ?? entry.transcript}
+                          </div>
+                        )}
                         <div className="history-meta">
                           <span>{entry.app_name ?? "—"}</span>
                           {entry.window_title && (
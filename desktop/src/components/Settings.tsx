@@ -3,6 +3,7 @@ import { useNavigate } from "react-router-dom";
 import { invoke } from "@tauri-apps/api/core";
 import { usePermissions, openSystemPreferences } from "../lib/permissions";
 import { SettingsKeys } from "../lib/settings"; // L7: 類型安全的設定鍵名
+import { DEFAULT_HOTKEY_BINDINGS, DEFAULT_HOTKEY_CONFIG, HOTKEY_ACTIONS, HOTKEY_TIMING_FIELDS, TRIGGER_KEY_PRESETS, parseHotkeyConfig, type HotkeyBindings, type HotkeyConfig, type HotkeyMode } from "../lib/hotkey-config";
 
 interface AudioDevice {
   id: string;
@@ -62,6 +63,97 @@ function PermissionRow({
   );
 }
 
+// MARK: - 個人詞典
+
+/** 與 dictionary.rs 的 DictionaryEntry 一致 */
+interface DictionaryEntry {
+  term: string;
+  sounds_like: string[];
+  case: "canonical" | "sensitive";
+}
+
+/** 每行一個詞；「詞 = 別名1, 別名2」指定 ASR 常見的誤辨識寫法 */
+function formatDictionary(entries: DictionaryEntry[]): string {
+  return entries
+    .map(e => e.sounds_like.length ? `${e.term} = ${e.sounds_like.join(", ")}` : e.term)
+    .join('\n');
+}
+
+/** 解析文字框內容；沿用既有詞條的大小寫規則 */
+function parseDictionary(text: string, previous: DictionaryEntry[]): DictionaryEntry[] {
+  return text.split('\n').map(line => line.trim()).filter(Boolean).map(line => {
+    const [term, aliases = ""] = line.split('=', 2).map(part => part.trim());
+    return {
+      term,
+      sounds_like: aliases.split(/[,，]/).map(a => a.trim()).filter(Boolean),
+      case: previous.find(e => e.term === term)?.case ?? "canonical",
+    };
+  }).filter(e => e.term);
+}
+
+// MARK: - 語音片段
+
+/** 與 snippets.rs 的 Snippet 一致 */
+interface Snippet {
+  trigger: string;
+  text: string;
+}
+
+// MARK: - 口述格式指令
+
+interface FormattingCommands {
+  enabled: boolean;
+  languages: string[];
+  disabled_apps: string[];
+}
+
+/** 與 formatting.rs 的 FormattingSettings::default 一致 */
+const DEFAULT_FORMATTING: FormattingCommands = {
+  enabled: false,
+  languages: ["en", "zh", "ja"],
+  disabled_apps: ["com.apple.Terminal", "com.googlecode.iterm2", "dev.warp.Warp-Stable"],
+};
+
+// MARK: - 翻譯聽寫
+
+/** 與 translation.rs 的 TranslationSettings 一致 */
+interface Translation {
+  enabled: boolean;
+  default_target: string;
+  targets: Record<string, string>;
+}
+
+const DEFAULT_TRANSLATION: Translation = { enabled: false, default_target: "", targets: {} };
+
+/** 每行一個「bundle id 或網域 = 目標語言」 */
+function formatTargets(targets: Record<string, string>): string {
+  return Object.entries(targets).map(([key, lang]) => `${key} = ${lang}`).join('\n');
+}
+
+function parseTargets(text: string): Record<string, string> {
+  const targets: Record<string, string> = {};
+  for (const line of text.split('\n')) {
+    const [key, lang = ""] = line.split('=').map(part => part.trim());
+    if (key) targets[key] = lang;
+  }
+  return targets;
+}
+
+// MARK: - App 設定檔
+
+/** 與 profiles.rs 的 Profile 一致；未填的欄位沿用全域設定 */
+interface Profile {
+  name: string;
+  match: { field: "bundle_id" | "app_name" | "web_domain"; pattern: string; kind: "glob" | "regex" };
+  polisher_mode?: string;
+  tone?: string;
+  output_language?: string;
+  formatting_commands?: boolean;
+  injection?: "auto" | "accessibility" | "paste";
+}
+
+const NEW_PROFILE: Profile = { name: "", match: { field: "bundle_id", pattern: "", kind: "glob" } };
+
 // MARK: - Settings 主元件
 
 export default function Settings() {
@@ -75,8 +167,18 @@ export default function Settings() {
   const [historyRetention, setHistoryRetention] = useState("30");
   const [apiKey, setApiKey] = useState("");
   const [apiBaseUrl, setApiBaseUrl] = useState("https://api.groq.com/openai/v1/chat/completions");
-  const [hotkeyMode, setHotkeyMode] = useState("push_to_talk");
+  const [polisherModel, setPolisherModel] = useState("");
+  const [hotkey, setHotkey] = useState<HotkeyConfig>(DEFAULT_HOTKEY_CONFIG);
+  const [customTrigger, setCustomTrigger] = useState("");
+  const [bindings, setBindings] = useState<HotkeyBindings>(DEFAULT_HOTKEY_BINDINGS);
   const [dictionary, setDictionary] = useState("");
+  const [dictionaryEntries, setDictionaryEntries] = useState<DictionaryEntry[]>([]);
+  const [snippets, setSnippets] = useState<Snippet[]>([]);
+  const [formatting, setFormatting] = useState<FormattingCommands>(DEFAULT_FORMATTING);
+  const [formattingApps, setFormattingApps] = useState(DEFAULT_FORMATTING.disabled_apps.join('\n'));
+  const [translation, setTranslation] = useState<Translation>(DEFAULT_TRANSLATION);
+  const [translationTargets, setTranslationTargets] = useState("");
+  const [profiles, setProfiles] = useState<Profile[]>([]);
   const navigate = useNavigate();
 
   // v0.3.0: 權限狀態（每 3 秒自動刷新）
@@ -94,14 +196,49 @@ export default function Settings() {
       if (s.history_retention_days) setHistoryRetention(s.history_retention_days);
       if (s.api_key) setApiKey(s.api_key);
       if (s.api_base_url) setApiBaseUrl(s.api_base_url);
-      if (s.hotkey) setHotkeyMode(s.hotkey);
+      if (s.polisher_model) setPolisherModel(s.polisher_model);
+      if (s.hotkey) {
+        const parsed = parseHotkeyConfig(s.hotkey);
+        setHotkey(parsed);
+        if (!TRIGGER_KEY_PRESETS.some(p => p.value === parsed.triggerKey)) setCustomTrigger(parsed.triggerKey);
+      }
+      if (s.formatting_commands) {
+        try {
+          const parsed = { ...DEFAULT_FORMATTING, ...JSON.parse(s.formatting_commands) };
+          setFormatting(parsed);
+          setFormattingApps(parsed.disabled_apps.join('\n'));
+        } catch (e) {
+          console.error("Invalid formattingCommands setting:", e);
+        }
+      }
+      if (s.translation) {
+        try {
+          const parsed = { ...DEFAULT_TRANSLATION, ...JSON.parse(s.translation) };
+          setTranslation(parsed);
+          setTranslationTargets(formatTargets(parsed.targets));
+        } catch (e) {
+          console.error("Invalid translation setting:", e);
+        }
+      }
     });
 
     // 載入麥克風列表
     invoke<AudioDevice[]>("get_microphones").then(setMics).catch(console.error);
 
     // 載入個人詞典
-    invoke<string[]>("get_dictionary").then(words => setDictionary(words.join('\n'))).catch(console.error);
+    invoke<DictionaryEntry[]>("get_dictionary").then(entries => {
+      setDictionaryEntries(entries);
+      setDictionary(formatDictionary(entries));
+    }).catch(console.error);
+
+    // 載入語音片段
+    invoke<Snippet[]>("get_snippets").then(setSnippets).catch(console.error);
+
+    // 載入 App 設定檔
+    invoke<Profile[]>("get_profiles").then(setProfiles).catch(console.error);
+
+    // 載入快捷鍵動作綁定
+    invoke<HotkeyBindings>("get_hotkey_bindings").then(setBindings).catch(console.error);
   }, []);
 
   const save = (key: string, value: string) => {
@@ -114,6 +251,68 @@ export default function Settings() {
       });
   };
 
+  // 觸發鍵由 Rust 端驗證，無效時保存失敗並提示
+  const saveHotkey = (next: HotkeyConfig) => {
+    invoke("set_setting", { key: "hotkey", value: JSON.stringify(next) })
+      .then(() => setHotkey(next))
+      .catch((error) => {
+        console.error("Failed to save hotkey:", error);
+        alert(`快捷鍵設定無效: ${error}`);
+      });
+  };
+
+  // 綁定衝突（與觸發鍵或彼此）由 Rust 端檢查，衝突時保存失敗並提示
+  const saveBindings = (next: HotkeyBindings) => {
+    invoke("set_hotkey_bindings", { bindings: next }).catch((error) => {
+      console.error("Failed to save hotkey bindings:", error);
+      alert(`快捷鍵綁定無效: ${error}`);
+    });
+  };
+
+  const saveSnippets = (next: Snippet[]) => {
+    const valid = next.filter(sn => sn.trigger.trim());
+    invoke("set_snippets", { snippets: valid }).catch((e) => {
+      console.error("Snippets save failed:", e);
+      alert(`片段儲存失敗: ${e}`);
+    });
+  };
+
+  const updateSnippet = (index: number, patch: Partial<Snippet>) => {
+    setSnippets(prev => prev.map((sn, i) => i === index ? { ...sn, ...patch } : sn));
+  };
+
+  const saveProfiles = (next: Profile[]) => {
+    const valid = next.filter(p => p.match.pattern.trim());
+    invoke("set_profiles", { profiles: valid }).catch((e) => {
+      console.error("Profiles save failed:", e);
+      alert(`設定檔儲存失敗: ${e}`);
+    });
+  };
+
+  /** 空字串代表沿用全域設定，存成未設定 */
+  const updateProfile = (index: number, patch: Partial<Profile>, persist = false) => {
+    const next = profiles.map((p, i) => {
+      if (i !== index) return p;
+      const merged: Profile = { ...p, ...patch };
+      for (const key of ["polisher_mode", "tone", "output_language", "injection"] as const) {
+        if (merged[key] === "") delete merged[key];
+      }
+      return merged;
+    });
+    setProfiles(next);
+    if (persist) saveProfiles(next);
+  };
+
+  const saveFormatting = (next: FormattingCommands) => {
+    setFormatting(next);
+    save("formattingCommands", JSON.stringify(next));
+  };
+
+  const saveTranslation = (next: Translation) => {
+    setTranslation(next);
+    save("translation", JSON.stringify(next));
+  };
+
   // M7 修復：使用防抖版本的 save，避免頻繁保存
   const debouncedSave = useDebounce(save, 500);
 
@@ -207,6 +406,7 @@ export default function Settings() {
                 style={{ ...selectStyle, marginBottom: 16 }}
               >
                 <option value="cloud">雲端 AI（推薦：Groq 免費高速）</option>
+                <option value="local">本地規則（離線：去除贅字、標點、詞典）</option>
                 <option value="none">只轉錄，不潤飾</option>
               </select>
 
@@ -225,15 +425,30 @@ export default function Settings() {
                   </div>
                   <div>
                     <label style={{ fontSize: 14, marginBottom: 8, display: "block", color: "var(--color-text-muted)" }}>API Base URL</label>
-                    <select
+                    {/* 任何 OpenAI 相容的 endpoint 都可以，包含本機的 llama.cpp / Ollama */}
+                    <input
+                      list="polisher-endpoints"
                       value={apiBaseUrl}
-                      onChange={e => { setApiBaseUrl(e.target.value); save("apiBaseUrl", e.target.value); }}
+                      onChange={e => setApiBaseUrl(e.target.value)}
+                      onBlur={() => save("apiBaseUrl", apiBaseUrl)}
                       style={inputStyle}
-                    >
+                    />
+                    <datalist id="polisher-endpoints">
                       <option value="https://api.groq.com/openai/v1/chat/completions">Groq（推薦：免費、極速）</option>
                       <option value="https://api.openai.com/v1/chat/completions">OpenAI（GPT-4o mini）</option>
-                      <option value="https://api.anthropic.com/v1/messages">Anthropic（Claude 3.5 Haiku）</option>
-                    </select>
+                      <option value="http://localhost:11434/v1">Ollama（本機）</option>
+                      <option value="http://localhost:8080/v1">llama.cpp server（本機）</option>
+                    </datalist>
+                  </div>
+                  <div>
+                    <label style={{ fontSize: 14, marginBottom: 8, display: "block", color: "var(--color-text-muted)" }}>模型</label>
+                    <input
+                      value={polisherModel}
+                      onChange={e => setPolisherModel(e.target.value)}
+                      onBlur={() => save("polisherModel", polisherModel)}
+                      placeholder="留空使用預設（例如 gpt-4o-mini）"
+                      style={inputStyle}
+                    />
                   </div>
                   <div style={{ padding: 12, background: "var(--color-accent-bg)", border: "1px solid rgba(0,122,255,0.15)", borderRadius: 6, fontSize: 13, color: "var(--color-accent)", lineHeight: 1.6 }}>
                     推薦使用 Groq：免費額度大、速度極快（&gt;300 tokens/s），相容 OpenAI 格式。<br/>
@@ -321,19 +536,21 @@ export default function Settings() {
             <h2 style={sectionHeaderStyle}>個人詞典</h2>
             <div className="glass-card" style={{ padding: 20 }}>
               <p style={{ fontSize: 13, color: "var(--color-text-muted)", marginBottom: 12, lineHeight: 1.5 }}>
-                加入常用的專有名詞、人名、技術術語，提升語音辨識準確度。每行一個詞彙。
+                加入常用的專有名詞、人名、技術術語，提升語音辨識準確度，並自動修正發音相近的誤辨識。每行一個詞彙；
+                可用「詞彙 = 誤辨識1, 誤辨識2」指定常被聽錯的寫法。
               </p>
               <textarea
                 value={dictionary}
                 onChange={e => setDictionary(e.target.value)}
                 onBlur={() => {
-                  const words = dictionary.split('\n').map(w => w.trim()).filter(Boolean);
-                  invoke("set_dictionary", { words }).catch((e) => {
+                  const entries = parseDictionary(dictionary, dictionaryEntries);
+                  setDictionaryEntries(entries);
+                  invoke("set_dictionary", { entries }).catch((e) => {
                     console.error("Dictionary save failed:", e);
                     alert("詞典儲存失敗，請稍後再試");
                   });
                 }}
-                placeholder={"例如：\nEchoType\nWhisper Turbo\n台積電"}
+                placeholder={"例如：\nEchoType\nWhisper Turbo\n台積電 = 台雞電\nKubernetes = cooper netties"}
                 rows={6}
                 style={{
                   background: "var(--color-surface-2)",
@@ -350,27 +567,317 @@ export default function Settings() {
             </div>
           </section>
 
+          {/* 語音片段 */}
+          <section>
+            <h2 style={sectionHeaderStyle}>語音片段</h2>
+            <div className="glass-card" style={{ padding: 20 }}>
+              <p style={{ fontSize: 13, color: "var(--color-text-muted)", marginBottom: 12, lineHeight: 1.5 }}>
+                說出觸發詞即插入儲存的文字（地址、署名、常用連結）。可使用 {"{date}"}、{"{clipboard}"}、{"{selected_text}"}。
+              </p>
+              {snippets.map((sn, i) => (
+                <div key={i} style={{ display: "flex", gap: 8, marginBottom: 12, alignItems: "flex-start" }}>
+                  <input
+                    value={sn.trigger}
+                    onChange={e => updateSnippet(i, { trigger: e.target.value })}
+                    onBlur={() => saveSnippets(snippets)}
+                    placeholder="觸發詞"
+                    style={{ ...inputStyle, width: 140, flexShrink: 0 }}
+                  />
+                  <textarea
+                    value={sn.text}
+                    onChange={e => updateSnippet(i, { text: e.target.value })}
+                    onBlur={() => saveSnippets(snippets)}
+                    placeholder="插入的文字"
+                    rows={2}
+                    style={{ ...inputStyle, resize: "vertical" }}
+                  />
+                  <button
+                    className="btn btn-ghost"
+                    onClick={() => {
+                      const next = snippets.filter((_, j) => j !== i);
+                      setSnippets(next);
+                      saveSnippets(next);
+                    }}
+                    style={{ padding: 8 }}
+                  >
+                    ✕
+                  </button>
+                </div>
+              ))}
+              <button className="btn btn-ghost" onClick={() => setSnippets([...snippets, { trigger: "", text: "" }])}>
+                ＋ 新增片段
+              </button>
+            </div>
+          </section>
+
+          {/* 口述格式指令 */}
+          <section>
+            <h2 style={sectionHeaderStyle}>語音格式指令</h2>
+            <div className="glass-card" style={{ padding: 20 }}>
+              <label style={{ display: "flex", alignItems: "center", gap: 12, cursor: "pointer" }}>
+                <input
+                  type="checkbox"
+                  checked={formatting.enabled}
+                  onChange={e => saveFormatting({ ...formatting, enabled: e.target.checked })}
+                  style={{ width: 18, height: 18, cursor: "pointer" }}
+                />
+                <span style={{ fontSize: 15 }}>將「換行」、「句號」、"new line" 等口述指令轉為格式</span>
+              </label>
+              <p style={{ marginTop: 12, fontSize: 13, color: "var(--color-text-muted)", marginBottom: 12, lineHeight: 1.5 }}>
+                以下 App 不套用（每行一個 bundle id）。終端機預設停用，避免口述的換行直接執行命令。
+              </p>
+              <textarea
+                value={formattingApps}
+                onChange={e => setFormattingApps(e.target.value)}
+                onBlur={() => {
+                  const apps = formattingApps.split('\n').map(a => a.trim()).filter(Boolean);
+                  saveFormatting({ ...formatting, disabled_apps: apps });
+                }}
+                disabled={!formatting.enabled}
+                rows={3}
+                style={{ ...inputStyle, resize: "vertical" }}
+              />
+            </div>
+          </section>
+
+          {/* 翻譯聽寫 */}
+          <section>
+            <h2 style={sectionHeaderStyle}>翻譯聽寫</h2>
+            <div className="glass-card" style={{ padding: 20 }}>
+              <label style={{ display: "flex", alignItems: "center", gap: 12, cursor: "pointer" }}>
+                <input
+                  type="checkbox"
+                  checked={translation.enabled}
+                  onChange={e => saveTranslation({ ...translation, enabled: e.target.checked })}
+                  style={{ width: 18, height: 18, cursor: "pointer" }}
+                />
+                <span style={{ fontSize: 15 }}>用原語言口述，經 AI 翻譯後輸入（使用上方的 AI 服務設定）</span>
+              </label>
+              <p style={{ marginTop: 12, fontSize: 13, color: "var(--color-text-muted)", marginBottom: 12, lineHeight: 1.5 }}>
+                每行一個「bundle id 或網域 = 目標語言」，例如 <code>atlassian.net = en-US</code>；子網域也適用，留空語言代表不翻譯。
+              </p>
+              <textarea
+                value={translationTargets}
+                onChange={e => setTranslationTargets(e.target.value)}
+                onBlur={() => saveTranslation({ ...translation, targets: parseTargets(translationTargets) })}
+                disabled={!translation.enabled}
+                rows={3}
+                placeholder={"com.tinyspeck.slackmacgap = en-US\natlassian.net = en-US"}
+                style={{ ...inputStyle, resize: "vertical" }}
+              />
+              <div className="setting-row" style={{ marginTop: 12 }}>
+                <label>其他 App 的目標語言</label>
+                <input
+                  type="text"
+                  value={translation.default_target}
+                  onChange={e => setTranslation({ ...translation, default_target: e.target.value })}
+                  onBlur={() => saveTranslation(translation)}
+                  disabled={!translation.enabled}
+                  placeholder="不翻譯"
+                  style={{ ...inputStyle, width: 160 }}
+                />
+              </div>
+            </div>
+          </section>
+
+          {/* App 設定檔 */}
+          <section>
+            <h2 style={sectionHeaderStyle}>App 設定檔</h2>
+            <div className="glass-card" style={{ padding: 20 }}>
+              <p style={{ fontSize: 13, color: "var(--color-text-muted)", marginBottom: 12, lineHeight: 1.5 }}>
+                依 App 或網站覆寫潤飾設定，由上而下套用第一個符合的設定檔。glob 支援 * 與 ?（例如 <code>com.tinyspeck.*</code>）；留空的欄位沿用全域設定。
+              </p>
+              {profiles.map((p, i) => (
+                <div key={i} style={{ borderBottom: "1px solid var(--color-border)", paddingBottom: 12, marginBottom: 12 }}>
+                  <div style={{ display: "flex", gap: 8, marginBottom: 8 }}>
+                    <input
+                      value={p.name}
+                      onChange={e => updateProfile(i, { name: e.target.value })}
+                      onBlur={() => saveProfiles(profiles)}
+                      placeholder="名稱"
+                      style={{ ...inputStyle, width: 120, flexShrink: 0 }}
+                    />
+                    <select
+                      value={p.match.field}
+                      onChange={e => updateProfile(i, { match: { ...p.match, field: e.target.value as Profile["match"]["field"] } }, true)}
+                      style={{ ...selectStyle, width: 130, flexShrink: 0 }}
+                    >
+                      <option value="bundle_id">Bundle ID</option>
+                      <option value="app_name">App 名稱</option>
+                      <option value="web_domain">網域</option>
+                    </select>
+                    <input
+                      value={p.match.pattern}
+                      onChange={e => updateProfile(i, { match: { ...p.match, pattern: e.target.value } })}
+                      onBlur={() => saveProfiles(profiles)}
+                      placeholder={p.match.kind === "regex" ? "\\.atlassian\\.net$" : "com.tinyspeck.*"}
+                      style={inputStyle}
+                    />
+                    <select
+                      value={p.match.kind}
+                      onChange={e => updateProfile(i, { match: { ...p.match, kind: e.target.value as Profile["match"]["kind"] } }, true)}
+                      style={{ ...selectStyle, width: 90, flexShrink: 0 }}
+                    >
+                      <option value="glob">glob</option>
+                      <option value="regex">regex</option>
+                    </select>
+                    <button
+                      className="btn btn-ghost"
+                      onClick={() => {
+                        const next = profiles.filter((_, j) => j !== i);
+                        setProfiles(next);
+                        saveProfiles(next);
+                      }}
+                      style={{ padding: 8 }}
+                    >
+                      ✕
+                    </button>
+                  </div>
+                  <div style={{ display: "flex", gap: 8 }}>
+                    <select
+                      value={p.polisher_mode ?? ""}
+                      onChange={e => updateProfile(i, { polisher_mode: e.target.value }, true)}
+                      style={selectStyle}
+                    >
+                      <option value="">潤飾：沿用</option>
+                      <option value="cloud">雲端 AI</option>
+                      <option value="local">本地規則</option>
+                      <option value="none">不潤飾</option>
+                    </select>
+                    <input
+                      value={p.tone ?? ""}
+                      onChange={e => updateProfile(i, { tone: e.target.value })}
+                      onBlur={() => saveProfiles(profiles)}
+                      placeholder="語氣（例如 正式專業）"
+                      style={inputStyle}
+                    />
+                    <input
+                      value={p.output_language ?? ""}
+                      onChange={e => updateProfile(i, { output_language: e.target.value })}
+                      onBlur={() => saveProfiles(profiles)}
+                      placeholder="輸出語言"
+                      style={{ ...inputStyle, width: 110, flexShrink: 0 }}
+                    />
+                    <select
+                      value={p.formatting_commands === undefined ? "" : String(p.formatting_commands)}
+                      onChange={e => updateProfile(i, {
+                        formatting_commands: e.target.value === "" ? undefined : e.target.value === "true",
+                      }, true)}
+                      style={selectStyle}
+                    >
+                      <option value="">格式指令：沿用</option>
+                      <option value="true">開啟</option>
+                      <option value="false">關閉</option>
+                    </select>
+                    <select
+                      value={p.injection ?? ""}
+                      onChange={e => updateProfile(i, { injection: e.target.value as Profile["injection"] }, true)}
+                      style={selectStyle}
+                    >
+                      <option value="">注入：自動</option>
+                      <option value="accessibility">輔助使用 API</option>
+                      <option value="paste">剪貼簿貼上</option>
+                    </select>
+                  </div>
+                </div>
+              ))}
+              <button className="btn btn-ghost" onClick={() => setProfiles([...profiles, NEW_PROFILE])}>
+                ＋ 新增設定檔
+              </button>
+            </div>
+          </section>
+
           {/* v0.3.0 新增：快捷鍵設定 */}
           <section>
             <h2 style={sectionHeaderStyle}>快捷鍵</h2>
             <div className="glass-card" style={{ padding: 20 }}>
               <div className="setting-row">
                 <label>觸發鍵</label>
-                <span className="kbd-badge">fn (Globe 🌐)</span>
+                <select
+                  value={TRIGGER_KEY_PRESETS.some(p => p.value === hotkey.triggerKey) ? hotkey.triggerKey : "custom"}
+                  onChange={e => {
+                    if (e.target.value === "custom") {
+                      setHotkey({ ...hotkey, triggerKey: customTrigger });
+                    } else {
+                      saveHotkey({ ...hotkey, triggerKey: e.target.value });
+                    }
+                  }}
+                  style={{ ...inputStyle, padding: "8px 12px" }}
+                >
+                  {TRIGGER_KEY_PRESETS.map(p => <option key={p.value} value={p.value}>{p.label}</option>)}
+                  <option value="custom">自訂組合…</option>
+                </select>
               </div>
+              {!TRIGGER_KEY_PRESETS.some(p => p.value === hotkey.triggerKey) && (
+                <div className="setting-row" style={{ marginTop: 12 }}>
+                  <label>自訂觸發鍵</label>
+                  <input
+                    value={customTrigger}
+                    placeholder="例如 cmd+alt+k、F15、right_shift"
+                    onChange={e => setCustomTrigger(e.target.value)}
+                    onBlur={() => customTrigger.trim() && saveHotkey({ ...hotkey, triggerKey: customTrigger.trim() })}
+                    style={inputStyle}
+                  />
+                </div>
+              )}
               <div className="setting-row" style={{ marginTop: 16 }}>
                 <label>錄音模式</label>
                 <select
-                  value={hotkeyMode}
-                  onChange={e => { setHotkeyMode(e.target.value); save("hotkey", e.target.value); }}
+                  value={hotkey.mode}
+                  onChange={e => saveHotkey({ ...hotkey, mode: e.target.value as HotkeyMode })}
                   style={{ ...inputStyle, padding: "8px 12px" }}
                 >
                   <option value="push_to_talk">長按錄音 — 按住 fn 錄音，放開停止</option>
                   <option value="toggle">點按切換 — 按一下開始，再按一下停止</option>
                 </select>
               </div>
+              {HOTKEY_TIMING_FIELDS.map(f => (
+                <div className="setting-row" style={{ marginTop: 12 }} key={f.key}>
+                  <label title={f.hint}>{f.label}（ms）</label>
+                  <input
+                    type="number"
+                    min={f.min}
+                    max={f.max}
+                    step={10}
+                    value={hotkey[f.key]}
+                    onChange={e => setHotkey({ ...hotkey, [f.key]: Number(e.target.value) })}
+                    onBlur={() => saveHotkey(hotkey)}
+                    style={{ ...inputStyle, width: 100 }}
+                  />
+                </div>
+              ))}
+              <div className="setting-hint">
+                長按判定 {HOTKEY_TIMING_FIELDS[0].min}–{HOTKEY_TIMING_FIELDS[0].max}ms、最短錄音 {HOTKEY_TIMING_FIELDS[1].min}–{HOTKEY_TIMING_FIELDS[1].max}ms、去抖動 {HOTKEY_TIMING_FIELDS[2].min}–{HOTKEY_TIMING_FIELDS[2].max}ms；常被「錄音太短」取消時可調低最短錄音，常誤觸時可調高長按判定。
+              </div>
+              {HOTKEY_ACTIONS.map((a, i) => (
+                <div className="setting-row" style={{ marginTop: i === 0 ? 16 : 12 }} key={a.action}>
+                  <label title={a.hint}>{a.label}</label>
+                  <input
+                    value={bindings[a.action]}
+                    placeholder="未綁定"
+                    onChange={e => setBindings({ ...bindings, [a.action]: e.target.value })}
+                    onBlur={() => saveBindings(bindings)}
+                    style={{ ...inputStyle, width: 180 }}
+                  />
+                </div>
+              ))}
+              <div className="setting-hint">
+                聽寫類動作綁在觸發鍵上：trigger 為單按觸發鍵，trigger+shift、trigger+ctrl 等為同時按住 modifier；其他動作綁快捷鍵（例如 ctrl+cmd+x），需包含 Ctrl、Cmd 或 Option（F 鍵除外）。清空即取消綁定。
+              </div>
+              <div className="setting-hint">
+                免持錄音：快速按兩下觸發鍵即鎖定錄音，不必按住；再按一次觸發鍵或按 Esc 停止。
+              </div>
+              <div className="setting-hint">
+                語音指令：選取文字後按住觸發鍵 + Ctrl（預設綁定）說出指令（例如「改得正式一點」、「翻成英文」、「整理成條列」），結果會取代選取的文字。
+              </div>
+              <div className="setting-hint">
+                撤銷：注入的文字有誤時按撤銷快捷鍵（預設 Ctrl + Cmd + Z）刪除；再加按 Shift 改放回未潤飾的原始轉錄。
+              </div>
+              <div className="setting-hint">
+                自訂觸發鍵可用單側 modifier（right_option、right_cmd…）、F1–F20，或加上 Cmd / Option / Shift / Ctrl 的組合（例如 cmd+alt+k）；組合用到的 modifier 不能再綁成觸發鍵 + modifier 的動作。
+              </div>
               <div className="setting-hint">
-                提示：請在「系統設定 → 鍵盤 → 按下 🌐 鍵時」選擇「不做任何動作」以避免衝突。
+                提示：使用 fn 時，請在「系統設定 → 鍵盤 → 按下 🌐 鍵時」選擇「不做任何動作」以避免衝突。
               </div>
             </div>
           </section>
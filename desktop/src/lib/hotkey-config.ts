@@ -4,14 +4,30 @@ export type HotkeyMode = "push_to_talk" | "toggle";
 
 export interface HotkeyConfig {
   mode: HotkeyMode;
-  triggerKey: string; // "fn" | "F13" | 自訂
+  triggerKey: string; // "fn" | "right_option" | "F13" | "cmd+alt+k"（格式見 hotkey.rs 的 Trigger）
+  holdThresholdMs: number; // 長按判定閾值
+  minRecordingMs: number; // 最短有效錄音時長
+  debounceMs: number; // 觸發鍵去抖動
+  doubleTapMs: number; // 雙擊鎖定的間隔（0 = 停用）
 }
 
 export const DEFAULT_HOTKEY_CONFIG: HotkeyConfig = {
   mode: "push_to_talk",
   triggerKey: "fn",
+  holdThresholdMs: 300,
+  minRecordingMs: 500,
+  debounceMs: 50,
+  doubleTapMs: 300,
 };
 
+// 與 hotkey.rs 的 *_RANGE 一致（超出範圍時 Rust 端拒絕保存）
+export const HOTKEY_TIMING_FIELDS: { key: "holdThresholdMs" | "minRecordingMs" | "debounceMs" | "doubleTapMs"; label: string; min: number; max: number; hint: string }[] = [
+  { key: "holdThresholdMs", label: "長按判定", min: 100, max: 1000, hint: "按住超過此時間才開始錄音" },
+  { key: "minRecordingMs", label: "最短錄音", min: 0, max: 5000, hint: "錄音短於此時間會被取消" },
+  { key: "debounceMs", label: "去抖動", min: 0, max: 200, hint: "忽略此時間內的重複按鍵訊號" },
+  { key: "doubleTapMs", label: "雙擊鎖定", min: 0, max: 1000, hint: "點按後在此時間內再按一次即鎖定錄音，0 為停用" },
+];
+
 export const HOTKEY_MODE_LABELS: Record<HotkeyMode, { name: string; desc: string }> = {
   push_to_talk: {
     name: "長按錄音",
@@ -22,3 +38,53 @@ export const HOTKEY_MODE_LABELS: Record<HotkeyMode, { name: string; desc: string
     desc: "短按 fn 鍵開始錄音，再按一次停止",
   },
 };
+
+export const TRIGGER_KEY_PRESETS: { value: string; label: string }[] = [
+  { value: "fn", label: "fn (Globe 🌐)" },
+  { value: "right_option", label: "右 Option" },
+  { value: "right_cmd", label: "右 Command" },
+  { value: "F13", label: "F13" },
+];
+
+// 與 hotkey.rs 的 HotkeyAction 一致；觸發鍵手勢寫成 "trigger" / "trigger+shift"，快捷鍵寫成 "ctrl+cmd+v"
+export type HotkeyAction =
+  | "dictate"
+  | "dictate-and-translate"
+  | "command-on-selection"
+  | "paste-last"
+  | "undo-last"
+  | "cancel";
+
+export type HotkeyBindings = Record<HotkeyAction, string>;
+
+export const HOTKEY_ACTIONS: { action: HotkeyAction; label: string; hint: string }[] = [
+  { action: "dictate", label: "聽寫", hint: "觸發鍵手勢，例如 trigger" },
+  { action: "dictate-and-translate", label: "聽寫並翻譯", hint: "翻譯成翻譯聽寫的目標語言" },
+  { action: "command-on-selection", label: "語音指令", hint: "改寫選取的文字" },
+  { action: "paste-last", label: "貼上最後一次轉錄", hint: "快捷鍵，例如 ctrl+cmd+v" },
+  { action: "undo-last", label: "撤銷最後一次注入", hint: "加按 Shift 放回原始轉錄" },
+  { action: "cancel", label: "取消錄音", hint: "放棄進行中的錄音或轉錄" },
+];
+
+export const DEFAULT_HOTKEY_BINDINGS: HotkeyBindings = {
+  dictate: "trigger",
+  "dictate-and-translate": "trigger+shift",
+  "command-on-selection": "trigger+ctrl",
+  "paste-last": "ctrl+cmd+V",
+  "undo-last": "ctrl+cmd+Z",
+  cancel: "",
+};
+
+/** 解析 `hotkey` 設定；舊版只存模式字串 */
+export function parseHotkeyConfig(raw: string | undefined): HotkeyConfig {
+  if (!raw) return DEFAULT_HOTKEY_CONFIG;
+  if (!raw.trim().startsWith("{")) {
+    return { ...DEFAULT_HOTKEY_CONFIG, mode: raw as HotkeyMode };
+  }
+  try {
+    return { ...DEFAULT_HOTKEY_CONFIG, ...JSON.parse(raw) };
+  } catch (e) {
+    console.error("Invalid hotkey setting:", e);
+    return DEFAULT_HOTKEY_CONFIG;
+  }
+}
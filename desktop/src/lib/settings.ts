@@ -11,7 +11,13 @@ export const SettingsKeys = {
   HISTORY_RETENTION_DAYS: 'history_retention_days',
   API_KEY: 'api_key',
   API_BASE_URL: 'api_base_url',
+  POLISHER_MODEL: 'polisher_model',
   HOTKEY: 'hotkey',
+  HOTKEY_BINDINGS: 'hotkey_bindings',
+  HELPER_TIMEOUTS: 'helper_timeouts',
+  FORMATTING_COMMANDS: 'formatting_commands',
+  TRANSLATION: 'translation',
+  PROFILES: 'profiles',
 } as const;
 
 export type SettingsKey = typeof SettingsKeys[keyof typeof SettingsKeys];
@@ -27,5 +33,11 @@ export interface Settings {
   [SettingsKeys.HISTORY_RETENTION_DAYS]: string;
   [SettingsKeys.API_KEY]: string;
   [SettingsKeys.API_BASE_URL]: string;
-  [SettingsKeys.HOTKEY]: 'push_to_talk' | 'toggle';
+  [SettingsKeys.POLISHER_MODEL]: string; // 空字串 = 依 endpoint 預設
+  [SettingsKeys.HOTKEY]: string; // JSON: { "mode": "push_to_talk", "triggerKey": "right_option", "holdThresholdMs": 300, "minRecordingMs": 500, "debounceMs": 50, "doubleTapMs": 300 }（舊版為 'push_to_talk' | 'toggle'）
+  [SettingsKeys.HOTKEY_BINDINGS]: string; // JSON: { "dictate": "trigger", "command-on-selection": "trigger+ctrl", "cancel": "ctrl+cmd+x" }（空字串 = 未綁定）
+  [SettingsKeys.HELPER_TIMEOUTS]: string; // JSON: { "stop_recording": 300 }
+  [SettingsKeys.FORMATTING_COMMANDS]: string; // JSON: { "enabled": true, "languages": ["en", "zh"], "disabled_apps": [] }
+  [SettingsKeys.TRANSLATION]: string; // JSON: { "enabled": true, "default_target": "", "targets": { "atlassian.net": "en-US" } }
+  [SettingsKeys.PROFILES]: string; // JSON: [{ "name": "Slack", "match": { "field": "bundle_id", "pattern": "com.tinyspeck.*" }, "tone": "輕鬆友善" }]
 }
@@ -1,9 +1,22 @@
 import Foundation
+import AppKit
 import AVFoundation
+import Combine
 import CoreKit
 
 // MARK: - JSON IPC Bridge
 // Rust (Tauri) ─stdin JSON→ EchoTypeHelper ─stdout JSON→ Rust
+// 每個請求帶有 `id`，在獨立 Task 中處理，回應時原樣帶回 `id`，
+// 因此慢速命令（stop_recording / download_model）不會阻塞其他命令。
+// helper 也可主動推送事件行（沒有 `id`）：{"event": "audio_level", "data": {...}}
+//
+// 以 `EchoTypeHelper --socket [path]` 啟動時改為監聽 Unix domain socket，
+// 可獨立運行（掛 debugger）並同時服務多個 client（app 與 CLI）：
+// 回應只寫回發出請求的連線，事件廣播給所有連線。
+//
+// Framing：Rust 端在 hello 的 `framings` 中列出支援的格式；選定 `length_prefixed` 時，
+// hello 回應之後雙向改用 [header_len u32 BE][payload_len u32 BE][JSON header][payload]，
+// 讓錄音 PCM 等二進位資料不需 base64（見 desktop/src-tauri/src/framing.rs）。
 
 let audioManager = AudioManager.shared
 let historyStore = HistoryStore.shared
@@ -13,25 +26,234 @@ let textInjector = MacOSTextInjector()
 let asrManager = ASREngineManager.shared
 let modelDownloader = ModelDownloader.shared
 
-func respond(_ result: Any) {
-    if let data = try? JSONSerialization.data(withJSONObject: ["ok": true, "result": result]),
-       let str = String(data: data, encoding: .utf8) {
-        print(str)
-        fflush(stdout)
+// 目前 Task 所處理請求的 id 與來源連線（由主迴圈以 TaskLocal 綁定）
+enum RequestContext {
+    @TaskLocal static var id: Int?
+    @TaskLocal static var connection: Connection?
+}
+
+// MARK: - Connections
+
+/// 一條 client 連線（stdout 或 socket）；多個 Task 會同時回應，寫入需序列化以免行交錯
+final class Connection: @unchecked Sendable {
+    private let handle: FileHandle
+    private let lock = NSLock()
+    private var framed = false          // hello 協商為 length_prefixed 後為 true（受 lock 保護）
+    private var audioStreaming = false  // `stream_audio` 開啟後推送 audio_chunk
+    let inflight = InflightRequests()
+
+    init(handle: FileHandle) {
+        self.handle = handle
+    }
+
+    /// 寫出一則訊息；JSON-line 模式無法攜帶 payload，帶 payload 的訊息直接略過
+    func write(_ header: String, payload: Data = Data()) {
+        lock.lock()
+        defer { lock.unlock() }
+        writeLocked(header, payload: payload)
+    }
+
+    /// 寫出 hello 回應後立即切換 framing（同一把鎖內，之後的事件也都已 framed）
+    func writeThenEnableFraming(_ header: String) {
+        lock.lock()
+        defer { lock.unlock() }
+        writeLocked(header, payload: Data())
+        framed = true
+    }
+
+    private func writeLocked(_ header: String, payload: Data) {
+        if framed {
+            let headerData = Data(header.utf8)
+            var frame = Data()
+            frame.append(bigEndian: UInt32(headerData.count))
+            frame.append(bigEndian: UInt32(payload.count))
+            frame.append(headerData)
+            frame.append(payload)
+            try? handle.write(contentsOf: frame)
+        } else if payload.isEmpty {
+            try? handle.write(contentsOf: Data((header + "\n").utf8))
+        }
+    }
+
+    /// 是否要收到二進位的 audio_chunk 事件
+    var wantsAudio: Bool {
+        lock.lock()
+        defer { lock.unlock() }
+        return framed && audioStreaming
+    }
+
+    func setAudioStreaming(_ enabled: Bool) {
+        lock.lock()
+        audioStreaming = enabled
+        lock.unlock()
+    }
+}
+
+extension Data {
+    mutating func append(bigEndian value: UInt32) {
+        var v = value.bigEndian
+        Swift.withUnsafeBytes(of: &v) { append(contentsOf: $0) }
     }
 }
 
+/// 目前所有連線，供事件廣播
+final class ConnectionRegistry: @unchecked Sendable {
+    private var connections: [ObjectIdentifier: Connection] = [:]
+    private let lock = NSLock()
+
+    func add(_ connection: Connection) {
+        lock.lock()
+        connections[ObjectIdentifier(connection)] = connection
+        lock.unlock()
+    }
+
+    func remove(_ connection: Connection) {
+        lock.lock()
+        connections[ObjectIdentifier(connection)] = nil
+        lock.unlock()
+    }
+
+    func broadcast(_ line: String) {
+        all().forEach { $0.write(line) }
+    }
+
+    func all() -> [Connection] {
+        lock.lock()
+        defer { lock.unlock() }
+        return Array(connections.values)
+    }
+}
+
+let connections = ConnectionRegistry()
+
+func encodeLine(_ object: [String: Any]) -> String? {
+    guard let data = try? JSONSerialization.data(withJSONObject: object) else { return nil }
+    return String(data: data, encoding: .utf8)
+}
+
+/// 回應寫回發出請求的連線
+func writeLine(_ object: [String: Any]) {
+    guard let line = encodeLine(object), let connection = RequestContext.connection else { return }
+    connection.write(line)
+}
+
+/// hello 的 framing 協商：Rust 端依偏好列出 `framings`，選第一個 helper 也支援的
+func negotiateFraming(_ args: [String: Any]) -> String {
+    let offered = args["framings"] as? [String] ?? []
+    return offered.first { ["length_prefixed", "json_lines"].contains($0) } ?? "json_lines"
+}
+
+func respond(_ result: Any) {
+    var payload: [String: Any] = ["ok": true, "result": result]
+    if let id = RequestContext.id { payload["id"] = id }
+    writeLine(payload)
+}
+
 func respondError(_ message: String) {
-    if let data = try? JSONSerialization.data(withJSONObject: ["ok": false, "error": message]),
-       let str = String(data: data, encoding: .utf8) {
-        print(str)
-        fflush(stdout)
+    var payload: [String: Any] = ["ok": false, "error": message]
+    if let id = RequestContext.id { payload["id"] = id }
+    writeLine(payload)
+}
+
+/// 推送非請求回應的事件（不可帶 id，Rust 端據此與回應區分）
+func emitEvent(_ name: String, _ data: Any = [String: Any]()) {
+    guard let line = encodeLine(["event": name, "data": data]) else { return }
+    connections.broadcast(line)
+}
+
+/// 錄音 PCM 以二進位 payload 推送給開啟 `stream_audio` 的 framed 連線
+func emitAudioChunk(_ samples: [Float]) {
+    let targets = connections.all().filter { $0.wantsAudio }
+    guard !targets.isEmpty,
+          let header = encodeLine(["event": "audio_chunk",
+                                   "data": ["format": "f32le", "sample_rate": 16000, "channels": 1]])
+    else { return }
+    let payload = samples.withUnsafeBufferPointer { Data(buffer: $0) }
+    targets.forEach { $0.write(header, payload: payload) }
+}
+
+// MARK: - Event Sources
+
+@MainActor var eventSubscriptions = Set<AnyCancellable>()
+
+@MainActor
+func startEventSources() {
+    // 錄音 PCM（音訊執行緒呼叫），只送給要求串流的連線
+    audioManager.onSamples = { samples in emitAudioChunk(samples) }
+
+    // 錄音中的音量（約 20 Hz），驅動浮動條波形
+    audioManager.$audioLevel
+        .filter { _ in audioManager.isRecording }
+        .throttle(for: .milliseconds(50), scheduler: RunLoop.main, latest: true)
+        .sink { level in emitEvent("audio_level", ["level": level]) }
+        .store(in: &eventSubscriptions)
+
+    // 麥克風熱插拔
+    for name in [AVCaptureDevice.wasConnectedNotification, AVCaptureDevice.wasDisconnectedNotification] {
+        NotificationCenter.default.publisher(for: name)
+            .sink { _ in
+                Task { @MainActor in
+                    await audioManager.refreshDevices()
+                    let list = audioManager.availableDevices.map { d -> [String: Any] in
+                        ["id": d.id, "name": d.name, "is_default": d.isDefault]
+                    }
+                    emitEvent("device_changed", ["devices": list])
+                }
+            }
+            .store(in: &eventSubscriptions)
     }
 }
 
+// MARK: - Protocol Handshake
+// Rust 端在 spawn 後先送 `hello`，據此拒絕不相容的 helper，並讓不支援的命令立即失敗
+
+let protocolVersion = 1
+let helperVersion = "0.3.0"
+
+/// dispatch() 支援的所有命令；新增 case 時必須同步加入
+let supportedCommands = [
+    "hello", "cancel", "ping",
+    "get_context", "start_recording", "stop_recording", "inject_text", "delete_backward",
+    "get_microphones", "get_settings", "set_setting", "get_api_key",
+    "get_dictionary", "set_dictionary",
+    "get_snippets", "set_snippets", "get_placeholder_values",
+    "get_history", "save_history", "prune_history",
+    "check_model_status", "download_model",
+    "check_microphone_permission", "request_microphone", "check_accessibility",
+    "stream_audio",
+]
+
 func dispatch(cmd: String, args: [String: Any]) async {
     switch cmd {
 
+    case "hello":
+        let framing = negotiateFraming(args)
+        var payload: [String: Any] = ["ok": true, "result": [
+            "protocol_version": protocolVersion,
+            "helper_version":   helperVersion,
+            "commands":         supportedCommands,
+            "framing":          framing
+        ]]
+        if let id = RequestContext.id { payload["id"] = id }
+        guard let line = encodeLine(payload), let connection = RequestContext.connection else { return }
+        // hello 回應本身仍是 JSON-line，之後的輸出才改為 frame
+        if framing == "length_prefixed" {
+            connection.writeThenEnableFraming(line)
+        } else {
+            connection.write(line)
+        }
+
+    case "cancel":
+        guard let target = args["request_id"] as? Int else {
+            respondError("Missing 'request_id' argument")
+            return
+        }
+        respond(RequestContext.connection?.inflight.cancel(target) ?? false)
+
+    case "ping":
+        // 心跳：Rust 端以此偵測卡死的 helper
+        respond(true)
+
     case "get_context":
         let ctx = await contextReader.getFocusedContext()
         respond([
@@ -62,11 +284,7 @@ func dispatch(cmd: String, args: [String: Any]) async {
         do {
             // 載入個人詞典並加入到 context prompt
             let dictRaw = await settingsStore.get(.personalDictionary) ?? "[]"
-            let dictWords: [String] = {
-                guard let data = dictRaw.data(using: .utf8),
-                      let arr = try? JSONSerialization.jsonObject(with: data) as? [String] else { return [] }
-                return arr
-            }()
+            let dictWords = dictionaryTerms(dictRaw)
             let dictPrompt = dictWords.isEmpty ? "" : dictWords.joined(separator: ", ") + ". "
 
             // 取得當前上下文，用於 Whisper initial_prompt
@@ -86,12 +304,26 @@ func dispatch(cmd: String, args: [String: Any]) async {
         }
 
     case "inject_text":
+        guard let text = args["text"] as? String else {
+            respondError("Missing 'text' argument")
+            return
+        }
+        let method = (args["method"] as? String).flatMap(InjectionMethod.init(rawValue:)) ?? .auto
+        do {
+            try await textInjector.insertText(text, method: method)
+            respond(true)
+        } catch {
+            respondError(error.localizedDescription)
+        }
+
+    case "delete_backward":
+        // 撤銷注入：以字元叢集計算 Backspace 次數（emoji、組合字元只算一個）
         guard let text = args["text"] as? String else {
             respondError("Missing 'text' argument")
             return
         }
         do {
-            try await textInjector.insertText(text)
+            try await textInjector.deleteBackward(count: text.count)
             respond(true)
         } catch {
             respondError(error.localizedDescription)
@@ -107,6 +339,7 @@ func dispatch(cmd: String, args: [String: Any]) async {
     case "get_settings":
         let engine   = await settingsStore.get(.asrEngine)    ?? "whisper_turbo"
         let polisher = await settingsStore.get(.polisherMode) ?? "none"
+        let polisherModel = await settingsStore.get(.polisherModel) ?? ""
         let inputLang  = await settingsStore.get(.inputLanguage)  ?? "auto"
         let outputLang = await settingsStore.get(.outputLanguage) ?? "zh-TW"
         let mic      = await settingsStore.get(.selectedMic)  ?? "default"
@@ -114,7 +347,12 @@ func dispatch(cmd: String, args: [String: Any]) async {
         let apiBaseUrl = await settingsStore.get(.apiBaseUrl) ?? "https://api.groq.com/openai/v1/chat/completions"
         let retention = await settingsStore.get(.historyRetentionDays) ?? "30"
         let hotkey   = await settingsStore.get(.hotkey)       ?? "push_to_talk"
+        let hotkeyBindings = await settingsStore.get(.hotkeyBindings) ?? ""
         let launchAtLogin = await settingsStore.get(.launchAtLogin) ?? "false"
+        let helperTimeouts = await settingsStore.get(.helperTimeouts) ?? ""
+        let formattingCommands = await settingsStore.get(.formattingCommands) ?? ""
+        let translation = await settingsStore.get(.translation) ?? ""
+        let profiles = await settingsStore.get(.profiles) ?? ""
 
         // Mask API key for security (show first 7 + last 4 only if long enough)
         let maskedKey: String
@@ -129,6 +367,7 @@ func dispatch(cmd: String, args: [String: Any]) async {
         respond([
             "asr_engine":      engine,
             "polisher_mode":   polisher,
+            "polisher_model":  polisherModel,
             "input_language":  inputLang,
             "output_language": outputLang,
             "selected_mic_id": mic,
@@ -136,9 +375,18 @@ func dispatch(cmd: String, args: [String: Any]) async {
             "api_base_url":    apiBaseUrl,
             "history_retention_days": retention,
             "hotkey":          hotkey,
-            "launch_at_login": launchAtLogin
+            "hotkey_bindings": hotkeyBindings,
+            "launch_at_login": launchAtLogin,
+            "helper_timeouts": helperTimeouts,
+            "formatting_commands": formattingCommands,
+            "translation":     translation,
+            "profiles":        profiles
         ])
 
+    case "get_api_key":
+        // 未遮罩：只有 Rust 端的 LLM 潤飾會呼叫，結果不會轉給前端
+        respond(await settingsStore.get(.apiKey) ?? "")
+
     case "set_setting":
         guard let key = args["key"] as? String, let value = args["value"] as? String else {
             respondError("Missing 'key' or 'value'")
@@ -159,21 +407,22 @@ func dispatch(cmd: String, args: [String: Any]) async {
         }
 
     case "get_dictionary":
+        // 元素可能是字串（舊版）或 {term, sounds_like, case} 物件，原樣交給 Rust 解析
         let raw = await settingsStore.get(.personalDictionary) ?? "[]"
         if let data = raw.data(using: .utf8),
-           let arr = try? JSONSerialization.jsonObject(with: data) as? [String] {
+           let arr = try? JSONSerialization.jsonObject(with: data) as? [Any] {
             respond(arr)
         } else {
-            respond([String]())
+            respond([Any]())
         }
 
     case "set_dictionary":
-        guard let words = args["words"] as? [String] else {
-            respondError("Missing 'words' array")
+        guard let entries = args["entries"] as? [Any] else {
+            respondError("Missing 'entries' array")
             return
         }
         do {
-            let json = try JSONSerialization.data(withJSONObject: words)
+            let json = try JSONSerialization.data(withJSONObject: entries)
             guard let str = String(data: json, encoding: .utf8) else {
                 respondError("Failed to encode dictionary as UTF-8")
                 return
@@ -184,6 +433,43 @@ func dispatch(cmd: String, args: [String: Any]) async {
             respondError(error.localizedDescription)
         }
 
+    case "get_snippets":
+        let raw = await settingsStore.get(.snippets) ?? "[]"
+        if let data = raw.data(using: .utf8),
+           let arr = try? JSONSerialization.jsonObject(with: data) as? [[String: Any]] {
+            respond(arr)
+        } else {
+            respond([[String: Any]]())
+        }
+
+    case "set_snippets":
+        guard let snippets = args["snippets"] as? [[String: Any]] else {
+            respondError("Missing 'snippets' array")
+            return
+        }
+        do {
+            let json = try JSONSerialization.data(withJSONObject: snippets)
+            guard let str = String(data: json, encoding: .utf8) else {
+                respondError("Failed to encode snippets as UTF-8")
+                return
+            }
+            try await settingsStore.set(.snippets, value: str)
+            respond(true)
+        } catch {
+            respondError(error.localizedDescription)
+        }
+
+    case "get_placeholder_values":
+        // 片段佔位符：本機時區的日期；clipboard 為 true 時才讀取剪貼簿
+        let formatter = DateFormatter()
+        formatter.dateFormat = "yyyy-MM-dd"
+        let wantsClipboard = args["clipboard"] as? Bool ?? false
+        let clipboard = wantsClipboard ? await MainActor.run { NSPasteboard.general.string(forType: .string) } : nil
+        respond([
+            "date":      formatter.string(from: Date()),
+            "clipboard": clipboard as Any,
+        ])
+
     case "get_history":
         let limit = args["limit"] as? Int ?? 50
         do {
@@ -196,6 +482,8 @@ func dispatch(cmd: String, args: [String: Any]) async {
                     "created_at": ISO8601DateFormatter().string(from: e.createdAt)
                 ]
                 if let p = e.polishedText  { dict["polished_text"] = p }
+                if let t = e.translatedText { dict["translated_text"] = t }
+                if let l = e.targetLanguage { dict["target_language"] = l }
                 if let a = e.appName       { dict["app_name"]      = a }
                 if let w = e.windowTitle   { dict["window_title"]  = w }
                 if let u = e.webUrl        { dict["web_url"]       = u }
@@ -216,13 +504,16 @@ func dispatch(cmd: String, args: [String: Any]) async {
         let entry = HistoryEntry(
             transcript:     transcript,
             polishedText:   args["polished_text"] as? String,
+            translatedText: args["translated_text"] as? String,
+            targetLanguage: args["target_language"] as? String,
             appName:        args["app_name"]      as? String,
             windowTitle:    args["window_title"]  as? String,
             webUrl:         args["web_url"]       as? String,
             webDomain:      args["web_domain"]    as? String,
             webTitle:       args["web_title"]     as? String,
             asrEngine:      args["asr_engine"]    as? String ?? "whisper_turbo",
-            durationSeconds: args["duration"]      as? Double ?? 0
+            durationSeconds: args["duration"]      as? Double ?? 0,
+            mode:           args["translated_text"] is String ? "translate" : "voice_transcript"
         )
         do {
             try await historyStore.insert(entry)
@@ -278,7 +569,7 @@ func dispatch(cmd: String, args: [String: Any]) async {
                 ])
             } else if modelType == "qwen3" {
                 let _ = try await modelDownloader.downloadQwen3 { progress in
-                    fputs("Qwen3 download progress: \(Int(progress * 100))%\n", stderr)
+                    emitEvent("model_download_progress", ["model_type": modelType, "progress": progress])
                 }
                 respond(true)
             } else {
@@ -310,23 +601,217 @@ func dispatch(cmd: String, args: [String: Any]) async {
     case "check_accessibility":
         respond(AXIsProcessTrusted())
 
+    case "stream_audio":
+        // 開關此連線的 audio_chunk 二進位事件（需 length_prefixed framing）
+        RequestContext.connection?.setAudioStreaming(args["enabled"] as? Bool ?? false)
+        respond(true)
+
     default:
         respondError("Unknown command: \(cmd)")
     }
 }
 
+/// 個人詞典的詞（給 Whisper prompt）；元素是字串或 {term, ...} 物件
+func dictionaryTerms(_ raw: String) -> [String] {
+    guard let data = raw.data(using: .utf8),
+          let arr = try? JSONSerialization.jsonObject(with: data) as? [Any] else { return [] }
+    return arr.compactMap { element in
+        if let term = element as? String { return term }
+        return (element as? [String: Any])?["term"] as? String
+    }
+}
+
+// MARK: - In-flight Requests
+// 進行中的請求（id → Task），供 `cancel` 命令中止（例如使用者在轉錄時按 Esc）
+
+/// JSONSerialization 產生的參數只在單一 Task 內讀取
+struct RequestArgs: @unchecked Sendable {
+    let value: [String: Any]
+}
+
+final class InflightRequests: @unchecked Sendable {
+    private var tasks: [Int: Task<Void, Never>] = [:]
+    private let lock = NSLock()
+
+    /// 建立並登記 Task；登記在鎖內完成，Task 結束時的移除必定發生在登記之後
+    func start(_ id: Int, _ body: @escaping @Sendable () async -> Void) {
+        lock.lock()
+        defer { lock.unlock() }
+        tasks[id] = Task {
+            await body()
+            self.remove(id)
+        }
+    }
+
+    func remove(_ id: Int) {
+        lock.lock()
+        tasks[id] = nil
+        lock.unlock()
+    }
+
+    /// 取消指定請求；回傳是否仍在進行中
+    func cancel(_ id: Int) -> Bool {
+        lock.lock()
+        let task = tasks.removeValue(forKey: id)
+        lock.unlock()
+        task?.cancel()
+        return task != nil
+    }
+
+    /// 連線中斷：取消該連線所有進行中的請求
+    func cancelAll() {
+        lock.lock()
+        let all = Array(tasks.values)
+        tasks.removeAll()
+        lock.unlock()
+        all.forEach { $0.cancel() }
+    }
+}
+
 // MARK: - Main Loop (newline-delimited JSON)
-for line in AnySequence({ () -> AnyIterator<String> in
-    AnyIterator { readLine(strippingNewline: true) }
-}) {
-    guard
-        let data = line.data(using: .utf8),
-        let json = try? JSONSerialization.jsonObject(with: data) as? [String: Any],
-        let cmd = json["cmd"] as? String
-    else {
-        respondError("Invalid JSON or missing 'cmd' field")
-        continue
+// 每條連線在背景執行緒讀取，主執行緒保留給 MainActor（剪貼簿、ASR 引擎狀態）
+/// 處理一則請求（JSON-line 的一行或 frame 的 header）。
+/// 回傳 true 表示這是協商為 length_prefixed 的 hello，之後的輸入改讀 frame。
+/// 請求的二進位 payload 目前沒有命令使用，讀取後即丟棄。
+@discardableResult
+func handleMessage(_ data: Data, from connection: Connection) -> Bool {
+    guard let json = try? JSONSerialization.jsonObject(with: data) as? [String: Any] else {
+        RequestContext.$connection.withValue(connection) { respondError("Invalid JSON") }
+        return false
+    }
+    let id = json["id"] as? Int
+    guard let cmd = json["cmd"] as? String else {
+        RequestContext.$connection.withValue(connection) {
+            RequestContext.$id.withValue(id) { respondError("Missing 'cmd' field") }
+        }
+        return false
+    }
+    let args = RequestArgs(value: json["args"] as? [String: Any] ?? [:])
+    let run: @Sendable () async -> Void = {
+        await RequestContext.$connection.withValue(connection) {
+            await RequestContext.$id.withValue(id) {
+                await dispatch(cmd: cmd, args: args.value)
+            }
+        }
+    }
+    if let id {
+        connection.inflight.start(id, run)
+    } else {
+        Task { await run() }
+    }
+    return cmd == "hello" && negotiateFraming(args.value) == "length_prefixed"
+}
+
+/// 從 stream 讀取剛好 count 個位元組；EOF 時回傳 nil
+func readExactly(_ count: Int, from stream: UnsafeMutablePointer<FILE>) -> Data? {
+    guard count > 0 else { return Data() }
+    var buffer = [UInt8](repeating: 0, count: count)
+    guard fread(&buffer, 1, count, stream) == count else { return nil }
+    return Data(buffer)
+}
+
+/// 讀取一個 frame（[header_len][payload_len][header][payload]）
+func readFrame(from stream: UnsafeMutablePointer<FILE>) -> (header: Data, payload: Data)? {
+    guard let lengths = readExactly(8, from: stream) else { return nil }
+    let bytes = [UInt8](lengths)
+    let headerLength = bytes[0..<4].reduce(0) { $0 << 8 | Int($1) }
+    let payloadLength = bytes[4..<8].reduce(0) { $0 << 8 | Int($1) }
+    guard let header = readExactly(headerLength, from: stream),
+          let payload = readExactly(payloadLength, from: stream) else { return nil }
+    return (header, payload)
+}
+
+/// 讀取一條連線直到 EOF：先逐行讀取，hello 協商出 framing 後改讀 frame
+func serve(input: FileHandle, connection: Connection) {
+    guard let stream = fdopen(input.fileDescriptor, "r") else { return }
+    var buffer: UnsafeMutablePointer<CChar>? = nil
+    var capacity = 0
+    var framed = false
+    while true {
+        if framed {
+            guard let frame = readFrame(from: stream) else { break }
+            handleMessage(frame.header, from: connection)
+        } else {
+            guard getline(&buffer, &capacity, stream) > 0, let cLine = buffer else { break }
+            let line = String(cString: cLine).trimmingCharacters(in: .newlines)
+            if !line.isEmpty {
+                framed = handleMessage(Data(line.utf8), from: connection)
+            }
+        }
+    }
+    free(buffer)
+    fclose(stream)
+}
+
+// MARK: - Unix Socket Server
+
+/// `--socket` 的預設路徑，與 Rust 端 transport::socket_path() 相同
+func defaultSocketPath() -> String {
+    if let path = ProcessInfo.processInfo.environment["ECHOTYPE_HELPER_SOCKET"], !path.isEmpty {
+        return path
+    }
+    return (NSTemporaryDirectory() as NSString).appendingPathComponent("echotype/helper.sock")
+}
+
+/// 監聽 Unix domain socket，每條連線在自己的執行緒讀取；不會返回
+func runSocketServer(path: String) -> Never {
+    signal(SIGPIPE, SIG_IGN)  // client 斷線時寫入失敗不應結束 helper
+    try? FileManager.default.createDirectory(
+        atPath: (path as NSString).deletingLastPathComponent, withIntermediateDirectories: true)
+    unlink(path)
+
+    let listener = socket(AF_UNIX, SOCK_STREAM, 0)
+    var addr = sockaddr_un()
+    addr.sun_family = sa_family_t(AF_UNIX)
+    let maxLength = MemoryLayout.size(ofValue: addr.sun_path) - 1
+    guard listener >= 0, path.utf8.count <= maxLength else {
+        fputs("[helper] Invalid socket path: \(path)\n", stderr)
+        exit(1)
+    }
+    withUnsafeMutableBytes(of: &addr.sun_path) { raw in
+        raw.copyBytes(from: path.utf8)
+        raw[path.utf8.count] = 0
+    }
+    let bound = withUnsafePointer(to: &addr) {
+        $0.withMemoryRebound(to: sockaddr.self, capacity: 1) {
+            bind(listener, $0, socklen_t(MemoryLayout<sockaddr_un>.size))
+        }
+    }
+    guard bound == 0, listen(listener, 8) == 0 else {
+        fputs("[helper] Cannot listen on \(path): \(String(cString: strerror(errno)))\n", stderr)
+        exit(1)
+    }
+    fputs("[helper] Listening on \(path)\n", stderr)
+
+    while true {
+        let fd = accept(listener, nil, nil)
+        guard fd >= 0 else { continue }
+        Thread.detachNewThread {
+            let connection = Connection(handle: FileHandle(fileDescriptor: dup(fd), closeOnDealloc: true))
+            connections.add(connection)
+            serve(input: FileHandle(fileDescriptor: fd, closeOnDealloc: false), connection: connection)
+            // client 斷線：放棄它的請求，helper 繼續服務其他連線
+            connections.remove(connection)
+            connection.inflight.cancelAll()
+        }
     }
-    let args = json["args"] as? [String: Any] ?? [:]
-    await dispatch(cmd: cmd, args: args)
 }
+
+MainActor.assumeIsolated { startEventSources() }
+
+let arguments = CommandLine.arguments
+if let flag = arguments.firstIndex(of: "--socket") {
+    let next = arguments.index(after: flag)
+    let path = next < arguments.endIndex ? arguments[next] : defaultSocketPath()
+    Thread.detachNewThread { runSocketServer(path: path) }
+} else {
+    Thread.detachNewThread {
+        let connection = Connection(handle: .standardOutput)
+        connections.add(connection)
+        serve(input: .standardInput, connection: connection)
+        // stdin 關閉 = Rust 端已結束，helper 隨之退出
+        exit(0)
+    }
+}
+
+dispatchMain()